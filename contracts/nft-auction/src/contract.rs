@@ -1,12 +1,19 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
+use cosmwasm_std::{
+    to_binary, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
+};
 use cw2::set_contract_version;
 
 use crate::error::ContractError;
-use crate::execute::{execute_auction_nft, execute_bid_auction, execute_settle_auction};
+use crate::execute::{
+    execute_auction_nft, execute_bid_auction, execute_create_auction, execute_settle_auction,
+    execute_update_config, reply_remove_auctioned_listing, REMOVE_AUCTIONED_LISTING_REPLY_ID,
+};
 use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::query::{query_buyer_auctions, query_nft_auction, query_owner_auctions};
+use crate::query::{
+    query_auction_state, query_buyer_auctions, query_nft_auction, query_owner_auctions,
+};
 use crate::state::{Config, CONFIG};
 
 // version info for migration info
@@ -20,7 +27,11 @@ pub fn instantiate(
     info: MessageInfo,
     msg: InstantiateMsg,
 ) -> Result<Response, ContractError> {
-    let conf = Config { owner: msg.owner };
+    let conf = Config {
+        owner: msg.owner,
+        marketplace: None,
+        accepted_code_ids: vec![],
+    };
 
     CONFIG.save(deps.storage, &conf)?;
 
@@ -47,6 +58,26 @@ pub fn execute(
             execute_bid_auction(deps, _env, info, nft, bid_price)
         }
         ExecuteMsg::SettleAuction { nft } => execute_settle_auction(deps, _env, info, nft),
+        ExecuteMsg::CreateAuction {
+            seller,
+            nft,
+            auction_config,
+            code_id,
+        } => execute_create_auction(deps, _env, info, seller, nft, auction_config, code_id),
+        ExecuteMsg::UpdateConfig {
+            marketplace,
+            accepted_code_ids,
+        } => execute_update_config(deps, info, marketplace, accepted_code_ids),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(_deps: DepsMut, _env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        REMOVE_AUCTIONED_LISTING_REPLY_ID => reply_remove_auctioned_listing(msg),
+        id => Err(ContractError::CustomError {
+            val: format!("Unknown reply id: {}", id),
+        }),
     }
 }
 
@@ -59,6 +90,7 @@ pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response,
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     let api = deps.api;
     match msg {
+        QueryMsg::Config {} => to_binary(&CONFIG.load(deps.storage)?),
         QueryMsg::NftAuction {
             contract_address,
             token_id,
@@ -68,6 +100,15 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
             api.addr_validate(&contract_address)?,
             token_id,
         )?),
+        QueryMsg::AuctionState {
+            contract_address,
+            token_id,
+        } => to_binary(&query_auction_state(
+            deps,
+            env,
+            api.addr_validate(&contract_address)?,
+            token_id,
+        )?),
         QueryMsg::OwnerAuctions {
             owner,
             start_after_nft,
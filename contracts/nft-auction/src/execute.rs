@@ -1,165 +1,73 @@
+use crate::msg::MarketplaceExecuteMsg;
 use crate::state::{
-    consideration_item, contract, offer_item, order_key, Asset, AuctionConfigInput,
-    OrderComponents, PaymentAsset, NATIVE, NFT,
+    auction_consideration, auction_offer, contract, order_key, Asset, AuctionConfigInput,
+    AuctionOrder, PaymentAsset, CONFIG, NATIVE, NFT,
 };
 use crate::ContractError;
 use cosmwasm_std::{
-    coin, has_coins, to_binary, Addr, BankMsg, Coin, CosmosMsg, Decimal, DepsMut, Env, MessageInfo,
-    QueryRequest, Response, StdResult, Uint128, WasmMsg, WasmQuery,
-};
-use cw20::Cw20ExecuteMsg;
-use cw2981_royalties::{
-    msg::RoyaltiesInfoResponse, ExecuteMsg as Cw2981ExecuteMsg, QueryMsg as Cw2981QueryMsg,
+    coin, from_binary, has_coins, to_binary, Addr, BankMsg, Binary, Coin, Decimal, DepsMut, Env,
+    MessageInfo, QueryRequest, Reply, Response, StdResult, SubMsg, Uint128, WasmMsg, WasmQuery,
 };
+use cw2981_royalties::ExecuteMsg as Cw2981ExecuteMsg;
 use cw721::{Cw721QueryMsg, Expiration as Cw721Expiration};
+use marketplace_types::payment::{payment_with_royalty, NftPayment};
 
-// function to process payment transfer with royalty
-fn payment_with_royalty(
-    deps: &DepsMut,
-    nft_contract_address: &Addr,
-    nft_id: &str,
-    token: PaymentAsset,
-    sender: &Addr,
-    recipient: &Addr,
-) -> Vec<CosmosMsg> {
-    // create empty vector of CosmosMsg
-    let mut res_messages: Vec<CosmosMsg> = vec![];
-
-    // Extract information from token
-    let (is_native, token_info, amount) = match token {
-        PaymentAsset::Cw20 {
-            contract_address: token_address,
-            amount,
-        } => (false, token_address.to_string(), Uint128::from(amount)),
-        PaymentAsset::Native { denom, amount } => (true, denom, Uint128::from(amount)),
-    };
+// the reply id of the callback removing the listing of an auctioned nft from the marketplace
+pub const REMOVE_AUCTIONED_LISTING_REPLY_ID: u64 = 1;
 
-    // get cw2981 royalties info
-    let royalty_query_msg = Cw2981QueryMsg::Extension {
-        msg: cw2981_royalties::msg::Cw2981QueryMsg::RoyaltyInfo {
-            token_id: nft_id.into(),
-            sale_price: amount,
-        },
-    };
+pub fn execute_auction_nft(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    nft: NFT,
+    auction_config: AuctionConfigInput,
+) -> Result<Response, ContractError> {
+    create_auction(deps, env, info.sender, nft, auction_config, true)
+}
 
-    let royalty_info_rsp: Result<RoyaltiesInfoResponse, cosmwasm_std::StdError> =
-        deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-            contract_addr: nft_contract_address.to_string(),
-            msg: to_binary(&royalty_query_msg).unwrap(),
-        }));
-
-    let (creator, royalty_amount): (Option<Addr>, Option<Uint128>) = match royalty_info_rsp {
-        Ok(RoyaltiesInfoResponse {
-            address,
-            royalty_amount,
-        }) => {
-            if address.is_empty() || royalty_amount == Uint128::zero() {
-                (None, None)
-            } else {
-                (
-                    Some(deps.api.addr_validate(&address).unwrap()),
-                    Some(royalty_amount),
-                )
-            }
-        }
-        Err(_) => (None, None),
-    };
+pub fn execute_create_auction(
+    deps: DepsMut,
+    env: Env,
+    info: MessageInfo,
+    seller: String,
+    nft: NFT,
+    auction_config: Binary,
+    code_id: u32,
+) -> Result<Response, ContractError> {
+    // only the marketplace can create an auction for its seller
+    let conf = CONFIG.load(deps.storage)?;
+    if conf.marketplace != Some(info.sender) {
+        return Err(ContractError::Unauthorized {});
+    }
 
-    // there is no royalty, creator is the recipient, or royalty amount is 0
-    if creator.is_none()
-        || *creator.as_ref().unwrap() == *recipient
-        || royalty_amount.is_none()
-        || royalty_amount.unwrap().is_zero()
-    {
-        match &is_native {
-            false => {
-                // execute cw20 transfer msg from info.sender to recipient
-                let transfer_response = WasmMsg::Execute {
-                    contract_addr: deps.api.addr_validate(&token_info).unwrap().to_string(),
-                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                        owner: sender.to_string(),
-                        recipient: recipient.to_string(),
-                        amount,
-                    })
-                    .unwrap(),
-                    funds: vec![],
-                };
-                res_messages.push(transfer_response.into());
-            }
-            true => {
-                // transfer all funds to recipient
-                let transfer_response = BankMsg::Send {
-                    to_address: recipient.to_string(),
-                    amount: vec![Coin {
-                        denom: token_info,
-                        amount,
-                    }],
-                };
-                res_messages.push(transfer_response.into());
-            }
-        }
-    } else if let (Some(creator), Some(royalty_amount)) = (creator, royalty_amount) {
-        match &is_native {
-            false => {
-                // execute cw20 transfer transfer royalty to creator
-                let transfer_token_creator_response = WasmMsg::Execute {
-                    contract_addr: deps.api.addr_validate(&token_info).unwrap().to_string(),
-                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                        owner: sender.to_string(),
-                        recipient: creator.to_string(),
-                        amount: royalty_amount,
-                    })
-                    .unwrap(),
-                    funds: vec![],
-                };
-                res_messages.push(transfer_token_creator_response.into());
-
-                // execute cw20 transfer remaining funds to recipient
-                let transfer_token_seller_msg = WasmMsg::Execute {
-                    contract_addr: deps.api.addr_validate(&token_info).unwrap().to_string(),
-                    msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                        owner: sender.to_string(),
-                        recipient: recipient.to_string(),
-                        amount: amount - royalty_amount,
-                    })
-                    .unwrap(),
-                    funds: vec![],
-                };
-                res_messages.push(transfer_token_seller_msg.into());
-            }
-            true => {
-                // transfer royalty to creator
-                let transfer_token_creator_response = BankMsg::Send {
-                    to_address: creator.to_string(),
-                    amount: vec![Coin {
-                        denom: token_info.clone(),
-                        amount: royalty_amount,
-                    }],
-                };
-                res_messages.push(transfer_token_creator_response.into());
-
-                // transfer remaining funds to recipient
-                let transfer_token_seller_msg = BankMsg::Send {
-                    to_address: recipient.to_string(),
-                    amount: vec![Coin {
-                        denom: token_info,
-                        amount: amount - royalty_amount,
-                    }],
-                };
-                res_messages.push(transfer_token_seller_msg.into());
-            }
-        }
+    // the config was written for the code id registered in the marketplace,
+    // after a migration we decide to process it or reject it based on this code id
+    let current_code_id = deps
+        .querier
+        .query_wasm_contract_info(env.contract.address.clone())?
+        .code_id;
+    if u64::from(code_id) != current_code_id && !conf.accepted_code_ids.contains(&code_id) {
+        return Err(ContractError::CustomError {
+            val: ("Auction config code id is not accepted".to_string()),
+        });
     }
 
-    res_messages
+    let seller = deps.api.addr_validate(&seller)?;
+    let auction_config: AuctionConfigInput = from_binary(&auction_config)?;
+
+    // the nft has been transferred to this contract by the marketplace
+    create_auction(deps, env, seller, nft, auction_config, false)
 }
 
-pub fn execute_auction_nft(
+// create an auction of the nft of the seller,
+// the nft is transferred to this contract if it is not held by this contract yet
+fn create_auction(
     deps: DepsMut,
     env: Env,
-    info: MessageInfo,
+    seller: Addr,
     nft: NFT,
     auction_config: AuctionConfigInput,
+    transfer_nft: bool,
 ) -> Result<Response, ContractError> {
     // if the AuctionConfig is match with EnglishAuction
     match auction_config {
@@ -196,9 +104,14 @@ pub fn execute_auction_nft(
                             contract_addr: nft.contract_address.to_string(),
                             msg: to_binary(&query_owner_msg)?,
                         }));
+                    let expected_owner = if transfer_nft {
+                        &seller
+                    } else {
+                        &env.contract.address
+                    };
                     match owner_response {
                         Ok(owner) => {
-                            if owner.owner != info.sender {
+                            if owner.owner != *expected_owner {
                                 return Err(ContractError::Unauthorized {});
                             }
                         }
@@ -209,36 +122,46 @@ pub fn execute_auction_nft(
 
                     let mut res = Response::new();
                     // transfer nft to contract
-                    let transfer_nft_msg = WasmMsg::Execute {
-                        contract_addr: nft.contract_address.to_string(),
-                        msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
-                            recipient: env.contract.address.to_string(),
-                            token_id: token_id.clone(),
-                        })?,
-                        funds: vec![],
-                    };
-                    res = res.add_message(transfer_nft_msg);
+                    if transfer_nft {
+                        let transfer_nft_msg = WasmMsg::Execute {
+                            contract_addr: nft.contract_address.to_string(),
+                            msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                                recipient: env.contract.address.to_string(),
+                                token_id: token_id.clone(),
+                            })?,
+                            funds: vec![],
+                        };
+                        res = res.add_message(transfer_nft_msg);
+
+                        // the listing of the nft in the marketplace ends when the nft is escrowed
+                        // the auction is created even if the marketplace rejects the callback
+                        res = res.add_submessages(remove_listing_message(
+                            &deps,
+                            &nft.contract_address,
+                            &token_id,
+                        )?);
+                    }
 
                     // create offer item based on the nft
-                    let offer_item = offer_item(
+                    let offer_item = auction_offer(
                         &Asset::Nft(NFT {
                             contract_address: nft.contract_address.clone(),
                             token_id: Some(token_id.clone()),
                         }),
                         &1u128,
                         &1u128,
-                        &info.sender,
+                        &seller,
                     );
 
                     // create consideration item based on the auction config
-                    let consideration_item = consideration_item(
+                    let consideration_item = auction_consideration(
                         &Asset::Native(NATIVE {
                             denom: start_price.denom.clone(),
                             amount: start_price.amount.into(),
                         }),
                         &start_price.amount.into(),
                         &buyout_price.unwrap_or(0),
-                        &info.sender, // the recipient is the offerer by default
+                        &seller, // the recipient is the offerer by default
                     );
 
                     // create order key based on the marketplace address, nft.contract_address and nft.token_id
@@ -248,7 +171,7 @@ pub fn execute_auction_nft(
                     let percent = step_percentage.unwrap_or(5).to_string();
 
                     // create order
-                    let order = OrderComponents {
+                    let order = AuctionOrder {
                         order_id: order_key.clone(),
                         offer: vec![offer_item],
                         consideration: vec![consideration_item],
@@ -269,7 +192,7 @@ pub fn execute_auction_nft(
 
                     Ok(res.add_attributes([
                         ("method", "auction_nft"),
-                        ("seller", info.sender.as_str()),
+                        ("seller", seller.as_str()),
                         ("contract_address", nft.contract_address.as_str()),
                         ("token_id", token_id.as_str()),
                         ("start_price", start_price.amount.to_string().as_str()),
@@ -472,6 +395,13 @@ pub fn execute_settle_auction(
     };
     res = res.add_message(transfer_nft_msg);
 
+    // the listing of the auction in the marketplace ends when the auction is settled
+    res = res.add_submessages(remove_listing_message(
+        &deps,
+        &nft.contract_address,
+        nft.token_id.as_ref().unwrap(),
+    )?);
+
     // if the auction has no bid, stop the function here
     if order.consideration[0].recipient == order.offer[0].offerer {
         // delete order
@@ -489,16 +419,20 @@ pub fn execute_settle_auction(
 
     // send the native token to the offerer
 
-    let payment = PaymentAsset::from(order.consideration[0].item.clone());
+    let payment = PaymentAsset::try_from(order.consideration[0].item.clone())?;
 
+    // the bid is held by this contract
     let payment_messages = payment_with_royalty(
-        &deps,
-        &nft.contract_address,
-        nft.token_id.as_ref().unwrap(),
-        payment,
-        &env.contract.address,
-        &order.offer[0].offerer,
-    );
+        deps.as_ref(),
+        &NftPayment {
+            nft_contract_address: &nft.contract_address,
+            token_id: nft.token_id.as_ref().unwrap(),
+            token: &payment,
+            sender: None,
+            recipient: &order.offer[0].offerer,
+            protocol_fee: None,
+        },
+    )?;
 
     // add messages to response to execute
     res = res.add_messages(payment_messages);
@@ -515,3 +449,65 @@ pub fn execute_settle_auction(
         ("status", "success"),
     ]))
 }
+
+pub fn execute_update_config(
+    deps: DepsMut,
+    info: MessageInfo,
+    marketplace: Option<String>,
+    accepted_code_ids: Option<Vec<u32>>,
+) -> Result<Response, ContractError> {
+    // check if the sender is the owner
+    let mut conf = CONFIG.load(deps.storage)?;
+    if conf.owner != info.sender {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    if let Some(marketplace) = marketplace {
+        conf.marketplace = Some(deps.api.addr_validate(&marketplace)?);
+    }
+    if let Some(accepted_code_ids) = accepted_code_ids {
+        conf.accepted_code_ids = accepted_code_ids;
+    }
+
+    CONFIG.save(deps.storage, &conf)?;
+
+    Ok(Response::new()
+        .add_attribute("method", "update_config")
+        .add_attribute(
+            "marketplace",
+            conf.marketplace
+                .map(|addr| addr.to_string())
+                .unwrap_or_default(),
+        ))
+}
+
+// the callback removing the listing of the nft from the marketplace, if the marketplace is set
+// the failure of the callback is ignored, for example when this contract is not registered in the marketplace
+fn remove_listing_message(
+    deps: &DepsMut,
+    contract_address: &Addr,
+    token_id: &str,
+) -> StdResult<Option<SubMsg>> {
+    match CONFIG.load(deps.storage)?.marketplace {
+        Some(marketplace) => Ok(Some(SubMsg::reply_on_error(
+            WasmMsg::Execute {
+                contract_addr: marketplace.to_string(),
+                msg: to_binary(&MarketplaceExecuteMsg::RemoveAuctionedListing {
+                    contract_address: contract_address.to_string(),
+                    token_id: token_id.to_string(),
+                })?,
+                funds: vec![],
+            },
+            REMOVE_AUCTIONED_LISTING_REPLY_ID,
+        ))),
+        None => Ok(None),
+    }
+}
+
+// the marketplace failed to remove the listing of the nft, the auction is kept or settled anyway
+pub fn reply_remove_auctioned_listing(msg: Reply) -> Result<Response, ContractError> {
+    let error = msg.result.unwrap_err();
+    Ok(Response::new()
+        .add_attribute("method", "reply_remove_auctioned_listing")
+        .add_attribute("error", error))
+}
@@ -1,5 +1,5 @@
 use crate::msg::ExecuteMsg;
-use crate::state::{OrderComponents, NFT};
+use crate::state::{AuctionOrder, NFT};
 use crate::test_setup::env::{instantiate_contracts, NATIVE_DENOM, OWNER, USER_1, USER_2};
 
 use anyhow::Result as AnyResult;
@@ -525,7 +525,7 @@ mod bid_auction {
         };
 
         // get auction of nft
-        let res: OrderComponents = app
+        let res: AuctionOrder = app
             .wrap()
             .query_wasm_smart(Addr::unchecked(&marketplace_address), &query_msg)
             .unwrap();
@@ -558,7 +558,7 @@ mod bid_auction {
         };
 
         // get auction of nft
-        let res: OrderComponents = app
+        let res: AuctionOrder = app
             .wrap()
             .query_wasm_smart(Addr::unchecked(&marketplace_address), &query_msg)
             .unwrap();
@@ -1296,9 +1296,90 @@ mod settle_auction {
 
 mod query_auction {
     use crate::msg::AuctionsResponse;
+    use crate::state::AuctionState;
 
     use super::*;
 
+    #[test]
+    fn query_auction_state_of_nft() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft(&mut app, TOKEN_ID_1, OWNER, cw2981_address.clone());
+        approval_token(
+            &mut app,
+            OWNER,
+            TOKEN_ID_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+        );
+
+        let query_state_msg = QueryMsg::AuctionState {
+            contract_address: cw2981_address.clone(),
+            token_id: TOKEN_ID_1.to_string(),
+        };
+
+        // the nft is not auctioned
+        let res: Option<AuctionState> = app
+            .wrap()
+            .query_wasm_smart(marketplace_address.clone(), &query_state_msg)
+            .unwrap();
+        assert_eq!(res, None);
+
+        let end_time = Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000));
+        let auction_config = AuctionConfigInput::EnglishAuction {
+            start_price: coin(START_PRICE, NATIVE_DENOM),
+            step_percentage: Some(STEP_PERCENTAGE),
+            buyout_price: None,
+            start_time: None,
+            end_time,
+        };
+        let res = create_auction(
+            &mut app,
+            Some(TOKEN_ID_1.to_string()),
+            OWNER,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+            auction_config,
+        );
+        assert!(res.is_ok());
+
+        // the auction has no bidder, the current price is the start price
+        let res: Option<AuctionState> = app
+            .wrap()
+            .query_wasm_smart(marketplace_address.clone(), &query_state_msg)
+            .unwrap();
+        assert_eq!(
+            res,
+            Some(AuctionState {
+                seller: Addr::unchecked(OWNER),
+                current_price: coin(START_PRICE, NATIVE_DENOM),
+                highest_bidder: None,
+                end_time,
+            })
+        );
+
+        // USER_1 bid auction
+        let res = bid_auction(
+            &mut app,
+            Some(TOKEN_ID_1.to_string()),
+            USER_1,
+            cw2981_address,
+            marketplace_address.clone(),
+            START_PRICE,
+            Some(START_PRICE),
+        );
+        assert!(res.is_ok());
+
+        let res: Option<AuctionState> = app
+            .wrap()
+            .query_wasm_smart(marketplace_address, &query_state_msg)
+            .unwrap();
+        assert_eq!(res.unwrap().highest_bidder, Some(Addr::unchecked(USER_1)));
+    }
+
     #[test]
     fn query_all_auctions_of_owner() {
         // get integration test app and contracts
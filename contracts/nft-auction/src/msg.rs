@@ -1,7 +1,7 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary};
 
-use crate::state::{AuctionConfigInput, OrderComponents, NFT};
+use crate::state::{AuctionConfigInput, AuctionOrder, AuctionState, Config, NFT};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -24,6 +24,28 @@ pub enum ExecuteMsg {
     SettleAuction {
         nft: NFT,
     },
+    // the marketplace creates an auction of a nft it has transferred to this contract,
+    // the config is rejected if it was written for a code id which is not accepted
+    CreateAuction {
+        seller: String,
+        nft: NFT,
+        auction_config: Binary,
+        code_id: u32,
+    },
+    // update the marketplace and the accepted code ids, only the owner can update them
+    UpdateConfig {
+        marketplace: Option<String>,
+        accepted_code_ids: Option<Vec<u32>>,
+    },
+}
+
+// the message executed on the marketplace to remove the listing of a nft escrowed by this contract
+#[cw_serde]
+pub enum MarketplaceExecuteMsg {
+    RemoveAuctionedListing {
+        contract_address: String,
+        token_id: String,
+    },
 }
 
 #[cw_serde]
@@ -32,12 +54,20 @@ pub struct MigrateMsg {}
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
+    #[returns(Config)]
+    Config {},
     // get auction of a nft
-    #[returns(OrderComponents)]
+    #[returns(AuctionOrder)]
     NftAuction {
         contract_address: String,
         token_id: String,
     },
+    // get the state of the auction of a nft, none if the nft is not auctioned
+    #[returns(Option<AuctionState>)]
+    AuctionState {
+        contract_address: String,
+        token_id: String,
+    },
     // get all auctions of owner
     #[returns(AuctionsResponse)]
     OwnerAuctions {
@@ -56,5 +86,5 @@ pub enum QueryMsg {
 
 #[cw_serde]
 pub struct AuctionsResponse {
-    pub auctions: Vec<OrderComponents>,
+    pub auctions: Vec<AuctionOrder>,
 }
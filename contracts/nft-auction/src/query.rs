@@ -1,9 +1,9 @@
-use cosmwasm_std::{Addr, Deps, Env, Order, StdResult};
+use cosmwasm_std::{coin, Addr, Deps, Env, Order, StdError, StdResult};
 use cw_storage_plus::Bound;
 
 use crate::{
     msg::AuctionsResponse,
-    state::{contract, order_key, OrderComponents, OrderKey, NFT},
+    state::{contract, order_key, Asset, AuctionOrder, AuctionState, OrderKey, NATIVE, NFT},
 };
 
 // query all auctions of a specific nft
@@ -12,7 +12,7 @@ pub fn query_nft_auction(
     env: Env,
     contract_address: Addr,
     token_id: String,
-) -> StdResult<OrderComponents> {
+) -> StdResult<AuctionOrder> {
     // create order key based on the offerer address, nft.contract_address and nft.token_id
     let order_key = order_key(&env.contract.address, &contract_address, &token_id);
 
@@ -23,6 +23,35 @@ pub fn query_nft_auction(
     Ok(order)
 }
 
+// query the state of the auction of a specific nft
+pub fn query_auction_state(
+    deps: Deps,
+    env: Env,
+    contract_address: Addr,
+    token_id: String,
+) -> StdResult<Option<AuctionState>> {
+    let order_key = order_key(&env.contract.address, &contract_address, &token_id);
+    let order = match contract().auctions.may_load(deps.storage, order_key)? {
+        Some(order) => order,
+        None => return Ok(None),
+    };
+
+    // the recipient of the consideration is the offerer until the first bid
+    let seller = order.offer[0].offerer.clone();
+    let bidder = order.consideration[0].recipient.clone();
+    let current_price = match &order.consideration[0].item {
+        Asset::Native(NATIVE { denom, amount }) => coin(*amount, denom),
+        _ => return Err(StdError::generic_err("Invalid consideration item")),
+    };
+
+    Ok(Some(AuctionState {
+        highest_bidder: (bidder != seller).then_some(bidder),
+        seller,
+        current_price,
+        end_time: order.end_time,
+    }))
+}
+
 // query all auctions of a specific owner
 pub fn query_owner_auctions(
     deps: Deps,
@@ -1,11 +1,26 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, BlockInfo, Coin};
+use cosmwasm_std::{Addr, Coin};
 use cw721::Expiration;
 use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex};
 
+// the assets and the order keys are shared with the marketplace contract
+pub use marketplace_types::asset::{order_key, Asset, OrderKey, PaymentAsset, CW20, NATIVE, NFT};
+// the auctions and their state are shared with the marketplace contract
+pub use marketplace_types::auction::{
+    auction_consideration, auction_offer, AuctionConsideration, AuctionOffer, AuctionOrder,
+    AuctionState,
+};
+
 #[cw_serde]
 pub struct Config {
     pub owner: Addr,
+    // the marketplace which can create auctions for its sellers
+    #[serde(default)]
+    pub marketplace: Option<Addr>,
+    // the configs routed by the marketplace are tagged with the code id they were written for,
+    // the configs of the current code id and of these code ids are accepted
+    #[serde(default)]
+    pub accepted_code_ids: Vec<u32>,
 }
 
 // New enum data structure for AuctionConfig input only
@@ -29,156 +44,35 @@ pub struct EnglishAuctionConfig {
     end_time: Expiration,
 }
 
-#[cw_serde]
-pub struct NFT {
-    pub contract_address: Addr,
-    pub token_id: Option<String>,
-}
-
-#[cw_serde]
-pub struct CW20 {
-    pub contract_address: Addr,
-    pub amount: u128,
-}
-
-#[cw_serde]
-pub struct NATIVE {
-    pub denom: String,
-    pub amount: u128,
-}
-
-#[cw_serde]
-pub enum Asset {
-    Nft(NFT),
-    Native(NATIVE),
-    Cw20(CW20),
-}
-
-#[cw_serde]
-pub enum PaymentAsset {
-    Native {
-        denom: String,
-        amount: u128,
-    },
-    Cw20 {
-        contract_address: Addr,
-        amount: u128,
-    },
-}
-
-impl From<Asset> for PaymentAsset {
-    fn from(asset: Asset) -> Self {
-        match asset {
-            Asset::Native(NATIVE { denom, amount }) => PaymentAsset::Native { denom, amount },
-            Asset::Cw20(CW20 {
-                contract_address,
-                amount,
-            }) => PaymentAsset::Cw20 {
-                contract_address,
-                amount,
-            },
-            _ => panic!("Asset is not a payment asset"),
-        }
-    }
-}
-
-#[cw_serde]
-pub struct Offer {
-    pub item: Asset,
-    pub start_amount: u128,
-    pub end_amount: u128,
-    pub offerer: Addr,
-}
-
-pub fn offer_item(item: &Asset, start_amount: &u128, end_amount: &u128, offerer: &Addr) -> Offer {
-    Offer {
-        item: item.clone(),
-        start_amount: *start_amount,
-        end_amount: *end_amount,
-        offerer: offerer.clone(),
-    }
-}
-
-#[cw_serde]
-pub struct Consideration {
-    pub item: Asset,
-    pub start_amount: u128,
-    pub end_amount: u128,
-    pub recipient: Addr,
-}
-
-pub fn consideration_item(
-    item: &Asset,
-    start_amount: &u128,
-    end_amount: &u128,
-    recipient: &Addr,
-) -> Consideration {
-    Consideration {
-        item: item.clone(),
-        start_amount: *start_amount,
-        end_amount: *end_amount,
-        recipient: recipient.clone(),
-    }
-}
-
-// the OrderKey includes the address and id of NFT
-// !DO NOT change the order of the fields
-pub type OrderKey = (Addr, Addr, String);
-
-pub fn order_key(user_address: &Addr, contract_address: &Addr, token_id: &str) -> OrderKey {
-    (
-        user_address.clone(),
-        contract_address.clone(),
-        token_id.to_owned(),
-    )
-}
-
-#[cw_serde]
-pub struct OrderComponents {
-    pub order_id: OrderKey,
-    pub offer: Vec<Offer>,
-    pub consideration: Vec<Consideration>,
-    pub start_time: Expiration,
-    pub end_time: Expiration,
-    pub config: String,
-}
-
-impl OrderComponents {
-    // expired is when a listing has passed the end_time
-    pub fn is_expired(&self, block_info: &BlockInfo) -> bool {
-        self.end_time.is_expired(block_info)
-    }
-}
-
 pub struct AuctionIndexes<'a> {
-    pub owners: MultiIndex<'a, Addr, OrderComponents, OrderKey>,
-    pub nfts: MultiIndex<'a, (Addr, String), OrderComponents, OrderKey>,
-    pub buyers: MultiIndex<'a, Addr, OrderComponents, OrderKey>,
+    pub owners: MultiIndex<'a, Addr, AuctionOrder, OrderKey>,
+    pub nfts: MultiIndex<'a, (Addr, String), AuctionOrder, OrderKey>,
+    pub buyers: MultiIndex<'a, Addr, AuctionOrder, OrderKey>,
 }
 
-impl<'a> IndexList<OrderComponents> for AuctionIndexes<'a> {
+impl<'a> IndexList<AuctionOrder> for AuctionIndexes<'a> {
     // this method returns a list of all indexes
-    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<OrderComponents>> + '_> {
-        let v: Vec<&dyn Index<OrderComponents>> = vec![&self.owners, &self.nfts, &self.buyers];
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<AuctionOrder>> + '_> {
+        let v: Vec<&dyn Index<AuctionOrder>> = vec![&self.owners, &self.nfts, &self.buyers];
         Box::new(v.into_iter())
     }
 }
 
 // helper function create a IndexedMap for listings
-pub fn auctions<'a>() -> IndexedMap<'a, OrderKey, OrderComponents, AuctionIndexes<'a>> {
+pub fn auctions<'a>() -> IndexedMap<'a, OrderKey, AuctionOrder, AuctionIndexes<'a>> {
     let indexes = AuctionIndexes {
         owners: MultiIndex::new(
-            |_pk: &[u8], l: &OrderComponents| (l.offer[0].offerer.clone()),
+            |_pk: &[u8], l: &AuctionOrder| (l.offer[0].offerer.clone()),
             "auctions",
             "auctions__owner_address",
         ),
         nfts: MultiIndex::new(
-            |_pk: &[u8], l: &OrderComponents| (l.order_id.1.clone(), l.order_id.2.clone()),
+            |_pk: &[u8], l: &AuctionOrder| (l.order_id.1.clone(), l.order_id.2.clone()),
             "auctions",
             "auctions__nft_identifier",
         ),
         buyers: MultiIndex::new(
-            |_pk: &[u8], l: &OrderComponents| (l.consideration[0].recipient.clone()),
+            |_pk: &[u8], l: &AuctionOrder| (l.consideration[0].recipient.clone()),
             "auctions",
             "auctions__buyer_address",
         ),
@@ -187,7 +81,7 @@ pub fn auctions<'a>() -> IndexedMap<'a, OrderKey, OrderComponents, AuctionIndexe
 }
 
 pub struct AuctionContract<'a> {
-    pub auctions: IndexedMap<'a, OrderKey, OrderComponents, AuctionIndexes<'a>>,
+    pub auctions: IndexedMap<'a, OrderKey, AuctionOrder, AuctionIndexes<'a>>,
 }
 
 // impl default for MarketplaceContract
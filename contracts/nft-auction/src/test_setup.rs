@@ -10,6 +10,7 @@ pub mod env {
 
     use crate::contract::{
         execute as AuctionExecute, instantiate as AuctionInstantiate, query as AuctionQuery,
+        reply as AuctionReply,
     };
     use crate::msg::InstantiateMsg;
 
@@ -73,7 +74,8 @@ pub mod env {
     }
 
     fn nft_auction_contract_template() -> Box<dyn Contract<Empty>> {
-        let contract = ContractWrapper::new(AuctionExecute, AuctionInstantiate, AuctionQuery);
+        let contract = ContractWrapper::new(AuctionExecute, AuctionInstantiate, AuctionQuery)
+            .with_reply(AuctionReply);
         Box::new(contract)
     }
 
@@ -1,11 +1,19 @@
 #[cfg(not(feature = "library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
+use cosmwasm_std::{
+    to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Reply, Response, StdResult,
+};
+use cw2::{get_contract_version, set_contract_version};
 
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, MigrateMsg, QueryMsg};
-use crate::state::{contract, Config};
+use crate::msg::{
+    ExecuteMsg, InstantiateMsg, ListNftItem, MigrateMsg, NftOffer, QueryMsg, SwapOffer,
+};
+use crate::order_state::{order_key, trait_offer_token_id, NFT};
+use crate::state::{
+    contract, AuctionContract, Config, RegisteredCollection, RegistryMode, MAX_SWEEP_ITEMS,
+    SWEEP_ITEM_REPLY_ID,
+};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:nft-marketplace";
@@ -22,6 +30,9 @@ pub fn instantiate(
     let conf = Config {
         owner: msg.owner,
         vaura_address: Addr::unchecked("aura0"),
+        protocol_fee_bps: 0,
+        fee_collector: None,
+        registry_mode: RegistryMode::default(),
     };
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
     contract().config.save(deps.storage, &conf)?;
@@ -44,13 +55,19 @@ pub fn execute(
             contract_address,
             token_id,
             auction_config,
+            escrow,
+            reserved_buyers,
         } => contract().execute_list_nft(
             deps,
             _env,
             info,
-            api.addr_validate(&contract_address)?,
-            token_id,
-            auction_config,
+            ListNftItem {
+                contract_address,
+                token_id,
+                auction_config,
+                escrow,
+                reserved_buyers,
+            },
         ),
         ExecuteMsg::Buy {
             contract_address,
@@ -62,6 +79,36 @@ pub fn execute(
             api.addr_validate(&contract_address)?,
             token_id,
         ),
+        ExecuteMsg::ListNfts { items } => contract().execute_list_nfts(deps, _env, info, items),
+        ExecuteMsg::BuyNfts { items } => contract().execute_buy_nfts(deps, _env, info, items),
+        ExecuteMsg::SweepNfts { items } => contract().execute_sweep_nfts(deps, _env, info, items),
+        ExecuteMsg::SweepItem {
+            buyer,
+            contract_address,
+            token_id,
+        } => contract().execute_sweep_item(
+            deps,
+            _env,
+            info,
+            api.addr_validate(&buyer)?,
+            api.addr_validate(&contract_address)?,
+            token_id,
+        ),
+        ExecuteMsg::FinalizeSweep {} => contract().execute_finalize_sweep(deps, _env, info),
+        ExecuteMsg::PruneListings { listings } => {
+            contract().execute_prune_listings(deps, _env, info, listings)
+        }
+        ExecuteMsg::ListBundle {
+            nfts,
+            price,
+            end_time,
+        } => contract().execute_list_bundle(deps, _env, info, nfts, price, end_time),
+        ExecuteMsg::BuyBundle { bundle_id } => {
+            contract().execute_buy_bundle(deps, _env, info, bundle_id)
+        }
+        ExecuteMsg::CancelBundle { bundle_id } => {
+            contract().execute_cancel_bundle(deps, _env, info, bundle_id)
+        }
         ExecuteMsg::Cancel {
             contract_address,
             token_id,
@@ -75,8 +122,21 @@ pub fn execute(
         ExecuteMsg::OfferNft {
             nft,
             funds_amount,
+            end_funds_amount,
+            start_time,
             end_time,
-        } => contract().execute_offer_nft(deps, _env, info, nft, funds_amount, end_time),
+        } => contract().execute_offer_nft(
+            deps,
+            _env,
+            info,
+            NftOffer {
+                nft,
+                funds_amount,
+                end_funds_amount,
+                start_time,
+                end_time,
+            },
+        ),
         ExecuteMsg::AcceptNftOffer {
             offerer,
             nft,
@@ -89,20 +149,210 @@ pub fn execute(
             nft,
             funds_amount,
         ),
+        ExecuteMsg::OfferTraitNft {
+            nft,
+            funds_amount,
+            end_time,
+        } => contract().execute_offer_trait_nft(deps, _env, info, nft, funds_amount, end_time),
+        ExecuteMsg::AcceptTraitOffer {
+            offerer,
+            nft,
+            token_id,
+            funds_amount,
+        } => contract().execute_accept_trait_offer(
+            deps,
+            _env,
+            info,
+            order_key(
+                &api.addr_validate(&offerer)?,
+                &nft.contract_address,
+                &trait_offer_token_id(&nft.traits),
+            ),
+            token_id,
+            funds_amount,
+        ),
+        ExecuteMsg::OfferSwap {
+            nft,
+            offered_nfts,
+            funds_amount,
+            end_time,
+        } => contract().execute_offer_swap(
+            deps,
+            _env,
+            info,
+            SwapOffer {
+                nft,
+                offered_nfts,
+                funds_amount,
+                end_time,
+            },
+        ),
+        ExecuteMsg::AcceptSwapOffer {
+            offerer,
+            nft,
+            offered_nfts,
+            funds_amount,
+        } => contract().execute_accept_swap_offer(
+            deps,
+            _env,
+            info,
+            order_key(
+                &api.addr_validate(&offerer)?,
+                &nft.contract_address,
+                nft.token_id.as_deref().unwrap_or_default(),
+            ),
+            offered_nfts,
+            funds_amount,
+        ),
+        ExecuteMsg::AcceptCollectionOffer {
+            offerer,
+            nft,
+            funds_amount,
+        } => contract().execute_accept_collection_offer(
+            deps,
+            _env,
+            info,
+            api.addr_validate(&offerer)?,
+            nft,
+            funds_amount,
+        ),
+        ExecuteMsg::FulfillSignedOrder {
+            order,
+            signature,
+            public_key,
+        } => {
+            contract().execute_fulfill_signed_order(deps, _env, info, order, signature, public_key)
+        }
+        ExecuteMsg::IncrementCounter {} => contract().execute_increment_counter(deps, _env, info),
         ExecuteMsg::CancelOffer { nfts } => contract().execute_cancel_offer(deps, _env, info, nfts),
+        ExecuteMsg::CancelSwapOffer { nfts } => {
+            contract().execute_cancel_swap_offer(deps, _env, info, nfts)
+        }
+        ExecuteMsg::CancelTraitOffer {
+            contract_address,
+            traits,
+        } => contract().execute_cancel_trait_offer(
+            deps,
+            _env,
+            info,
+            api.addr_validate(&contract_address)?,
+            traits,
+        ),
         ExecuteMsg::EditVauraToken { token_address } => {
             contract().execute_edit_vaura_token(deps, _env, info, token_address)
         }
+        ExecuteMsg::Receive(receive_msg) => {
+            contract().execute_receive(deps, _env, info, receive_msg)
+        }
+        ExecuteMsg::AddPaymentToken { token_address } => {
+            contract().execute_add_payment_token(deps, _env, info, token_address)
+        }
+        ExecuteMsg::RemovePaymentToken { token_address } => {
+            contract().execute_remove_payment_token(deps, _env, info, token_address)
+        }
+        ExecuteMsg::UpdateConfig {
+            protocol_fee_bps,
+            fee_collector,
+            registry_mode,
+        } => contract().execute_update_config(
+            deps,
+            info,
+            protocol_fee_bps,
+            fee_collector,
+            registry_mode,
+        ),
+        ExecuteMsg::RebuildIndexes { start_after, limit } => {
+            contract().execute_rebuild_indexes(deps, info, start_after, limit)
+        }
+        ExecuteMsg::RegisterCollection {
+            contract_address,
+            name,
+            description,
+            status,
+        } => contract().execute_register_collection(
+            deps,
+            info,
+            RegisteredCollection {
+                contract_address: api.addr_validate(&contract_address)?,
+                name,
+                description,
+                status,
+            },
+        ),
+        ExecuteMsg::UnregisterCollection { contract_address } => contract()
+            .execute_unregister_collection(deps, info, api.addr_validate(&contract_address)?),
+        ExecuteMsg::AddAuctionContract {
+            contract_address,
+            code_id,
+            name,
+        } => contract().execute_add_auction_contract(
+            deps,
+            info,
+            AuctionContract {
+                contract_address: api.addr_validate(&contract_address)?,
+                code_id,
+                name,
+            },
+        ),
+        ExecuteMsg::RemoveAuctionContract { contract_address } => contract()
+            .execute_remove_auction_contract(deps, info, api.addr_validate(&contract_address)?),
+        ExecuteMsg::ListNftInAuction {
+            contract_address,
+            token_id,
+            auction_type,
+            auction_config,
+        } => contract().execute_list_nft_in_auction(
+            deps,
+            _env,
+            info,
+            NFT {
+                contract_address: api.addr_validate(&contract_address)?,
+                token_id: Some(token_id),
+            },
+            auction_type,
+            auction_config,
+        ),
+        ExecuteMsg::RemoveAuctionedListing {
+            contract_address,
+            token_id,
+        } => contract().execute_remove_auctioned_listing(
+            deps,
+            info,
+            api.addr_validate(&contract_address)?,
+            token_id,
+        ),
+    }
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn reply(deps: DepsMut, env: Env, msg: Reply) -> Result<Response, ContractError> {
+    match msg.id {
+        // the buy of an item of a sweep failed
+        id if (SWEEP_ITEM_REPLY_ID..SWEEP_ITEM_REPLY_ID + MAX_SWEEP_ITEMS as u64).contains(&id) => {
+            contract().reply_sweep_item(deps, env, msg)
+        }
+        id => Err(ContractError::CustomError {
+            val: format!("Unknown reply id: {}", id),
+        }),
     }
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn migrate(_deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
-    Ok(Response::default())
+pub fn migrate(deps: DepsMut, _env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    let version = get_contract_version(deps.storage)?;
+    if version.contract != CONTRACT_NAME {
+        return Err(ContractError::CustomError {
+            val: ("Cannot migrate from a different contract".to_string()),
+        });
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // the indexes of the stored listings and offers are rebuilt by the rebuild_indexes message
+    Ok(Response::new().add_attribute("method", "migrate"))
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     let api = deps.api;
     match msg {
         // get config
@@ -117,6 +367,44 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after,
             limit,
         )?),
+        QueryMsg::RegisteredCollection { contract_address } => to_binary(
+            &contract()
+                .collections
+                .load(deps.storage, api.addr_validate(&contract_address)?)?,
+        ),
+        QueryMsg::RegisteredCollections {
+            status,
+            start_after,
+            limit,
+        } => {
+            to_binary(&contract().query_registered_collections(deps, status, start_after, limit)?)
+        }
+        QueryMsg::AuctionContracts { start_after, limit } => {
+            to_binary(&contract().query_auction_contracts(deps, start_after, limit)?)
+        }
+        QueryMsg::Listings {
+            contract_address,
+            filter,
+            start_after,
+            limit,
+        } => to_binary(&contract().query_listings(
+            deps,
+            env,
+            api.addr_validate(&contract_address)?,
+            filter,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::ListingsBySeller {
+            seller,
+            start_after_nft,
+            limit,
+        } => to_binary(&contract().query_listings_by_seller(
+            deps,
+            api.addr_validate(&seller)?,
+            start_after_nft,
+            limit,
+        )?),
         QueryMsg::Listing {
             contract_address,
             token_id,
@@ -125,6 +413,33 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             api.addr_validate(&contract_address)?,
             token_id,
         )?),
+        QueryMsg::ListingPrice {
+            contract_address,
+            token_id,
+        } => to_binary(&contract().query_listing_price(
+            deps,
+            env,
+            api.addr_validate(&contract_address)?,
+            token_id,
+        )?),
+        QueryMsg::ValidateListing {
+            contract_address,
+            token_id,
+        } => to_binary(&contract().query_validate_listing(
+            deps,
+            env,
+            api.addr_validate(&contract_address)?,
+            token_id,
+        )?),
+        QueryMsg::Bundle { bundle_id } => {
+            to_binary(&contract().bundles.load(deps.storage, bundle_id)?)
+        }
+        QueryMsg::Bundles { start_after, limit } => {
+            to_binary(&contract().query_bundles(deps, start_after, limit)?)
+        }
+        QueryMsg::PaymentTokens { start_after, limit } => {
+            to_binary(&contract().query_payment_tokens(deps, start_after, limit)?)
+        }
         QueryMsg::Offer {
             contract_address,
             token_id,
@@ -147,6 +462,28 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after_offerer,
             limit,
         )?),
+        QueryMsg::SwapOffers {
+            contract_address,
+            token_id,
+            start_after_offerer,
+            limit,
+        } => to_binary(&contract().query_swap_offers(
+            deps,
+            api.addr_validate(&contract_address)?,
+            token_id,
+            start_after_offerer,
+            limit,
+        )?),
+        QueryMsg::CollectionOffers {
+            contract_address,
+            start_after,
+            limit,
+        } => to_binary(&contract().query_collection_offers(
+            deps,
+            api.addr_validate(&contract_address)?,
+            start_after,
+            limit,
+        )?),
         QueryMsg::UserOffers {
             offerer,
             start_after_nft,
@@ -157,5 +494,68 @@ pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
             start_after_nft,
             limit,
         )?),
+        QueryMsg::UserSwapOffers {
+            offerer,
+            start_after_nft,
+            limit,
+        } => to_binary(&contract().query_user_swap_offers(
+            deps,
+            api.addr_validate(&offerer)?,
+            start_after_nft,
+            limit,
+        )?),
+        QueryMsg::OffersReceived {
+            owner,
+            collections,
+            start_after_nft,
+            limit,
+        } => to_binary(
+            &contract().query_offers_received(
+                deps,
+                env,
+                api.addr_validate(&owner)?,
+                collections
+                    .iter()
+                    .map(|collection| api.addr_validate(collection))
+                    .collect::<StdResult<Vec<_>>>()?,
+                start_after_nft,
+                limit,
+            )?,
+        ),
+        QueryMsg::Counter { user } => {
+            to_binary(&contract().query_counter(deps, api.addr_validate(&user)?)?)
+        }
+        QueryMsg::SignedOrderHash { order } => {
+            to_binary(&contract().signed_order_hash(&env, &order)?)
+        }
+        QueryMsg::SaleHistory {
+            contract_address,
+            token_id,
+            start_after,
+            limit,
+        } => to_binary(&contract().query_sale_history(
+            deps,
+            api.addr_validate(&contract_address)?,
+            token_id,
+            start_after,
+            limit,
+        )?),
+        QueryMsg::CollectionStats {
+            contract_address,
+            start_after,
+        } => to_binary(&contract().query_collection_stats(
+            deps,
+            env,
+            api.addr_validate(&contract_address)?,
+            start_after,
+        )?),
+        QueryMsg::LastSale {
+            contract_address,
+            token_id,
+        } => to_binary(&contract().query_last_sale(
+            deps,
+            api.addr_validate(&contract_address)?,
+            token_id,
+        )?),
     }
 }
@@ -1,20 +1,35 @@
 use crate::order_state::{
-    consideration_item, offer_item, order_key, Asset, ItemType, OrderComponents, OrderType,
-    PaymentAsset, CW20, NFT,
+    consideration_item, offer_item, order_key, trait_offer_token_id, Asset, ItemType, NftTrait,
+    OfferIndexes, OfferItem, OrderComponents, OrderKey, OrderType, PaymentAsset, TraitNFT,
+    COLLECTION_OFFER_TOKEN_ID, CW20, NFT,
 };
 use crate::{
-    state::{listing_key, AuctionConfig, Listing, MarketplaceContract},
+    msg::{
+        AuctionContractExecuteMsg, BuyNftItem, Cw20HookMsg, ExecuteMsg, ListNftItem, NftOffer,
+        RebuildIndexesCursor, RebuildIndexesResponse, SwapOffer,
+    },
+    state::{
+        listing_key, AuctionConfig, AuctionContract, Bundle, BundleItem, CollectionStatus, Listing,
+        MarketplaceContract, RegisteredCollection, RegistryMode, Sale, SaleKind, Sweep, SweepItem,
+        MAX_COLLECTION_SALES, MAX_NFT_SALES, MAX_PROTOCOL_FEE_BPS, MAX_SWEEP_ITEMS,
+        SWEEP_ITEM_REPLY_ID,
+    },
     ContractError,
 };
+use bech32::{ToBase32, Variant};
 use cosmwasm_std::{
-    to_binary, Addr, BankMsg, Coin, CosmosMsg, DepsMut, Env, MessageInfo, QueryRequest, Response,
-    StdResult, Uint128, WasmMsg, WasmQuery,
-};
-use cw20::{AllowanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
-use cw2981_royalties::{
-    msg::RoyaltiesInfoResponse, ExecuteMsg as Cw2981ExecuteMsg, QueryMsg as Cw2981QueryMsg,
+    from_binary, to_binary, Addr, BankMsg, Binary, Coin, CosmosMsg, Deps, DepsMut, Env, Event,
+    MessageInfo, Order, QueryRequest, Reply, Response, StdError, StdResult, Storage, SubMsg,
+    Uint128, WasmMsg, WasmQuery,
 };
+use cw20::{AllowanceResponse, Cw20QueryMsg, Cw20ReceiveMsg};
+use cw2981_royalties::{ExecuteMsg as Cw2981ExecuteMsg, Extension as Cw2981Extension, Metadata};
 use cw721::{Cw721QueryMsg, Expiration as Cw721Expiration};
+use cw_storage_plus::{Bound, IndexedMap};
+use marketplace_types::payment::{payment_with_royalty, NftPayment};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
 
 impl MarketplaceContract<'static> {
     pub fn validate_auction_config(&self, auction_config: &AuctionConfig) -> bool {
@@ -38,21 +53,217 @@ impl MarketplaceContract<'static> {
                 }
                 true
             }
+            AuctionConfig::DutchAuction {
+                start_price,
+                end_price,
+                start_time,
+                end_time,
+            } => {
+                // the price must decline to a non-zero end price of the same denom
+                if start_price.denom != end_price.denom
+                    || end_price.amount.is_zero()
+                    || start_price.amount <= end_price.amount
+                {
+                    return false;
+                }
+                // the price is calculated based on the block time, so both times must be AtTime
+                match (start_time, end_time) {
+                    (Cw721Expiration::AtTime(start), Cw721Expiration::AtTime(end)) => start < end,
+                    _ => false,
+                }
+            }
+            AuctionConfig::Cw20FixedPrice {
+                price,
+                start_time,
+                end_time,
+            } => {
+                if price.amount == 0 {
+                    return false;
+                }
+                // if start_time or end_time is not set, we don't need to check
+                if start_time.is_some()
+                    && end_time.is_some()
+                    && start_time.unwrap() >= end_time.unwrap()
+                {
+                    return false;
+                }
+                true
+            }
+            AuctionConfig::EnglishAuction {
+                start_price,
+                step_percentage,
+                buyout_price,
+                start_time,
+                end_time,
+            } => {
+                if start_price.amount.is_zero()
+                    || step_percentage.map_or(false, |step| step > 100)
+                    || buyout_price.map_or(false, |buyout| buyout < start_price.amount.u128())
+                {
+                    return false;
+                }
+                // the auction must end, after it starts
+                match end_time {
+                    Cw721Expiration::AtTime(_) => {
+                        start_time.map_or(true, |start| start < *end_time)
+                    }
+                    _ => false,
+                }
+            }
         }
     }
 
     pub fn execute_list_nft(
-        self,
+        &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        contract_address: Addr,
-        token_id: String,
-        auction_config: AuctionConfig,
+        item: ListNftItem,
     ) -> Result<Response, ContractError> {
+        let contract_address = deps.api.addr_validate(&item.contract_address)?;
+        let token_id = item.token_id;
+        let auction_config = item.auction_config;
+
+        self.check_not_auctioned(deps.as_ref(), &contract_address, &token_id)?;
+        self.check_collection_allowed(deps.storage, &contract_address)?;
+        self.check_owner_and_approval(&deps, &env, &info.sender, &contract_address, &token_id)?;
+
+        if !self.validate_auction_config(&auction_config) {
+            return Err(ContractError::CustomError {
+                val: "Invalid auction config".to_string(),
+            });
+        }
+
+        // only whitelisted cw20 tokens can be used to price a listing
+        if let AuctionConfig::Cw20FixedPrice { price, .. } = &auction_config {
+            if !self
+                .payment_tokens
+                .has(deps.storage, price.contract_address.clone())
+            {
+                return Err(ContractError::CustomError {
+                    val: "Cw20 token is not whitelisted".to_string(),
+                });
+            }
+        }
+
+        let reserved_buyers = item
+            .reserved_buyers
+            .iter()
+            .map(|buyer| deps.api.addr_validate(buyer))
+            .collect::<StdResult<Vec<Addr>>>()?;
+        if reserved_buyers.contains(&info.sender) {
+            return Err(ContractError::CustomError {
+                val: "Owner cannot be a reserved buyer".to_string(),
+            });
+        }
+
+        // an auctioned nft is held by the auction contract and can be bid by anyone
+        if auction_config.auction_type().is_some() && (item.escrow || !reserved_buyers.is_empty()) {
+            return Err(ContractError::CustomError {
+                val: "Auctioned nft cannot be escrowed or reserved".to_string(),
+            });
+        }
+
+        // add a nft to listings
+        let counter = self.counter(deps.storage, &info.sender)?;
+        let listing = Listing {
+            contract_address: contract_address.clone(),
+            token_id: token_id.clone(),
+            auction_config,
+            seller: info.sender,
+            buyer: None,
+            escrow: item.escrow,
+            reserved_buyers,
+            counter,
+        };
+        let listing_key = listing_key(&contract_address, &token_id);
+
+        // we will override the listing if it already exists, so that we can update the auction config
+        let new_listing = self.listings.update(
+            deps.storage,
+            listing_key,
+            |_old| -> Result<Listing, ContractError> { Ok(listing) },
+        )?;
+
+        let mut res = Response::new();
+
+        // an escrowed nft is held by this contract until the listing is bought or cancelled
+        if new_listing.escrow {
+            res = res.add_message(WasmMsg::Execute {
+                contract_addr: contract_address.to_string(),
+                msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                    recipient: env.contract.address.to_string(),
+                    token_id,
+                })?,
+                funds: vec![],
+            });
+        }
+
+        // an auctioned nft is transferred to the auction contract which creates its auction
+        if let Some(auction_type) = new_listing.auction_config.auction_type() {
+            res = res.add_messages(self.auction_messages(
+                &deps,
+                &new_listing.seller,
+                &NFT {
+                    contract_address: new_listing.contract_address.clone(),
+                    token_id: Some(new_listing.token_id.clone()),
+                },
+                auction_type,
+                to_binary(&new_listing.auction_config)?,
+            )?);
+        }
+
+        // println!("Listing: {:?}", _listing);
+        let auction_config_str = serde_json::to_string(&new_listing.auction_config);
+        match auction_config_str {
+            Ok(auction_config_str) => Ok(res
+                .add_attribute("method", "list_nft")
+                .add_attribute("contract_address", new_listing.contract_address)
+                .add_attribute("token_id", new_listing.token_id)
+                .add_attribute("auction_config", auction_config_str)
+                .add_attribute("seller", new_listing.seller.to_string())
+                .add_attribute("escrow", new_listing.escrow.to_string())
+                .add_attribute(
+                    "private",
+                    (!new_listing.reserved_buyers.is_empty()).to_string(),
+                )),
+            Err(_) => Err(ContractError::CustomError {
+                val: ("Auction Config Error".to_string()),
+            }),
+        }
+    }
+
+    // an auctioned nft cannot be listed again until its auction is settled
+    fn check_not_auctioned(
+        &self,
+        deps: Deps,
+        contract_address: &Addr,
+        token_id: &str,
+    ) -> Result<(), ContractError> {
+        // the auction may be created in any registered auction contract, not only by this marketplace
+        if self
+            .auctioning_contract(deps, contract_address, token_id)?
+            .is_some()
+        {
+            return Err(ContractError::CustomError {
+                val: "Nft is auctioned".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    // check that the owner holds the nft and approves this contract to transfer it
+    fn check_owner_and_approval(
+        &self,
+        deps: &DepsMut,
+        env: &Env,
+        owner_address: &Addr,
+        contract_address: &Addr,
+        token_id: &str,
+    ) -> Result<(), ContractError> {
         // check if user is the owner of the token
         let query_owner_msg = Cw721QueryMsg::OwnerOf {
-            token_id: token_id.clone(),
+            token_id: token_id.to_string(),
             include_expired: Some(false),
         };
         let owner_response: StdResult<cw721::OwnerOfResponse> =
@@ -62,7 +273,7 @@ impl MarketplaceContract<'static> {
             }));
         match owner_response {
             Ok(owner) => {
-                if owner.owner != info.sender {
+                if owner.owner != *owner_address {
                     return Err(ContractError::Unauthorized {});
                 }
             }
@@ -74,7 +285,7 @@ impl MarketplaceContract<'static> {
         // check that user approves this contract to manage this token
         // for now, we require never expired approval
         let query_approval_msg = Cw721QueryMsg::Approval {
-            token_id: token_id.clone(),
+            token_id: token_id.to_string(),
             spender: env.contract.address.to_string(),
             include_expired: Some(true),
         };
@@ -97,654 +308,2752 @@ impl MarketplaceContract<'static> {
             }
         }
 
-        if !self.validate_auction_config(&auction_config) {
+        Ok(())
+    }
+
+    pub fn execute_list_nfts(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        items: Vec<ListNftItem>,
+    ) -> Result<Response, ContractError> {
+        // if the number of items is greater than 50, then return error
+        if items.is_empty() || items.len() > 50 {
             return Err(ContractError::CustomError {
-                val: "Invalid auction config".to_string(),
+                val: ("Number of items must be between 1 and 50".to_string()),
             });
         }
 
-        // add a nft to listings
-        let listing = Listing {
-            contract_address: contract_address.clone(),
-            token_id: token_id.clone(),
-            auction_config,
-            seller: info.sender,
-            buyer: None,
-        };
-        let listing_key = listing_key(&contract_address, &token_id);
-
-        // we will override the listing if it already exists, so that we can update the auction config
-        let new_listing = self.listings.update(
-            deps.storage,
-            listing_key,
-            |_old| -> Result<Listing, ContractError> { Ok(listing) },
-        )?;
-
-        // println!("Listing: {:?}", _listing);
-        let auction_config_str = serde_json::to_string(&new_listing.auction_config);
-        match auction_config_str {
-            Ok(auction_config_str) => Ok(Response::new()
-                .add_attribute("method", "list_nft")
-                .add_attribute("contract_address", new_listing.contract_address)
-                .add_attribute("token_id", new_listing.token_id)
-                .add_attribute("auction_config", auction_config_str)
-                .add_attribute("seller", new_listing.seller.to_string())),
-            Err(_) => Err(ContractError::CustomError {
-                val: ("Auction Config Error".to_string()),
-            }),
+        let mut res = Response::new().add_attribute("method", "list_nfts");
+        for item in items {
+            let list_res = self.execute_list_nft(deps.branch(), env.clone(), info.clone(), item)?;
+            res = res.add_event(Event::new("list_nft").add_attributes(list_res.attributes));
         }
+
+        Ok(res)
     }
 
-    pub fn execute_buy(
-        self,
-        deps: DepsMut,
+    // buy many listings at once, all listings must be priced in native tokens
+    // the funds must cover the sum of the current prices, the exceeded funds are refunded
+    pub fn execute_buy_nfts(
+        &self,
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        contract_address: Addr,
-        token_id: String,
+        items: Vec<BuyNftItem>,
     ) -> Result<Response, ContractError> {
-        // get the listing
-        let listing_key = listing_key(&contract_address, &token_id);
-        let mut listing = self.listings.load(deps.storage, listing_key.clone())?;
-
-        // check if buyer is the same as seller
-        if info.sender == listing.seller {
+        // if the number of items is greater than 50, then return error
+        if items.is_empty() || items.len() > 50 {
             return Err(ContractError::CustomError {
-                val: ("Owner cannot buy".to_string()),
+                val: ("Number of items must be between 1 and 50".to_string()),
             });
         }
 
-        listing.buyer = Some(info.sender.clone());
+        let mut res = Response::new();
+        let mut total_prices: BTreeMap<String, Uint128> = BTreeMap::new();
+        for item in items {
+            let contract_address = deps.api.addr_validate(&item.contract_address)?;
 
-        // remove the listing
-        self.listings.remove(deps.storage, listing_key)?;
+            // get the listing, a listing cannot be bought twice
+            let listing_key = listing_key(&contract_address, &item.token_id);
+            let mut listing = self.listings.load(deps.storage, listing_key.clone())?;
 
-        match &listing.auction_config {
-            AuctionConfig::FixedPrice { .. } => {
-                self.process_buy_fixed_price(deps, env, info, &listing)
+            // the listing is cancelled if the seller has incremented its counter
+            if self.is_cancelled_listing(deps.storage, &listing) {
+                return Err(ContractError::ListingNotActive {});
             }
-        }
-    }
 
-    fn process_buy_fixed_price(
-        self,
-        deps: DepsMut,
-        env: Env,
-        info: MessageInfo,
-        listing: &Listing,
-    ) -> Result<Response, ContractError> {
-        match &listing.auction_config {
-            AuctionConfig::FixedPrice {
-                price,
-                start_time,
-                end_time,
-            } => {
-                // check if current block is after start_time
-                if start_time.is_some() && !start_time.unwrap().is_expired(&env.block) {
-                    return Err(ContractError::CustomError {
-                        val: ("Auction not started".to_string()),
-                    });
-                }
+            if listing.auction_config.auction_type().is_some() {
+                return Err(ContractError::CustomError {
+                    val: ("Auctioned nft must be bid in the auction contract".to_string()),
+                });
+            }
 
-                if end_time.is_some() && end_time.unwrap().is_expired(&env.block) {
-                    return Err(ContractError::CustomError {
-                        val: format!("Auction ended: {} {}", end_time.unwrap(), env.block.time),
-                    });
-                }
+            // check if buyer is the same as seller
+            if info.sender == listing.seller {
+                return Err(ContractError::CustomError {
+                    val: ("Owner cannot buy".to_string()),
+                });
+            }
 
-                // check if enough funds
-                if info.funds.is_empty() || info.funds[0] != *price {
-                    return Err(ContractError::InsufficientFunds {});
-                }
+            if !listing.can_be_bought_by(&info.sender) {
+                return Err(ContractError::CustomError {
+                    val: ("Listing is reserved for other buyers".to_string()),
+                });
+            }
 
-                // message to transfer nft to buyer
-                let transfer_nft_msg = WasmMsg::Execute {
-                    contract_addr: listing.contract_address.to_string(),
-                    msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
-                        recipient: listing.buyer.clone().unwrap().into_string(),
-                        token_id: listing.token_id.clone(),
-                    })?,
-                    funds: vec![],
-                };
-                let mut res = Response::new().add_message(transfer_nft_msg);
+            if !listing.is_started(&env.block) {
+                return Err(ContractError::CustomError {
+                    val: ("Auction not started".to_string()),
+                });
+            }
+            if listing.is_expired(&env.block) {
+                return Err(ContractError::CustomError {
+                    val: ("Auction ended".to_string()),
+                });
+            }
 
-                let payment = PaymentAsset::Native {
-                    denom: price.denom.clone(),
-                    amount: price.amount.into(),
-                };
+            let price = listing.current_price(&env.block);
+            match &price {
+                PaymentAsset::Native { denom, amount } => {
+                    *total_prices.entry(denom.clone()).or_default() += Uint128::from(*amount);
+                }
+                PaymentAsset::Cw20 { .. } => {
+                    return Err(ContractError::CustomError {
+                        val: ("Listing must be bought with cw20 token".to_string()),
+                    });
+                }
+            }
 
-                let payment_messages = self.payment_with_royalty(
-                    &deps,
-                    &listing.contract_address,
-                    &listing.token_id,
-                    payment,
-                    &info.sender,
-                    &listing.seller,
-                );
+            listing.buyer = Some(info.sender.clone());
 
-                for payment_message in payment_messages {
-                    res = res.add_message(payment_message);
-                }
+            // remove the listing
+            self.listings.remove(deps.storage, listing_key)?;
 
-                res = res
-                    .add_attribute("method", "buy")
-                    .add_attribute("contract_address", listing.contract_address.to_string())
-                    .add_attribute("token_id", listing.token_id.to_string())
-                    .add_attribute("buyer", info.sender);
+            let buy_res = self.settle_listing(&mut deps, &env, &listing, price)?;
+            res = res
+                .add_submessages(buy_res.messages)
+                .add_event(Event::new("buy").add_attributes(buy_res.attributes));
+        }
 
-                Ok(res)
+        // check if the funds cover the sum of the prices of each denom
+        for (denom, total_price) in total_prices.iter() {
+            let paid = info
+                .funds
+                .iter()
+                .find(|coin| &coin.denom == denom)
+                .map(|coin| coin.amount)
+                .unwrap_or_default();
+            if paid < *total_price {
+                return Err(ContractError::InsufficientFunds {});
             }
         }
+
+        // refund the exceeded funds to the buyer
+        let refund: Vec<Coin> = info
+            .funds
+            .iter()
+            .map(|coin| Coin {
+                denom: coin.denom.clone(),
+                amount: coin.amount - total_prices.get(&coin.denom).copied().unwrap_or_default(),
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+        if !refund.is_empty() {
+            res = res.add_message(BankMsg::Send {
+                to_address: info.sender.to_string(),
+                amount: refund,
+            });
+        }
+
+        Ok(res
+            .add_attribute("method", "buy_nfts")
+            .add_attribute("buyer", info.sender))
     }
 
-    pub fn execute_cancel(
-        self,
+    // buy many listings at once, each listing is bought by a submessage
+    // so that a listing which cannot be bought does not revert the whole sweep
+    pub fn execute_sweep_nfts(
+        &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        contract_address: Addr,
-        token_id: String,
+        items: Vec<BuyNftItem>,
     ) -> Result<Response, ContractError> {
-        // find listing
-        let listing_key = listing_key(&contract_address, &token_id);
-        let listing = self.listings.load(deps.storage, listing_key.clone())?;
+        // if the number of items is greater than 50, then return error
+        if items.is_empty() || items.len() > MAX_SWEEP_ITEMS {
+            return Err(ContractError::CustomError {
+                val: ("Number of items must be between 1 and 50".to_string()),
+            });
+        }
 
-        // if a listing is not expired, only seller can cancel
-        if (!listing.is_expired(&env.block)) && (listing.seller != info.sender) {
-            return Err(ContractError::Unauthorized {});
+        if self.sweep.may_load(deps.storage)?.is_some() {
+            return Err(ContractError::CustomError {
+                val: ("Sweep in progress".to_string()),
+            });
         }
 
-        // we will remove the cancelled listing
-        self.listings.remove(deps.storage, listing_key)?;
+        // the funds which are not spent yet for each denom
+        let mut remaining_funds: BTreeMap<String, Uint128> = BTreeMap::new();
+        for coin in info.funds.iter() {
+            *remaining_funds.entry(coin.denom.clone()).or_default() += coin.amount;
+        }
 
-        Ok(Response::new()
-            .add_attribute("method", "cancel")
-            .add_attribute("contract_address", contract_address)
-            .add_attribute("token_id", token_id)
-            .add_attribute("cancelled_at", env.block.time.to_string()))
+        let mut res = Response::new();
+        let mut sweep_items: Vec<SweepItem> = vec![];
+        for (index, item) in items.into_iter().enumerate() {
+            let contract_address = deps.api.addr_validate(&item.contract_address)?;
+            let listing = self
+                .listings
+                .may_load(deps.storage, listing_key(&contract_address, &item.token_id))?;
+
+            // skip the listing if it cannot be bought with the remaining funds
+            let price = match listing {
+                Some(listing)
+                    if listing.seller != info.sender
+                        && listing.can_be_bought_by(&info.sender)
+                        && !self.is_cancelled_listing(deps.storage, &listing)
+                        && listing.auction_config.auction_type().is_none()
+                        && listing.is_started(&env.block)
+                        && !listing.is_expired(&env.block) =>
+                {
+                    match listing.current_price(&env.block) {
+                        PaymentAsset::Native { denom, amount } => {
+                            let remaining = remaining_funds.entry(denom.clone()).or_default();
+                            if *remaining >= Uint128::from(amount) {
+                                *remaining -= Uint128::from(amount);
+                                Some(Coin {
+                                    denom,
+                                    amount: Uint128::from(amount),
+                                })
+                            } else {
+                                None
+                            }
+                        }
+                        PaymentAsset::Cw20 { .. } => None,
+                    }
+                }
+                _ => None,
+            };
+
+            // the price is sent with the submessage to buy the listing for the buyer
+            if let Some(price) = &price {
+                let sweep_item_msg = WasmMsg::Execute {
+                    contract_addr: env.contract.address.to_string(),
+                    msg: to_binary(&ExecuteMsg::SweepItem {
+                        buyer: info.sender.to_string(),
+                        contract_address: contract_address.to_string(),
+                        token_id: item.token_id.clone(),
+                    })?,
+                    funds: vec![price.clone()],
+                };
+                res = res.add_submessage(SubMsg::reply_on_error(
+                    sweep_item_msg,
+                    SWEEP_ITEM_REPLY_ID + index as u64,
+                ));
+            }
+
+            sweep_items.push(SweepItem {
+                contract_address,
+                token_id: item.token_id,
+                price,
+            });
+        }
+
+        self.sweep.save(
+            deps.storage,
+            &Sweep {
+                buyer: info.sender.clone(),
+                funds: info.funds,
+                items: sweep_items,
+            },
+        )?;
+
+        // the sweep is finalized after all submessages are executed
+        let finalize_sweep_msg = WasmMsg::Execute {
+            contract_addr: env.contract.address.to_string(),
+            msg: to_binary(&ExecuteMsg::FinalizeSweep {})?,
+            funds: vec![],
+        };
+
+        Ok(res
+            .add_message(finalize_sweep_msg)
+            .add_attribute("method", "sweep_nfts")
+            .add_attribute("buyer", info.sender))
     }
 
-    // function to add new offer nft using ordering style
-    // the 'offer' of offer_nft will contain the information of price
-    // the 'consideration' of offer_nft will contain the information of nft
-    pub fn execute_offer_nft(
+    pub fn execute_sweep_item(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        buyer: Addr,
+        contract_address: Addr,
+        token_id: String,
+    ) -> Result<Response, ContractError> {
+        // only this contract can buy for the buyer of a sweep
+        if info.sender != env.contract.address {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // the funds sent by this contract are the price of the listing
+        let buyer_info = MessageInfo {
+            sender: buyer,
+            funds: info.funds,
+        };
+        self.execute_buy(deps, env, buyer_info, contract_address, token_id)
+    }
+
+    // the listing of a sweep item cannot be bought, the price is not spent
+    pub fn reply_sweep_item(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        msg: Reply,
+    ) -> Result<Response, ContractError> {
+        let mut sweep = self.sweep.load(deps.storage)?;
+        let item = sweep
+            .items
+            .get_mut((msg.id - SWEEP_ITEM_REPLY_ID) as usize)
+            .ok_or(ContractError::CustomError {
+                val: ("Invalid sweep item".to_string()),
+            })?;
+        item.price = None;
+
+        let res = Response::new()
+            .add_attribute("method", "reply_sweep_item")
+            .add_attribute("contract_address", item.contract_address.to_string())
+            .add_attribute("token_id", item.token_id.clone());
+        self.sweep.save(deps.storage, &sweep)?;
+
+        Ok(res)
+    }
+
+    // refund the unused funds of the sweep and list the filled and skipped listings
+    pub fn execute_finalize_sweep(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        // only this contract can finalize a sweep
+        if info.sender != env.contract.address {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let sweep = self.sweep.load(deps.storage)?;
+        self.sweep.remove(deps.storage);
+
+        let mut res = Response::new()
+            .add_attribute("method", "finalize_sweep")
+            .add_attribute("buyer", sweep.buyer.to_string());
+
+        let mut spent_funds: BTreeMap<String, Uint128> = BTreeMap::new();
+        for item in sweep.items {
+            let listing = format!("{}/{}", item.contract_address, item.token_id);
+            match item.price {
+                Some(price) => {
+                    *spent_funds.entry(price.denom).or_default() += price.amount;
+                    res = res.add_attribute("filled", listing);
+                }
+                None => {
+                    res = res.add_attribute("skipped", listing);
+                }
+            }
+        }
+
+        // refund the unused funds to the buyer
+        let refund: Vec<Coin> = sweep
+            .funds
+            .into_iter()
+            .map(|coin| Coin {
+                amount: coin.amount - spent_funds.get(&coin.denom).copied().unwrap_or_default(),
+                denom: coin.denom,
+            })
+            .filter(|coin| !coin.amount.is_zero())
+            .collect();
+        if !refund.is_empty() {
+            res = res.add_message(BankMsg::Send {
+                to_address: sweep.buyer.to_string(),
+                amount: refund,
+            });
+        }
+
+        Ok(res)
+    }
+
+    pub fn execute_buy(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: Addr,
+        token_id: String,
+    ) -> Result<Response, ContractError> {
+        // get the listing
+        let listing_key = listing_key(&contract_address, &token_id);
+        let mut listing = self.listings.load(deps.storage, listing_key.clone())?;
+
+        // the listing is cancelled if the seller has incremented its counter
+        if self.is_cancelled_listing(deps.storage, &listing) {
+            return Err(ContractError::ListingNotActive {});
+        }
+
+        // check if buyer is the same as seller
+        if info.sender == listing.seller {
+            return Err(ContractError::CustomError {
+                val: ("Owner cannot buy".to_string()),
+            });
+        }
+
+        if !listing.can_be_bought_by(&info.sender) {
+            return Err(ContractError::CustomError {
+                val: ("Listing is reserved for other buyers".to_string()),
+            });
+        }
+
+        listing.buyer = Some(info.sender.clone());
+
+        // remove the listing
+        self.listings.remove(deps.storage, listing_key)?;
+
+        match &listing.auction_config {
+            AuctionConfig::FixedPrice { .. } => {
+                self.process_buy_fixed_price(deps, env, info, &listing)
+            }
+            AuctionConfig::DutchAuction { .. } => {
+                self.process_buy_dutch_auction(deps, env, info, &listing)
+            }
+            AuctionConfig::Cw20FixedPrice { .. } => Err(ContractError::CustomError {
+                val: ("Listing must be bought with cw20 token".to_string()),
+            }),
+            AuctionConfig::EnglishAuction { .. } => Err(ContractError::CustomError {
+                val: ("Auctioned nft must be bid in the auction contract".to_string()),
+            }),
+        }
+    }
+
+    // handle the cw20 tokens sent to this contract by the Send message of a cw20 contract
+    pub fn execute_receive(
         self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        nft: NFT,
-        funds_amount: u128,
-        end_time: Cw721Expiration,
+        receive_msg: Cw20ReceiveMsg,
     ) -> Result<Response, ContractError> {
-        // load config
-        let config = self.config.load(deps.storage)?;
-        // check ig the vaura_address is set (the default value is equal to "aura0")
-        if config.vaura_address == Addr::unchecked("aura0") {
-            return Err(ContractError::VauraAddressNotSet {});
+        // the sender of the cw20 tokens is the user, info.sender is the cw20 contract
+        let sender = deps.api.addr_validate(&receive_msg.sender)?;
+        let payment = PaymentAsset::Cw20 {
+            contract_address: info.sender,
+            amount: receive_msg.amount.u128(),
+        };
+
+        match from_binary(&receive_msg.msg)? {
+            Cw20HookMsg::Buy {
+                contract_address,
+                token_id,
+            } => {
+                let contract_address = deps.api.addr_validate(&contract_address)?;
+                self.execute_buy_with_cw20(deps, env, sender, payment, contract_address, token_id)
+            }
+        }
+    }
+
+    fn execute_buy_with_cw20(
+        self,
+        mut deps: DepsMut,
+        env: Env,
+        buyer: Addr,
+        payment: PaymentAsset,
+        contract_address: Addr,
+        token_id: String,
+    ) -> Result<Response, ContractError> {
+        // get the listing
+        let listing_key = listing_key(&contract_address, &token_id);
+        let mut listing = self.listings.load(deps.storage, listing_key.clone())?;
+
+        // the listing is cancelled if the seller has incremented its counter
+        if self.is_cancelled_listing(deps.storage, &listing) {
+            return Err(ContractError::ListingNotActive {});
+        }
+
+        // check if buyer is the same as seller
+        if buyer == listing.seller {
+            return Err(ContractError::CustomError {
+                val: ("Owner cannot buy".to_string()),
+            });
+        }
+
+        if !listing.can_be_bought_by(&buyer) {
+            return Err(ContractError::CustomError {
+                val: ("Listing is reserved for other buyers".to_string()),
+            });
+        }
+
+        listing.buyer = Some(buyer);
+
+        // remove the listing
+        self.listings.remove(deps.storage, listing_key)?;
+
+        match &listing.auction_config {
+            AuctionConfig::Cw20FixedPrice {
+                start_time,
+                end_time,
+                ..
+            } => {
+                // check if current block is after start_time
+                if start_time.is_some() && !start_time.unwrap().is_expired(&env.block) {
+                    return Err(ContractError::CustomError {
+                        val: ("Auction not started".to_string()),
+                    });
+                }
+
+                if end_time.is_some() && end_time.unwrap().is_expired(&env.block) {
+                    return Err(ContractError::CustomError {
+                        val: format!("Auction ended: {} {}", end_time.unwrap(), env.block.time),
+                    });
+                }
+
+                // check if the received token and amount match the price
+                if payment != listing.current_price(&env.block) {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+
+                self.settle_listing(&mut deps, &env, &listing, payment)
+            }
+            _ => Err(ContractError::CustomError {
+                val: ("Listing cannot be bought with cw20 token".to_string()),
+            }),
+        }
+    }
+
+    fn process_buy_fixed_price(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        listing: &Listing,
+    ) -> Result<Response, ContractError> {
+        match &listing.auction_config {
+            AuctionConfig::FixedPrice {
+                price,
+                start_time,
+                end_time,
+            } => {
+                // check if current block is after start_time
+                if start_time.is_some() && !start_time.unwrap().is_expired(&env.block) {
+                    return Err(ContractError::CustomError {
+                        val: ("Auction not started".to_string()),
+                    });
+                }
+
+                if end_time.is_some() && end_time.unwrap().is_expired(&env.block) {
+                    return Err(ContractError::CustomError {
+                        val: format!("Auction ended: {} {}", end_time.unwrap(), env.block.time),
+                    });
+                }
+
+                // check if enough funds
+                if info.funds.is_empty() || info.funds[0] != *price {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+
+                let payment = PaymentAsset::Native {
+                    denom: price.denom.clone(),
+                    amount: price.amount.into(),
+                };
+
+                self.settle_listing(&mut deps, &env, listing, payment)
+            }
+            _ => Err(ContractError::CustomError {
+                val: ("Listing is not a fixed price listing".to_string()),
+            }),
+        }
+    }
+
+    fn process_buy_dutch_auction(
+        &self,
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        listing: &Listing,
+    ) -> Result<Response, ContractError> {
+        match &listing.auction_config {
+            AuctionConfig::DutchAuction {
+                start_time,
+                end_time,
+                ..
+            } => {
+                // check if current block is after start_time
+                if !start_time.is_expired(&env.block) {
+                    return Err(ContractError::CustomError {
+                        val: ("Auction not started".to_string()),
+                    });
+                }
+
+                if end_time.is_expired(&env.block) {
+                    return Err(ContractError::CustomError {
+                        val: format!("Auction ended: {} {}", end_time, env.block.time),
+                    });
+                }
+
+                // the price declines while the buy transaction is pending,
+                // so we accept any funds covering the current price and refund the rest
+                let price = listing.current_price(&env.block);
+                let (denom, amount) = match &price {
+                    PaymentAsset::Native { denom, amount } => {
+                        (denom.clone(), Uint128::from(*amount))
+                    }
+                    _ => return Err(ContractError::InsufficientFunds {}),
+                };
+                // only the denom of the listing is accepted, other coins would be kept by this contract
+                if info.funds.len() != 1 || info.funds[0].denom != denom {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+                let paid = info.funds[0].amount;
+                if paid < amount {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+
+                let mut res = self.settle_listing(&mut deps, &env, listing, price)?;
+
+                // refund the exceeded funds to the buyer
+                if paid > amount {
+                    res = res.add_message(BankMsg::Send {
+                        to_address: info.sender.to_string(),
+                        amount: vec![Coin {
+                            denom,
+                            amount: paid - amount,
+                        }],
+                    });
+                }
+
+                Ok(res.add_attribute("price", amount.to_string()))
+            }
+            _ => Err(ContractError::CustomError {
+                val: ("Listing is not a dutch auction".to_string()),
+            }),
+        }
+    }
+
+    // transfer the nft of a listing to the buyer and pay the price to the seller (and the creator)
+    // the price must be already received by this contract
+    fn settle_listing(
+        &self,
+        deps: &mut DepsMut,
+        env: &Env,
+        listing: &Listing,
+        payment: PaymentAsset,
+    ) -> Result<Response, ContractError> {
+        // the collection may have been blocked since the listing was created
+        self.check_collection_allowed(deps.storage, &listing.contract_address)?;
+
+        let buyer = listing.buyer.clone().unwrap();
+
+        // message to transfer nft to buyer
+        let transfer_nft_msg = WasmMsg::Execute {
+            contract_addr: listing.contract_address.to_string(),
+            msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                recipient: buyer.to_string(),
+                token_id: listing.token_id.clone(),
+            })?,
+            funds: vec![],
+        };
+        let mut res = Response::new().add_message(transfer_nft_msg);
+
+        let (payment_messages, protocol_fee) = self.payment_with_royalty(
+            deps,
+            &listing.contract_address,
+            &listing.token_id,
+            payment.clone(),
+            None,
+            &listing.seller,
+        )?;
+
+        self.record_sale(
+            deps.storage,
+            Sale {
+                id: 0,
+                contract_address: listing.contract_address.clone(),
+                token_id: listing.token_id.clone(),
+                price: payment,
+                buyer: buyer.clone(),
+                seller: listing.seller.clone(),
+                time: env.block.time,
+                kind: SaleKind::Listing,
+            },
+        )?;
+
+        for payment_message in payment_messages {
+            res = res.add_message(payment_message);
+        }
+
+        res = res
+            .add_attribute("method", "buy")
+            .add_attribute("contract_address", listing.contract_address.to_string())
+            .add_attribute("token_id", listing.token_id.to_string())
+            .add_attribute("buyer", buyer)
+            .add_attribute("protocol_fee", protocol_fee);
+
+        Ok(res)
+    }
+
+    // remove the listings which cannot be bought anymore
+    pub fn execute_prune_listings(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        _info: MessageInfo,
+        listings: Vec<BuyNftItem>,
+    ) -> Result<Response, ContractError> {
+        // if the number of listings is greater than 50, then return error
+        if listings.len() > 50 {
+            return Err(ContractError::CustomError {
+                val: ("Number of listings is greater than 50".to_string()),
+            });
+        }
+
+        let mut res = Response::new().add_attribute("method", "prune_listings");
+        for item in listings {
+            let contract_address = deps.api.addr_validate(&item.contract_address)?;
+            let listing_key = listing_key(&contract_address, &item.token_id);
+            let listing = match self.listings.may_load(deps.storage, listing_key.clone())? {
+                Some(listing) => listing,
+                None => continue,
+            };
+
+            if !self.is_valid_listing(deps.as_ref(), &env, &listing) {
+                self.listings.remove(deps.storage, listing_key)?;
+
+                // the escrowed nft is returned to the seller
+                if listing.escrow {
+                    res = res.add_message(return_escrowed_nft_msg(&listing)?);
+                }
+                res = res.add_attribute(
+                    "pruned",
+                    format!("{}/{}", listing.contract_address, listing.token_id),
+                );
+            }
+        }
+
+        Ok(res)
+    }
+
+    pub fn execute_cancel(
+        self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: Addr,
+        token_id: String,
+    ) -> Result<Response, ContractError> {
+        // find listing
+        let listing_key = listing_key(&contract_address, &token_id);
+        let listing = self.listings.load(deps.storage, listing_key.clone())?;
+
+        // an auction is settled in the auction contract, it cannot be cancelled
+        if self.auction_state(deps.as_ref(), &listing)?.is_some() {
+            return Err(ContractError::CustomError {
+                val: ("Auction cannot be cancelled".to_string()),
+            });
+        }
+
+        // if a listing is not expired, only seller can cancel
+        if (!listing.is_expired(&env.block)) && (listing.seller != info.sender) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // we will remove the cancelled listing
+        self.listings.remove(deps.storage, listing_key)?;
+
+        let mut res = Response::new();
+
+        // the escrowed nft is returned to the seller
+        if listing.escrow {
+            res = res.add_message(return_escrowed_nft_msg(&listing)?);
+        }
+
+        Ok(res
+            .add_attribute("method", "cancel")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("token_id", token_id)
+            .add_attribute("cancelled_at", env.block.time.to_string()))
+    }
+
+    // list many nfts to be sold together for one price
+    // each nft must be owned by the seller and approved to this contract
+    pub fn execute_list_bundle(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        nfts: Vec<BuyNftItem>,
+        price: Coin,
+        end_time: Option<Cw721Expiration>,
+    ) -> Result<Response, ContractError> {
+        // a bundle must contain between 2 and 50 nfts
+        if nfts.len() < 2 || nfts.len() > 50 {
+            return Err(ContractError::CustomError {
+                val: ("Number of nfts must be between 2 and 50".to_string()),
+            });
+        }
+
+        // each nft must be apportioned a part of the price
+        if price.amount < Uint128::from(nfts.len() as u128) {
+            return Err(ContractError::CustomError {
+                val: ("Invalid bundle price".to_string()),
+            });
+        }
+
+        if let Some(end_time) = end_time {
+            if end_time.is_expired(&env.block) {
+                return Err(ContractError::InvalidEndTime {});
+            }
+        }
+
+        let mut bundle_items: Vec<BundleItem> = vec![];
+        for nft in nfts {
+            let contract_address = deps.api.addr_validate(&nft.contract_address)?;
+            self.check_collection_allowed(deps.storage, &contract_address)?;
+            let item = BundleItem {
+                contract_address,
+                token_id: nft.token_id,
+            };
+            if bundle_items.contains(&item) {
+                return Err(ContractError::CustomError {
+                    val: ("Duplicate nft in bundle".to_string()),
+                });
+            }
+
+            self.check_owner_and_approval(
+                &deps,
+                &env,
+                &info.sender,
+                &item.contract_address,
+                &item.token_id,
+            )?;
+            bundle_items.push(item);
+        }
+
+        let bundle_id = self
+            .bundle_count
+            .may_load(deps.storage)?
+            .unwrap_or_default()
+            + 1;
+        self.bundle_count.save(deps.storage, &bundle_id)?;
+
+        let bundle = Bundle {
+            id: bundle_id,
+            counter: self.counter(deps.storage, &info.sender)?,
+            seller: info.sender,
+            nfts: bundle_items,
+            price,
+            end_time,
+        };
+        self.bundles.save(deps.storage, bundle_id, &bundle)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "list_bundle")
+            .add_attribute("bundle_id", bundle_id.to_string())
+            .add_attribute("seller", bundle.seller)
+            .add_attribute("price", bundle.price.to_string())
+            .add_attribute("nfts", bundle.nfts.len().to_string()))
+    }
+
+    // buy all nfts of a bundle, the royalty of each nft is paid on its share of the price
+    pub fn execute_buy_bundle(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        bundle_id: u64,
+    ) -> Result<Response, ContractError> {
+        let bundle = self.bundles.load(deps.storage, bundle_id)?;
+
+        // the bundle is cancelled if the seller has incremented its counter
+        if bundle.counter != self.counter(deps.storage, &bundle.seller)? {
+            return Err(ContractError::ListingNotActive {});
+        }
+
+        // check if buyer is the same as seller
+        if info.sender == bundle.seller {
+            return Err(ContractError::CustomError {
+                val: ("Owner cannot buy".to_string()),
+            });
+        }
+
+        if let Some(end_time) = bundle.end_time {
+            if end_time.is_expired(&env.block) {
+                return Err(ContractError::CustomError {
+                    val: format!("Bundle ended: {} {}", end_time, env.block.time),
+                });
+            }
+        }
+
+        // check if enough funds
+        if info.funds.len() != 1 || info.funds[0] != bundle.price {
+            return Err(ContractError::InsufficientFunds {});
+        }
+
+        // the collections may have been blocked since the bundle was created
+        for item in bundle.nfts.iter() {
+            self.check_collection_allowed(deps.storage, &item.contract_address)?;
+        }
+
+        // remove the bundle
+        self.bundles.remove(deps.storage, bundle_id);
+
+        let mut res = Response::new();
+        let mut total_protocol_fee = Uint128::zero();
+        for (item, item_price) in bundle.nfts.iter().zip(bundle.item_prices()) {
+            // the transfer fails if the seller does not own or approve the nft anymore
+            res = res.add_message(WasmMsg::Execute {
+                contract_addr: item.contract_address.to_string(),
+                msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                    recipient: info.sender.to_string(),
+                    token_id: item.token_id.clone(),
+                })?,
+                funds: vec![],
+            });
+
+            let payment = PaymentAsset::Native {
+                denom: bundle.price.denom.clone(),
+                amount: item_price.u128(),
+            };
+            self.record_sale(
+                deps.storage,
+                Sale {
+                    id: 0,
+                    contract_address: item.contract_address.clone(),
+                    token_id: item.token_id.clone(),
+                    price: payment.clone(),
+                    buyer: info.sender.clone(),
+                    seller: bundle.seller.clone(),
+                    time: env.block.time,
+                    kind: SaleKind::Bundle,
+                },
+            )?;
+
+            // a bundle listed before its price was checked may apportion nothing to a nft
+            if item_price.is_zero() {
+                continue;
+            }
+
+            let (payment_messages, protocol_fee) = self.payment_with_royalty(
+                &deps,
+                &item.contract_address,
+                &item.token_id,
+                payment,
+                None,
+                &bundle.seller,
+            )?;
+            res = res.add_messages(payment_messages);
+            total_protocol_fee += protocol_fee;
+        }
+
+        Ok(res
+            .add_attribute("method", "buy_bundle")
+            .add_attribute("bundle_id", bundle_id.to_string())
+            .add_attribute("buyer", info.sender)
+            .add_attribute("protocol_fee", total_protocol_fee))
+    }
+
+    pub fn execute_cancel_bundle(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        bundle_id: u64,
+    ) -> Result<Response, ContractError> {
+        let bundle = self.bundles.load(deps.storage, bundle_id)?;
+
+        // if a bundle can still be bought, only seller can cancel
+        if bundle.seller != info.sender && self.is_valid_bundle(deps.as_ref(), &env, &bundle) {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        self.bundles.remove(deps.storage, bundle_id);
+
+        Ok(Response::new()
+            .add_attribute("method", "cancel_bundle")
+            .add_attribute("bundle_id", bundle_id.to_string())
+            .add_attribute("cancelled_at", env.block.time.to_string()))
+    }
+
+    // function to add new offer nft using ordering style
+    // the 'offer' of offer_nft will contain the information of price
+    // the 'consideration' of offer_nft will contain the information of nft
+    // the offered amount changes linearly from funds_amount at start_time
+    // to end_funds_amount at end_time if they are different
+    pub fn execute_offer_nft(
+        self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        nft_offer: NftOffer,
+    ) -> Result<Response, ContractError> {
+        self.check_collection_allowed(deps.storage, &nft_offer.nft.contract_address)?;
+        let offer = self.validate_offer_funds(
+            &deps,
+            &env,
+            &info,
+            nft_offer.funds_amount,
+            nft_offer.end_time,
+        )?;
+
+        let end_funds_amount = nft_offer.end_funds_amount.unwrap_or(nft_offer.funds_amount);
+        if end_funds_amount != nft_offer.funds_amount {
+            // the amount is calculated by block time, so both times must be AtTime
+            match (nft_offer.start_time, nft_offer.end_time) {
+                (Some(Cw721Expiration::AtTime(start)), Cw721Expiration::AtTime(end))
+                    if start < end && end_funds_amount > 0 => {}
+                _ => {
+                    return Err(ContractError::CustomError {
+                        val: ("Invalid time-varying offer".to_string()),
+                    });
+                }
+            }
+
+            // the allowance must also cover the end amount of an increasing offer
+            if end_funds_amount > nft_offer.funds_amount {
+                self.validate_offer_funds(
+                    &deps,
+                    &env,
+                    &info,
+                    end_funds_amount,
+                    nft_offer.end_time,
+                )?;
+            }
+        }
+
+        let contract_address = nft_offer.nft.contract_address;
+        let token_id = match nft_offer.nft.token_id {
+            Some(token_id) => {
+                // query the owner of the nft to check if the nft exist
+                let owner_response: StdResult<cw721::OwnerOfResponse> =
+                    deps.querier.query_wasm_smart(
+                        &contract_address,
+                        &Cw721QueryMsg::OwnerOf {
+                            token_id: token_id.clone(),
+                            include_expired: Some(false),
+                        },
+                    );
+
+                match owner_response {
+                    Ok(owner) => {
+                        if owner.owner == info.sender {
+                            return Err(ContractError::CustomError {
+                                val: ("Cannot offer owned nft".to_string()),
+                            });
+                        }
+                    }
+                    Err(_) => {
+                        return Err(ContractError::CustomError {
+                            val: ("Nft not exist".to_string()),
+                        });
+                    }
+                }
+                Some(token_id)
+            }
+            None => {
+                // if the token_id is not exist, then this order is offer for a collection of nft
+                // query the number of tokens to check if the collection exist
+                let num_tokens_response: StdResult<cw721::NumTokensResponse> = deps
+                    .querier
+                    .query_wasm_smart(&contract_address, &Cw721QueryMsg::NumTokens {});
+                if num_tokens_response.is_err() {
+                    return Err(ContractError::CustomError {
+                        val: ("Collection not exist".to_string()),
+                    });
+                }
+                None
+            }
+        };
+
+        // generate order key for order components based on user address, contract address and token id
+        let order_key = order_key(
+            &info.sender,
+            &contract_address,
+            token_id.as_deref().unwrap_or(COLLECTION_OFFER_TOKEN_ID),
+        );
+
+        // the consideration item will contain the infomation of nft
+        let consideration = Asset::Nft(NFT {
+            contract_address,
+            token_id,
+        });
+
+        // the offer item will contain the infomation of cw20 token
+        // and the amounts offered at the start time and at the end time
+        let offer_item = offer_item(
+            &ItemType::CW20,
+            &offer,
+            &nft_offer.funds_amount,
+            &end_funds_amount,
+        );
+
+        self.save_offer(
+            deps,
+            order_key,
+            vec![offer_item],
+            consideration,
+            nft_offer.start_time,
+            nft_offer.end_time,
+        )
+    }
+
+    // function to add new offer for any nft of a collection having all the traits
+    pub fn execute_offer_trait_nft(
+        self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        nft: TraitNFT,
+        funds_amount: u128,
+        end_time: Cw721Expiration,
+    ) -> Result<Response, ContractError> {
+        self.check_collection_allowed(deps.storage, &nft.contract_address)?;
+        let offer = self.validate_offer_funds(&deps, &env, &info, funds_amount, end_time)?;
+
+        let mut traits = nft.traits;
+        traits.sort();
+        traits.dedup();
+        if traits.is_empty() {
+            return Err(ContractError::CustomError {
+                val: ("Traits are required".to_string()),
+            });
+        }
+
+        // query the number of tokens to check if the collection exist
+        let num_tokens_response: StdResult<cw721::NumTokensResponse> = deps
+            .querier
+            .query_wasm_smart(&nft.contract_address, &Cw721QueryMsg::NumTokens {});
+        if num_tokens_response.is_err() {
+            return Err(ContractError::CustomError {
+                val: ("Collection not exist".to_string()),
+            });
+        }
+
+        let order_key = order_key(
+            &info.sender,
+            &nft.contract_address,
+            &trait_offer_token_id(&traits),
+        );
+
+        // the consideration item will contain the infomation of the collection and the traits
+        let consideration = Asset::TraitNft(TraitNFT {
+            contract_address: nft.contract_address,
+            traits,
+        });
+
+        // the offer item will contain the infomation of cw20 token
+        let offer_item = offer_item(&ItemType::CW20, &offer, &funds_amount, &funds_amount);
+
+        self.save_offer(
+            deps,
+            order_key,
+            vec![offer_item],
+            consideration,
+            None,
+            end_time,
+        )
+    }
+
+    // function to offer nfts, and optionally vaura token, in exchange for a nft
+    // the offered nfts must be owned by the offerer and approved to this contract
+    pub fn execute_offer_swap(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        swap_offer: SwapOffer,
+    ) -> Result<Response, ContractError> {
+        let contract_address = swap_offer.nft.contract_address;
+        self.check_collection_allowed(deps.storage, &contract_address)?;
+        for offered_nft in swap_offer.offered_nfts.iter() {
+            self.check_collection_allowed(deps.storage, &offered_nft.contract_address)?;
+        }
+        let token_id = match swap_offer.nft.token_id {
+            Some(token_id) => token_id,
+            None => {
+                return Err(ContractError::CustomError {
+                    val: ("Token id is required".to_string()),
+                });
+            }
+        };
+
+        // if the number of offered nfts is greater than 50, then return error
+        if swap_offer.offered_nfts.is_empty() || swap_offer.offered_nfts.len() > 50 {
+            return Err(ContractError::CustomError {
+                val: ("Number of offered NFTs must be between 1 and 50".to_string()),
+            });
+        }
+
+        // the vaura token is only offered with the nfts if the funds amount is not zero
+        let mut offer_items: Vec<OfferItem> = vec![];
+        if swap_offer.funds_amount > 0 {
+            let offer = self.validate_offer_funds(
+                &deps,
+                &env,
+                &info,
+                swap_offer.funds_amount,
+                swap_offer.end_time,
+            )?;
+            offer_items.push(offer_item(
+                &ItemType::CW20,
+                &offer,
+                &swap_offer.funds_amount,
+                &swap_offer.funds_amount,
+            ));
+        } else if swap_offer.end_time.is_expired(&env.block) {
+            return Err(ContractError::InvalidEndTime {});
+        }
+
+        // the wanted nft must exist and cannot be owned by the offerer
+        let owner: cw721::OwnerOfResponse = deps
+            .querier
+            .query_wasm_smart(
+                &contract_address,
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: token_id.clone(),
+                    include_expired: Some(false),
+                },
+            )
+            .map_err(|_| ContractError::NftNotFound {})?;
+        if owner.owner == info.sender {
+            return Err(ContractError::CannotOfferOwnNFT {});
+        }
+
+        for nft in swap_offer.offered_nfts {
+            let offered_token_id = match &nft.token_id {
+                Some(token_id) => token_id,
+                None => {
+                    return Err(ContractError::CustomError {
+                        val: ("Token id is required".to_string()),
+                    });
+                }
+            };
+
+            let asset = Asset::Nft(nft.clone());
+            if offer_items.iter().any(|item| item.item == asset) {
+                return Err(ContractError::CustomError {
+                    val: ("Duplicate offered NFT".to_string()),
+                });
+            }
+
+            self.check_owner_and_approval(
+                &deps,
+                &env,
+                &info.sender,
+                &nft.contract_address,
+                offered_token_id,
+            )?;
+            offer_items.push(offer_item(&ItemType::CW721, &asset, &0u128, &0u128));
+        }
+
+        let order_key = order_key(&info.sender, &contract_address, &token_id);
+
+        // the consideration item will contain the infomation of the wanted nft
+        let consideration = Asset::Nft(NFT {
+            contract_address,
+            token_id: Some(token_id),
+        });
+
+        self.save_offer(
+            deps,
+            order_key,
+            offer_items,
+            consideration,
+            None,
+            swap_offer.end_time,
+        )
+    }
+
+    // check that the offerer allows this contract to transfer the offered vaura token
+    // returns the offered vaura token
+    fn validate_offer_funds(
+        &self,
+        deps: &DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        funds_amount: u128,
+        end_time: Cw721Expiration,
+    ) -> Result<Asset, ContractError> {
+        // load config
+        let config = self.config.load(deps.storage)?;
+        // check ig the vaura_address is set (the default value is equal to "aura0")
+        if config.vaura_address == Addr::unchecked("aura0") {
+            return Err(ContractError::VauraAddressNotSet {});
+        }
+
+        // check if the end time is valid
+        if end_time.is_expired(&env.block) {
+            return Err(ContractError::InvalidEndTime {});
+        }
+        // ***********
+        // OFFERING FUNDS
+        // ***********
+        // load config
+        let config = self.config.load(deps.storage)?;
+
+        let token_address = config.vaura_address;
+        let amount = funds_amount;
+
+        // check that the allowance of the cw20 offer token is enough
+        let allowance_response: AllowanceResponse = deps
+            .querier
+            .query_wasm_smart(
+                &token_address,
+                &Cw20QueryMsg::Allowance {
+                    owner: info.sender.to_string(),
+                    spender: env.contract.address.to_string(),
+                },
+            )
+            .unwrap();
+
+        // check if the allowance is greater or equal the offer amount
+        if allowance_response.allowance < Uint128::from(amount) {
+            return Err(ContractError::InsufficientAllowance {});
+        }
+
+        Ok(Asset::Cw20(CW20 {
+            contract_address: token_address,
+            amount,
+        }))
+    }
+
+    // save an offer of the offer items for the consideration item
+    // the offerer is the user address of the order key
+    fn save_offer(
+        &self,
+        deps: DepsMut,
+        order_key: OrderKey,
+        offer_items: Vec<OfferItem>,
+        consideration: Asset,
+        start_time: Option<Cw721Expiration>,
+        end_time: Cw721Expiration,
+    ) -> Result<Response, ContractError> {
+        let offerer = order_key.0.clone();
+        let counter = self.counter(deps.storage, &offerer)?;
+
+        // the consideration item will contain the infomation of nft
+        let consideration_item =
+            consideration_item(&ItemType::CW721, &consideration, &0u128, &0u128, &offerer);
+
+        // generate order components
+        let order_offer = OrderComponents {
+            order_type: OrderType::OFFER, // The type of offer must be OFFER
+            order_id: order_key.clone(),
+            offerer,
+            offer: offer_items,
+            consideration: [consideration_item].to_vec(),
+            start_time,
+            end_time: Some(end_time),
+            counter,
+        };
+
+        // an offer of nfts is a swap offer, stored apart from the offers of funds
+        let offers = if order_offer
+            .offer
+            .iter()
+            .any(|item| item.item_type == ItemType::CW721)
+        {
+            &self.swap_offers
+        } else {
+            &self.offers
+        };
+
+        // we will override the order if it already exists
+        let new_offer = offers.update(
+            deps.storage,
+            order_key,
+            |_old| -> Result<OrderComponents, ContractError> { Ok(order_offer) },
+        )?;
+
+        let offer_str = serde_json::to_string(&new_offer.offer);
+        let consideration_str = serde_json::to_string(&new_offer.consideration);
+
+        // return success
+        Ok(Response::new()
+            .add_attribute("method", "create_offer")
+            .add_attribute("order_type", "OFFER")
+            .add_attribute("offerer", new_offer.offerer)
+            .add_attribute("offer", offer_str.unwrap())
+            .add_attribute("consideration", consideration_str.unwrap())
+            .add_attribute("end_time", new_offer.end_time.unwrap().to_string()))
+    }
+
+    // function to accept offer nft using ordering style
+    pub fn execute_accept_nft_offer(
+        self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        offerer: Addr,
+        nft: NFT,
+        funds_amount: u128,
+    ) -> Result<Response, ContractError> {
+        let contract_address = nft.contract_address;
+        let token_id = nft.token_id;
+
+        // cannot accept own offer
+        if info.sender == offerer {
+            return Err(ContractError::CustomError {
+                val: ("Cannot accept own offer".to_string()),
+            });
+        }
+
+        // if the token_id is exist, then this order is offer for a specific nft
+        if let Some(token_id) = token_id {
+            // generate order key for order components based on user address, contract address and token id
+            let order_key = order_key(&offerer, &contract_address, &token_id);
+
+            let res = self.accept_offer(deps, &env, &info, order_key, &token_id, funds_amount)?;
+            Ok(res.add_attribute("method", "execute_accept_nft_offer"))
+        } else {
+            Err(ContractError::CustomError {
+                val: ("Use AcceptCollectionOffer to accept a collection offer".to_string()),
+            })
+        }
+    }
+
+    // function to accept a collection offer by selling a nft of the collection
+    pub fn execute_accept_collection_offer(
+        self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        offerer: Addr,
+        nft: NFT,
+        funds_amount: u128,
+    ) -> Result<Response, ContractError> {
+        // cannot accept own offer
+        if info.sender == offerer {
+            return Err(ContractError::CustomError {
+                val: ("Cannot accept own offer".to_string()),
+            });
+        }
+
+        // the seller must name the nft sold to the offerer
+        let token_id = match nft.token_id {
+            Some(token_id) => token_id,
+            None => {
+                return Err(ContractError::CustomError {
+                    val: ("Token id is required".to_string()),
+                });
+            }
+        };
+
+        // the collection offer is stored with the empty token id
+        let order_key = order_key(&offerer, &nft.contract_address, COLLECTION_OFFER_TOKEN_ID);
+
+        let res = self.accept_offer(deps, &env, &info, order_key, &token_id, funds_amount)?;
+        Ok(res.add_attribute("method", "execute_accept_collection_offer"))
+    }
+
+    // function to accept a trait offer by selling a nft having all the traits of the offer
+    // the order key is generated from the offerer and the traits of the offer
+    pub fn execute_accept_trait_offer(
+        self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        order_key: OrderKey,
+        token_id: String,
+        funds_amount: u128,
+    ) -> Result<Response, ContractError> {
+        // cannot accept own offer
+        if info.sender == order_key.0 {
+            return Err(ContractError::CustomError {
+                val: ("Cannot accept own offer".to_string()),
+            });
+        }
+
+        let res = self.accept_offer(deps, &env, &info, order_key, &token_id, funds_amount)?;
+        Ok(res.add_attribute("method", "execute_accept_trait_offer"))
+    }
+
+    // function to accept a swap offer, the nft of the sender is exchanged with the offered nfts
+    // and funds of the offerer in the same transaction
+    pub fn execute_accept_swap_offer(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        order_key: OrderKey,
+        offered_nfts: Vec<NFT>,
+        funds_amount: u128,
+    ) -> Result<Response, ContractError> {
+        // cannot accept own offer
+        if info.sender == order_key.0 {
+            return Err(ContractError::CustomError {
+                val: ("Cannot accept own offer".to_string()),
+            });
+        }
+
+        // get order components
+        let order_components = self.swap_offers.load(deps.storage, order_key.clone())?;
+
+        // the offer is cancelled if the offerer has incremented its counter
+        if self.is_cancelled_offer(deps.storage, &order_components) {
+            return Err(ContractError::CustomError {
+                val: ("Offer is cancelled".to_string()),
+            });
+        }
+
+        if !order_components.is_started(&env.block) {
+            return Err(ContractError::CustomError {
+                val: ("Offer not started".to_string()),
+            });
+        }
+
+        // if the end time of the offer is expired, then return error
+        if order_components.end_time.unwrap().is_expired(&env.block) {
+            return Err(ContractError::CustomError {
+                val: ("Offer is expired".to_string()),
+            });
+        }
+
+        let (contract_address, token_id) = match &order_components.consideration[0].item {
+            Asset::Nft(NFT {
+                contract_address,
+                token_id: Some(token_id),
+            }) => (contract_address.clone(), token_id.clone()),
+            _ => {
+                return Err(ContractError::CustomError {
+                    val: ("Consideration is not NFT".to_string()),
+                })
+            }
+        };
+
+        // split the offer items into the offered nfts and the offered funds
+        let mut order_nfts: Vec<NFT> = vec![];
+        let mut payment_item: Option<PaymentAsset> = None;
+        for offer_item in order_components.offer.iter() {
+            match &offer_item.item {
+                Asset::Nft(nft) => order_nfts.push(nft.clone()),
+                Asset::Cw20(_) => {
+                    payment_item = Some(PaymentAsset::try_from(offer_item.item.clone())?)
+                }
+                _ => {
+                    return Err(ContractError::CustomError {
+                        val: ("Invalid Offer funding type".to_string()),
+                    });
+                }
+            }
+        }
+
+        // the sender must accept exactly the stored offer, to prevent front-running by offerer
+        let offered_amount = match &payment_item {
+            Some(PaymentAsset::Cw20 { amount, .. }) => *amount,
+            _ => 0,
+        };
+        if order_nfts != offered_nfts || offered_amount != funds_amount {
+            return Err(ContractError::CustomError {
+                val: ("Offer does not match".to_string()),
+            });
+        }
+
+        // the wanted nft cannot be swapped while it is auctioned
+        self.check_not_auctioned(deps.as_ref(), &contract_address, &token_id)?;
+
+        // the collections may have been blocked since the offer was created
+        self.check_collection_allowed(deps.storage, &contract_address)?;
+        for nft in order_nfts.iter() {
+            self.check_collection_allowed(deps.storage, &nft.contract_address)?;
+        }
+
+        // query the owner of the nft
+        let owner: cw721::OwnerOfResponse = deps
+            .querier
+            .query_wasm_smart(
+                &contract_address,
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: token_id.clone(),
+                    include_expired: Some(false),
+                },
+            )
+            .map_err(|_| ContractError::CustomError {
+                val: ("Nft not exist".to_string()),
+            })?;
+
+        // if the nft is not belong to the info.sender, then return error
+        if owner.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // transfer the nft of the sender to the offerer
+        let mut res = Response::new().add_message(WasmMsg::Execute {
+            contract_addr: contract_address.to_string(),
+            msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                recipient: order_components.offerer.to_string(),
+                token_id: token_id.clone(),
+            })?,
+            funds: vec![],
+        });
+        self.listings
+            .remove(deps.storage, listing_key(&contract_address, &token_id))?;
+
+        // transfer the offered nfts to the sender, the transfer fails if the offerer
+        // does not own or approve an offered nft anymore
+        for nft in order_nfts {
+            let offered_token_id = nft.token_id.unwrap_or_default();
+            res = res.add_message(WasmMsg::Execute {
+                contract_addr: nft.contract_address.to_string(),
+                msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                    recipient: info.sender.to_string(),
+                    token_id: offered_token_id.clone(),
+                })?,
+                funds: vec![],
+            });
+            self.listings.remove(
+                deps.storage,
+                listing_key(&nft.contract_address, &offered_token_id),
+            )?;
+        }
+
+        // the offered funds pay the royalty of the nft of the sender
+        let mut protocol_fee = Uint128::zero();
+        if let Some(payment_item) = payment_item {
+            let (payment_messages, fee) = self.payment_with_royalty(
+                &deps,
+                &contract_address,
+                &token_id,
+                payment_item,
+                Some(&order_components.offerer),
+                &info.sender,
+            )?;
+            res = res.add_messages(payment_messages);
+            protocol_fee = fee;
+        }
+
+        // After the offer is accepted, we will delete the order
+        self.swap_offers.remove(deps.storage, order_key)?;
+
+        Ok(res
+            .add_attribute("method", "execute_accept_swap_offer")
+            .add_attribute("owner", owner.owner)
+            .add_attribute("offerer", order_components.offerer)
+            .add_attribute("nft_contract_address", contract_address.to_string())
+            .add_attribute("token_id", token_id)
+            .add_attribute("protocol_fee", protocol_fee))
+    }
+
+    // transfer the nft of the sender to the offerer and pay the offered funds to the sender
+    // the nft must belong to the collection of the consideration item of the offer
+    fn accept_offer(
+        &self,
+        deps: DepsMut,
+        env: &Env,
+        info: &MessageInfo,
+        order_key: OrderKey,
+        token_id: &str,
+        funds_amount: u128,
+    ) -> Result<Response, ContractError> {
+        // get order components
+        let order_components = self.offers.load(deps.storage, order_key.clone())?;
+
+        // the offer is cancelled if the offerer has incremented its counter
+        if self.is_cancelled_offer(deps.storage, &order_components) {
+            return Err(ContractError::CustomError {
+                val: ("Offer is cancelled".to_string()),
+            });
+        }
+
+        if !order_components.is_started(&env.block) {
+            return Err(ContractError::CustomError {
+                val: ("Offer not started".to_string()),
+            });
+        }
+
+        // if the end time of the offer is expired, then return error
+        if order_components.end_time.unwrap().is_expired(&env.block) {
+            return Err(ContractError::CustomError {
+                val: ("Offer is expired".to_string()),
+            });
+        }
+
+        let contract_address = match &order_components.consideration[0].item {
+            // match if the consideration item is Nft
+            Asset::Nft(NFT {
+                contract_address, ..
+            }) => contract_address,
+            // match if the consideration item is any Nft having the traits
+            Asset::TraitNft(TraitNFT {
+                contract_address,
+                traits,
+            }) => {
+                if !nft_has_traits(&deps, contract_address, token_id, traits) {
+                    return Err(ContractError::CustomError {
+                        val: ("Nft does not match the traits of the offer".to_string()),
+                    });
+                }
+                contract_address
+            }
+            // if the consideration item is not Nft, then return error
+            _ => {
+                return Err(ContractError::CustomError {
+                    val: ("Consideration is not NFT".to_string()),
+                })
+            }
+        };
+
+        // the collection may have been blocked since the offer was created
+        self.check_collection_allowed(deps.storage, contract_address)?;
+        // the nft escrowed by an auction contract can only be sold in the auction
+        self.check_not_auctioned(deps.as_ref(), contract_address, token_id)?;
+
+        // query the owner of the nft
+        let owner: cw721::OwnerOfResponse = deps
+            .querier
+            .query_wasm_smart(
+                contract_address,
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: token_id.to_string(),
+                    include_expired: Some(false),
+                },
+            )
+            .map_err(|_| ContractError::CustomError {
+                val: ("Nft not exist".to_string()),
+            })?;
+
+        // if the nft is not belong to the info.sender, then return error
+        if owner.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let mut res: Response = Response::new();
+
+        // ***********************
+        // TRANSFER CW20 TO SENDER
+        // ***********************
+        // convert Asset to PaymentAsset
+        let payment_item = PaymentAsset::try_from(order_components.offer[0].item.clone())?;
+
+        // execute cw20 transfer msg from offerer to info.sender
+        let protocol_fee = match payment_item {
+            PaymentAsset::Cw20 {
+                contract_address: token_address,
+                amount,
+            } => {
+                // a time-varying offer pays its amount at the current block,
+                // which must not be less than the amount expected by the sender
+                let current_amount = order_components.current_offer_amount(&env.block);
+                let matched = if order_components.is_time_varying() {
+                    current_amount >= funds_amount
+                } else {
+                    funds_amount == amount
+                };
+                if !matched {
+                    return Err(ContractError::CustomError {
+                        val: ("Insufficient funds".to_string()),
+                    });
+                }
+                let payment_item = PaymentAsset::Cw20 {
+                    contract_address: token_address,
+                    amount: current_amount,
+                };
+                let (payment_messages, protocol_fee) = self.payment_with_royalty(
+                    &deps,
+                    contract_address,
+                    token_id,
+                    payment_item.clone(),
+                    Some(&order_components.offerer),
+                    &info.sender,
+                )?;
+
+                self.record_sale(
+                    deps.storage,
+                    Sale {
+                        id: 0,
+                        contract_address: contract_address.clone(),
+                        token_id: token_id.to_string(),
+                        price: payment_item,
+                        buyer: order_components.offerer.clone(),
+                        seller: info.sender.clone(),
+                        time: env.block.time,
+                        kind: SaleKind::Offer,
+                    },
+                )?;
+
+                // loop through all payment messages and add item to response to execute
+                for payment_message in payment_messages {
+                    res = res.add_message(payment_message);
+                }
+                protocol_fee
+            }
+            _ => {
+                return Err(ContractError::CustomError {
+                    val: ("Invalid Offer funding type".to_string()),
+                });
+            }
+        };
+
+        // ***********************
+        // TRANSFER NFT TO OFFERER
+        // ***********************
+        // message to transfer nft to offerer
+        let transfer_nft_msg = WasmMsg::Execute {
+            contract_addr: contract_address.to_string(),
+            msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                recipient: order_components.offerer.to_string(),
+                token_id: token_id.to_string(),
+            })?,
+            funds: vec![],
+        };
+
+        // add transfer nft message to response to execute
+        res = res.add_message(transfer_nft_msg);
+
+        // After the offer is accepted, we will delete the order
+        self.offers.remove(deps.storage, order_key)?;
+
+        let listing_key = listing_key(contract_address, &token_id.to_string());
+        self.listings.remove(deps.storage, listing_key)?;
+
+        Ok(res
+            .add_attribute("owner", owner.owner)
+            .add_attribute("offerer", order_components.offerer)
+            .add_attribute("nft_contract_address", contract_address.to_string())
+            .add_attribute("token_id", token_id)
+            .add_attribute("protocol_fee", protocol_fee))
+    }
+
+    // fulfill an order signed by its maker, the order is verified by the signature
+    // instead of being stored in this contract
+    pub fn execute_fulfill_signed_order(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        order: OrderComponents,
+        signature: Binary,
+        public_key: Binary,
+    ) -> Result<Response, ContractError> {
+        let maker = order.offerer.clone();
+
+        // cannot fulfill own order
+        if info.sender == maker {
+            return Err(ContractError::CustomError {
+                val: ("Cannot fulfill own order".to_string()),
+            });
+        }
+
+        // the public key must belong to the maker, the address uses the same prefix as the maker
+        let prefix = maker
+            .as_str()
+            .rsplit_once('1')
+            .map(|(prefix, _)| prefix)
+            .unwrap_or_default();
+        if pubkey_to_address(&public_key, prefix)? != maker {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let order_hash = self.signed_order_hash(&env, &order)?;
+        let verified = deps
+            .api
+            .secp256k1_verify(&order_hash, &signature, &public_key)
+            .map_err(|_| ContractError::Unauthorized {})?;
+        if !verified {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // the order must be signed with the current counter of the maker
+        if self.is_cancelled_offer(deps.storage, &order) {
+            return Err(ContractError::CustomError {
+                val: ("Order is cancelled".to_string()),
+            });
+        }
+
+        if self
+            .fulfilled_orders
+            .has(deps.storage, order_hash.as_slice())
+        {
+            return Err(ContractError::CustomError {
+                val: ("Order is already fulfilled".to_string()),
+            });
+        }
+        self.fulfilled_orders
+            .save(deps.storage, order_hash.as_slice(), &true)?;
+
+        if let Some(start_time) = order.start_time {
+            if !start_time.is_expired(&env.block) {
+                return Err(ContractError::CustomError {
+                    val: ("Order not started".to_string()),
+                });
+            }
+        }
+        if let Some(end_time) = order.end_time {
+            if end_time.is_expired(&env.block) {
+                return Err(ContractError::CustomError {
+                    val: ("Order is expired".to_string()),
+                });
+            }
+        }
+
+        if order.offer.len() != 1 || order.consideration.len() != 1 {
+            return Err(ContractError::CustomError {
+                val: ("Invalid signed order".to_string()),
+            });
+        }
+
+        let (nft, payment, buyer, seller) = match (
+            &order.order_type,
+            &order.offer[0].item,
+            &order.consideration[0].item,
+        ) {
+            // the maker sells the nft for the native token paid by the sender
+            (OrderType::LISTING, Asset::Nft(nft), Asset::Native(price)) => {
+                let paid = Coin {
+                    denom: price.denom.clone(),
+                    amount: Uint128::from(price.amount),
+                };
+                if info.funds.len() != 1 || info.funds[0] != paid {
+                    return Err(ContractError::InsufficientFunds {});
+                }
+                (
+                    nft,
+                    PaymentAsset::Native {
+                        denom: price.denom.clone(),
+                        amount: price.amount,
+                    },
+                    info.sender.clone(),
+                    maker,
+                )
+            }
+            // the maker buys the nft of the sender with the vaura token
+            (OrderType::OFFER, Asset::Cw20(funds), Asset::Nft(nft)) => {
+                let config = self.config.load(deps.storage)?;
+                if funds.contract_address != config.vaura_address {
+                    return Err(ContractError::OfferTokenTypeInvalid {});
+                }
+                (
+                    nft,
+                    PaymentAsset::Cw20 {
+                        contract_address: funds.contract_address.clone(),
+                        amount: funds.amount,
+                    },
+                    maker,
+                    info.sender.clone(),
+                )
+            }
+            _ => {
+                return Err(ContractError::CustomError {
+                    val: ("Invalid signed order".to_string()),
+                });
+            }
+        };
+
+        let token_id = nft.token_id.clone().ok_or(ContractError::CustomError {
+            val: ("Token id is required".to_string()),
+        })?;
+        self.check_collection_allowed(deps.storage, &nft.contract_address)?;
+
+        // the nft must be owned by the seller and approved to this contract
+        let owner: cw721::OwnerOfResponse = deps
+            .querier
+            .query_wasm_smart(
+                &nft.contract_address,
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: token_id.clone(),
+                    include_expired: Some(false),
+                },
+            )
+            .map_err(|_| ContractError::NftNotFound {})?;
+        if owner.owner != seller {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // message to transfer nft to buyer
+        let mut res = Response::new().add_message(WasmMsg::Execute {
+            contract_addr: nft.contract_address.to_string(),
+            msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                recipient: buyer.to_string(),
+                token_id: token_id.clone(),
+            })?,
+            funds: vec![],
+        });
+
+        // a signed listing is recorded as a bought listing, a signed offer as an accepted offer
+        self.record_sale(
+            deps.storage,
+            Sale {
+                id: 0,
+                contract_address: nft.contract_address.clone(),
+                token_id: token_id.clone(),
+                price: payment.clone(),
+                buyer: buyer.clone(),
+                seller: seller.clone(),
+                time: env.block.time,
+                kind: match order.order_type {
+                    OrderType::OFFER => SaleKind::Offer,
+                    _ => SaleKind::Listing,
+                },
+            },
+        )?;
+
+        // the native token is paid from the funds received by this contract,
+        // the vaura token is transferred from the maker by allowance
+        let payer = match payment {
+            PaymentAsset::Native { .. } => None,
+            PaymentAsset::Cw20 { .. } => Some(&buyer),
+        };
+        let (payment_messages, protocol_fee) = self.payment_with_royalty(
+            &deps,
+            &nft.contract_address,
+            &token_id,
+            payment,
+            payer,
+            &seller,
+        )?;
+        res = res.add_messages(payment_messages);
+
+        // the listing of the sold nft is not valid anymore
+        self.listings
+            .remove(deps.storage, listing_key(&nft.contract_address, &token_id))?;
+
+        Ok(res
+            .add_attribute("method", "fulfill_signed_order")
+            .add_attribute("order_hash", order_hash.to_base64())
+            .add_attribute("maker", order.offerer)
+            .add_attribute("taker", info.sender)
+            .add_attribute("nft_contract_address", nft.contract_address.to_string())
+            .add_attribute("token_id", token_id)
+            .add_attribute("protocol_fee", protocol_fee))
+    }
+
+    // increment the counter of the sender to cancel all its listings, bundles and offers,
+    // and invalidate all its signed orders
+    pub fn execute_increment_counter(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+    ) -> Result<Response, ContractError> {
+        let counter = self.counters.update(
+            deps.storage,
+            info.sender.clone(),
+            |counter| -> StdResult<u64> { Ok(counter.unwrap_or_default() + 1) },
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("method", "increment_counter")
+            .add_attribute("user", info.sender)
+            .add_attribute("counter", counter.to_string()))
+    }
+
+    pub fn execute_cancel_offer(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        nfts: Vec<NFT>,
+    ) -> Result<Response, ContractError> {
+        // if the number of nfts is greater than 50, then return error
+        if nfts.len() > 50 {
+            return Err(ContractError::CustomError {
+                val: ("Number of NFTs is greater than 50".to_string()),
+            });
+        }
+
+        // loop through all nfts
+        for nft in nfts {
+            // generate order key based on the sender address, nft.contract_address and nft.token_id
+            // the collection offer is cancelled by the nft without token id
+            let order_key = order_key(
+                &info.sender,
+                &nft.contract_address,
+                nft.token_id.as_deref().unwrap_or(COLLECTION_OFFER_TOKEN_ID),
+            );
+
+            self.remove_offer(&self.offers, deps.storage, order_key)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("method", "cancel_all_offer")
+            .add_attribute("user", info.sender.to_string())
+            .add_attribute("cancelled_at", env.block.time.to_string()))
+    }
+
+    pub fn execute_cancel_trait_offer(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        contract_address: Addr,
+        traits: Vec<NftTrait>,
+    ) -> Result<Response, ContractError> {
+        let order_key = order_key(
+            &info.sender,
+            &contract_address,
+            &trait_offer_token_id(&traits),
+        );
+        self.remove_offer(&self.offers, deps.storage, order_key)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "cancel_trait_offer")
+            .add_attribute("user", info.sender.to_string())
+            .add_attribute("nft_contract_address", contract_address.to_string())
+            .add_attribute("cancelled_at", env.block.time.to_string()))
+    }
+
+    // cancel the swap offers of the sender for the wanted nfts
+    pub fn execute_cancel_swap_offer(
+        &self,
+        deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        nfts: Vec<NFT>,
+    ) -> Result<Response, ContractError> {
+        // if the number of nfts is greater than 50, then return error
+        if nfts.len() > 50 {
+            return Err(ContractError::CustomError {
+                val: ("Number of NFTs is greater than 50".to_string()),
+            });
+        }
+
+        for nft in nfts {
+            let order_key = order_key(
+                &info.sender,
+                &nft.contract_address,
+                nft.token_id.as_deref().unwrap_or_default(),
+            );
+            self.remove_offer(&self.swap_offers, deps.storage, order_key)?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("method", "cancel_swap_offer")
+            .add_attribute("user", info.sender.to_string())
+            .add_attribute("cancelled_at", env.block.time.to_string()))
+    }
+
+    // remove a cancelled offer from the offers map
+    fn remove_offer(
+        &self,
+        offers: &IndexedMap<'static, OrderKey, OrderComponents, OfferIndexes<'static>>,
+        storage: &mut dyn Storage,
+        order_key: OrderKey,
+    ) -> Result<(), ContractError> {
+        // check if the order exists
+        if !offers.has(storage, order_key.clone()) {
+            return Err(ContractError::CustomError {
+                val: ("Offer does not exist".to_string()),
+            });
+        }
+
+        offers.remove(storage, order_key)?;
+        Ok(())
+    }
+
+    pub fn execute_edit_vaura_token(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_address: String,
+    ) -> Result<Response, ContractError> {
+        // get owner
+        let mut conf = self.config.load(deps.storage)?;
+
+        // check if the sender is the owner
+        if conf.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // update vaura address in config
+        conf.vaura_address = Addr::unchecked(&token_address);
+        // conf.vaura_address = deps.api.addr_validate(&token_address)?;
+
+        // save config
+        self.config.save(deps.storage, &conf)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "edit_vaura_token")
+            .add_attribute("vaura_token_address", token_address))
+    }
+
+    pub fn execute_add_payment_token(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_address: String,
+    ) -> Result<Response, ContractError> {
+        // check if the sender is the owner
+        let conf = self.config.load(deps.storage)?;
+        if conf.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        let token_address = deps.api.addr_validate(&token_address)?;
+        self.payment_tokens
+            .save(deps.storage, token_address.clone(), &true)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "add_payment_token")
+            .add_attribute("token_address", token_address))
+    }
+
+    pub fn execute_remove_payment_token(
+        &self,
+        deps: DepsMut,
+        _env: Env,
+        info: MessageInfo,
+        token_address: String,
+    ) -> Result<Response, ContractError> {
+        // check if the sender is the owner
+        let conf = self.config.load(deps.storage)?;
+        if conf.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
+
+        // the existing listings priced in this token can still be bought
+        let token_address = deps.api.addr_validate(&token_address)?;
+        self.payment_tokens
+            .remove(deps.storage, token_address.clone());
+
+        Ok(Response::new()
+            .add_attribute("method", "remove_payment_token")
+            .add_attribute("token_address", token_address))
+    }
+
+    pub fn execute_update_config(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        protocol_fee_bps: Option<u16>,
+        fee_collector: Option<String>,
+        registry_mode: Option<RegistryMode>,
+    ) -> Result<Response, ContractError> {
+        // check if the sender is the owner
+        let mut conf = self.config.load(deps.storage)?;
+        if conf.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
         }
 
-        // check if the end time is valid
-        if end_time.is_expired(&env.block) {
-            return Err(ContractError::InvalidEndTime {});
+        if let Some(protocol_fee_bps) = protocol_fee_bps {
+            if protocol_fee_bps > MAX_PROTOCOL_FEE_BPS {
+                return Err(ContractError::CustomError {
+                    val: ("Invalid protocol fee".to_string()),
+                });
+            }
+            conf.protocol_fee_bps = protocol_fee_bps;
+        }
+        if let Some(fee_collector) = fee_collector {
+            conf.fee_collector = Some(deps.api.addr_validate(&fee_collector)?);
+        }
+        if let Some(registry_mode) = registry_mode {
+            conf.registry_mode = registry_mode;
         }
-        // ***********
-        // OFFERING FUNDS
-        // ***********
-        // load config
-        let config = self.config.load(deps.storage)?;
 
-        let token_address = config.vaura_address;
-        let amount = funds_amount;
+        // the fee cannot be charged without a collector
+        if conf.protocol_fee_bps > 0 && conf.fee_collector.is_none() {
+            return Err(ContractError::CustomError {
+                val: ("Fee collector is not set".to_string()),
+            });
+        }
 
-        // check that the allowance of the cw20 offer token is enough
-        let allowance_response: AllowanceResponse = deps
-            .querier
-            .query_wasm_smart(
-                &token_address,
-                &Cw20QueryMsg::Allowance {
-                    owner: info.sender.to_string(),
-                    spender: env.contract.address.to_string(),
-                },
+        self.config.save(deps.storage, &conf)?;
+
+        Ok(Response::new()
+            .add_attribute("method", "update_config")
+            .add_attribute("protocol_fee_bps", conf.protocol_fee_bps.to_string())
+            .add_attribute(
+                "fee_collector",
+                conf.fee_collector
+                    .map(|addr| addr.to_string())
+                    .unwrap_or_default(),
             )
-            .unwrap();
+            .add_attribute("registry_mode", format!("{:?}", conf.registry_mode)))
+    }
 
-        // check if the allowance is greater or equal the offer amount
-        if allowance_response.allowance < Uint128::from(amount) {
-            return Err(ContractError::InsufficientAllowance {});
+    // allow or block a collection, only the owner can register a collection
+    pub fn execute_register_collection(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        collection: RegisteredCollection,
+    ) -> Result<Response, ContractError> {
+        let conf = self.config.load(deps.storage)?;
+        if conf.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
         }
 
-        let contract_address = nft.contract_address;
-        let token_id = nft.token_id;
-        if let Some(token_id) = token_id {
-            // query the owner of the nft to check if the nft exist
-            let owner_response: StdResult<cw721::OwnerOfResponse> = deps.querier.query_wasm_smart(
-                &contract_address,
-                &Cw721QueryMsg::OwnerOf {
-                    token_id: token_id.clone(),
-                    include_expired: Some(false),
-                },
-            );
+        self.collections.save(
+            deps.storage,
+            collection.contract_address.clone(),
+            &collection,
+        )?;
 
-            match owner_response {
-                Ok(owner) => {
-                    if owner.owner == info.sender {
-                        return Err(ContractError::CustomError {
-                            val: ("Cannot offer owned nft".to_string()),
-                        });
-                    }
-                }
-                Err(_) => {
-                    return Err(ContractError::CustomError {
-                        val: ("Nft not exist".to_string()),
-                    });
-                }
-            }
+        Ok(Response::new()
+            .add_attribute("method", "register_collection")
+            .add_attribute("contract_address", collection.contract_address)
+            .add_attribute("status", format!("{:?}", collection.status)))
+    }
 
-            // generate order key for order components based on user address, contract address and token id
-            let order_key = order_key(&info.sender, &contract_address, &token_id);
+    // remove a collection from the registry, only the owner can unregister a collection
+    pub fn execute_unregister_collection(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        contract_address: Addr,
+    ) -> Result<Response, ContractError> {
+        let conf = self.config.load(deps.storage)?;
+        if conf.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
 
-            // the offer item will contain the infomation of cw20 token
-            let offer_item = offer_item(
-                &ItemType::CW20,
-                &Asset::Cw20(CW20 {
-                    contract_address: token_address,
-                    amount,
-                }),
-                &0u128,
-                &0u128,
-            );
+        if !self.collections.has(deps.storage, contract_address.clone()) {
+            return Err(ContractError::CustomError {
+                val: ("Collection is not registered".to_string()),
+            });
+        }
+        self.collections
+            .remove(deps.storage, contract_address.clone());
 
-            // the consideration item will contain the infomation of nft
-            let consideration_item = consideration_item(
-                &ItemType::CW721,
-                &Asset::Nft(NFT {
-                    contract_address,
-                    token_id: Some(token_id),
-                }),
-                &0u128,
-                &0u128,
-                &info.sender,
-            );
+        Ok(Response::new()
+            .add_attribute("method", "unregister_collection")
+            .add_attribute("contract_address", contract_address))
+    }
 
-            // generate order components
-            let order_offer = OrderComponents {
-                order_type: OrderType::OFFER, // The type of offer must be OFFER
-                order_id: order_key.clone(),
-                offerer: info.sender,
-                offer: [offer_item].to_vec(),
-                consideration: [consideration_item].to_vec(),
-                start_time: None,
-                end_time: Some(end_time),
-            };
+    // in allow mode only the allowed collections can be listed and offered,
+    // in block mode all collections except the blocked ones
+    fn check_collection_allowed(
+        &self,
+        storage: &dyn Storage,
+        contract_address: &Addr,
+    ) -> Result<(), ContractError> {
+        let conf = self.config.load(storage)?;
+        let status = self
+            .collections
+            .may_load(storage, contract_address.clone())?
+            .map(|collection| collection.status);
+        let allowed = match conf.registry_mode {
+            RegistryMode::Allow => status == Some(CollectionStatus::Allowed),
+            RegistryMode::Block => status != Some(CollectionStatus::Blocked),
+        };
+        if !allowed {
+            return Err(ContractError::CustomError {
+                val: ("Collection is not allowed".to_string()),
+            });
+        }
+        Ok(())
+    }
 
-            // we will override the order if it already exists
-            let new_offer = self.offers.update(
-                deps.storage,
-                order_key,
-                |_old| -> Result<OrderComponents, ContractError> { Ok(order_offer) },
-            )?;
+    // register an auction contract, only the owner can register an auction contract
+    // the code id must be the current code id of the contract
+    pub fn execute_add_auction_contract(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        auction_contract: AuctionContract,
+    ) -> Result<Response, ContractError> {
+        let conf = self.config.load(deps.storage)?;
+        if conf.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
 
-            let offer_str = serde_json::to_string(&new_offer.offer);
-            let consideration_str = serde_json::to_string(&new_offer.consideration);
-
-            // return success
-            Ok(Response::new()
-                .add_attribute("method", "create_offer")
-                .add_attribute("order_type", "OFFER")
-                .add_attribute("offerer", new_offer.offerer)
-                .add_attribute("offer", offer_str.unwrap())
-                .add_attribute("consideration", consideration_str.unwrap())
-                .add_attribute("end_time", new_offer.end_time.unwrap().to_string()))
-        } else {
-            // if the token_id is not exist, then this order is offer for a collection of nft
-            // we will handle this in the next version => return error for now
-            Err(ContractError::CustomError {
-                val: ("Collection offer is not supported".to_string()),
-            })
+        let contract_info = deps
+            .querier
+            .query_wasm_contract_info(auction_contract.contract_address.clone())?;
+        if contract_info.code_id != u64::from(auction_contract.code_id) {
+            return Err(ContractError::CustomError {
+                val: ("Code id does not match the auction contract".to_string()),
+            });
         }
+
+        // an auction type and a code id are routed to a single contract
+        self.auction_contracts.save(
+            deps.storage,
+            auction_contract.contract_address.clone(),
+            &auction_contract,
+        )?;
+
+        Ok(Response::new()
+            .add_attribute("method", "add_auction_contract")
+            .add_attribute("contract_address", auction_contract.contract_address)
+            .add_attribute("code_id", auction_contract.code_id.to_string())
+            .add_attribute("name", auction_contract.name))
     }
 
-    // function to accept offer nft using ordering style
-    pub fn execute_accept_nft_offer(
-        self,
+    // remove an auction contract, only the owner can remove an auction contract
+    pub fn execute_remove_auction_contract(
+        &self,
         deps: DepsMut,
-        env: Env,
         info: MessageInfo,
-        offerer: Addr,
-        nft: NFT,
-        funds_amount: u128,
+        contract_address: Addr,
     ) -> Result<Response, ContractError> {
-        let contract_address = nft.contract_address;
-        let token_id = nft.token_id;
+        let conf = self.config.load(deps.storage)?;
+        if conf.owner != info.sender {
+            return Err(ContractError::Unauthorized {});
+        }
 
-        // cannot accept own offer
-        if info.sender == offerer {
+        if !self
+            .auction_contracts
+            .has(deps.storage, contract_address.clone())
+        {
             return Err(ContractError::CustomError {
-                val: ("Cannot accept own offer".to_string()),
+                val: ("Auction contract is not registered".to_string()),
             });
         }
+        self.auction_contracts
+            .remove(deps.storage, contract_address.clone())?;
 
-        // if the token_id is exist, then this order is offer for a specific nft
-        if let Some(token_id) = token_id {
-            // generate order key for order components based on user address, contract address and token id
-            let order_key = order_key(&offerer, &contract_address, &token_id);
+        Ok(Response::new()
+            .add_attribute("method", "remove_auction_contract")
+            .add_attribute("contract_address", contract_address))
+    }
+
+    // a registered auction contract removes the listing of a nft when it escrows the nft for an auction,
+    // the listing cannot be bought anymore since its seller is not the owner of the nft
+    pub fn execute_remove_auctioned_listing(
+        &self,
+        deps: DepsMut,
+        info: MessageInfo,
+        contract_address: Addr,
+        token_id: String,
+    ) -> Result<Response, ContractError> {
+        if !self
+            .auction_contracts
+            .has(deps.storage, info.sender.clone())
+        {
+            return Err(ContractError::Unauthorized {});
+        }
 
-            // get order components
-            let order_components = self.offers.load(deps.storage, order_key.clone())?;
+        let listing_key = listing_key(&contract_address, &token_id);
+        let mut res = Response::new();
 
-            // if the end time of the offer is expired, then return error
-            if order_components.end_time.unwrap().is_expired(&env.block) {
+        if let Some(listing) = self.listings.may_load(deps.storage, listing_key.clone())? {
+            // the marketplace holds the nft of an escrowed listing, so it cannot be auctioned
+            if listing.escrow {
                 return Err(ContractError::CustomError {
-                    val: ("Offer is expired".to_string()),
+                    val: ("Escrowed listing cannot be removed".to_string()),
                 });
             }
 
-            match &order_components.consideration[0].item {
-                // match if the consideration item is Nft
-                Asset::Nft(NFT {
-                    contract_address,
-                    token_id,
-                }) => {
-                    // query the owner of the nft
-                    let owner: cw721::OwnerOfResponse = deps
-                        .querier
-                        .query_wasm_smart(
-                            contract_address,
-                            &Cw721QueryMsg::OwnerOf {
-                                token_id: token_id.clone().unwrap(),
-                                include_expired: Some(false),
-                            },
-                        )
-                        .unwrap();
-
-                    // if the nft is not belong to the info.sender, then return error
-                    if owner.owner != info.sender {
-                        return Err(ContractError::Unauthorized {});
-                    }
-
-                    let mut res: Response = Response::new();
-
-                    // ***********************
-                    // TRANSFER CW20 TO SENDER
-                    // ***********************
-                    // convert Asset to PaymentAsset
-                    let payment_item = PaymentAsset::from(order_components.offer[0].item.clone());
-
-                    // execute cw20 transfer msg from offerer to info.sender
-                    match &payment_item {
-                        PaymentAsset::Cw20 {
-                            contract_address: _,
-                            amount,
-                        } => {
-                            if funds_amount != *amount {
-                                return Err(ContractError::CustomError {
-                                    val: ("Insufficient funds".to_string()),
-                                });
-                            }
-                            let payment_messages = self.payment_with_royalty(
-                                &deps,
-                                contract_address,
-                                token_id.as_ref().unwrap(),
-                                payment_item.clone(),
-                                &offerer,
-                                &info.sender,
-                            );
-
-                            // loop through all payment messages and add item to response to execute
-                            for payment_message in payment_messages {
-                                res = res.add_message(payment_message);
-                            }
-                        }
-                        _ => {
-                            return Err(ContractError::CustomError {
-                                val: ("Invalid Offer funding type".to_string()),
-                            });
-                        }
-                    }
-
-                    // ***********************
-                    // TRANSFER NFT TO OFFERER
-                    // ***********************
-                    // message to transfer nft to offerer
-                    let transfer_nft_msg = WasmMsg::Execute {
-                        contract_addr: contract_address.clone().to_string(),
-                        msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
-                            recipient: order_components.offerer.clone().to_string(),
-                            token_id: token_id.clone().unwrap(),
-                        })?,
-                        funds: vec![],
-                    };
-
-                    // add transfer nft message to response to execute
-                    res = res.add_message(transfer_nft_msg);
-
-                    // After the offer is accepted, we will delete the order
-                    self.offers.remove(deps.storage, order_key)?;
-
-                    let listing_key = listing_key(contract_address, &token_id.clone().unwrap());
-                    self.listings.remove(deps.storage, listing_key)?;
-
-                    Ok(res
-                        .add_attribute("method", "execute_accept_nft_offer")
-                        .add_attribute("owner", owner.owner)
-                        .add_attribute("offerer", order_components.offerer)
-                        .add_attribute("nft_contract_address", contract_address.to_string())
-                        .add_attribute("token_id", token_id.clone().unwrap()))
-                }
-                // if the consideration item is not Nft, then return error
-                _ => Err(ContractError::CustomError {
-                    val: ("Consideration is not NFT".to_string()),
-                }),
+            // the auction of a listing routed by this marketplace keeps its listing
+            if self.auction_state(deps.as_ref(), &listing)?.is_none() {
+                self.listings.remove(deps.storage, listing_key)?;
+                res = res.add_attribute("seller", listing.seller);
             }
-        } else {
-            Err(ContractError::CustomError {
-                val: ("Collection offer is not supported".to_string()),
-            })
         }
+
+        Ok(res
+            .add_attribute("method", "remove_auctioned_listing")
+            .add_attribute("contract_address", contract_address)
+            .add_attribute("token_id", token_id))
     }
 
-    pub fn execute_cancel_offer(
+    // route the listing of a nft to the auction contract registered for the auction type,
+    // the nft is transferred to the auction contract which creates the auction
+    pub fn execute_list_nft_in_auction(
         &self,
         deps: DepsMut,
         env: Env,
         info: MessageInfo,
-        nfts: Vec<NFT>,
+        nft: NFT,
+        auction_type: String,
+        auction_config: Binary,
     ) -> Result<Response, ContractError> {
-        // if the number of nfts is greater than 50, then return error
-        if nfts.len() > 50 {
-            return Err(ContractError::CustomError {
-                val: ("Number of NFTs is greater than 50".to_string()),
-            });
-        }
+        let token_id = nft.token_id.clone().unwrap_or_default();
 
-        // loop through all nfts
-        for nft in nfts {
-            // generate order key based on the sender address, nft.contract_address and nft.token_id
-            let order_key = order_key(&info.sender, &nft.contract_address, &nft.token_id.unwrap());
+        self.check_not_auctioned(deps.as_ref(), &nft.contract_address, &token_id)?;
+        self.check_collection_allowed(deps.storage, &nft.contract_address)?;
+        self.check_owner_and_approval(&deps, &env, &info.sender, &nft.contract_address, &token_id)?;
 
-            // check if the order exists
-            if !self.offers.has(deps.storage, order_key.clone()) {
-                return Err(ContractError::CustomError {
-                    val: ("Offer does not exist".to_string()),
-                });
-            }
+        let messages =
+            self.auction_messages(&deps, &info.sender, &nft, &auction_type, auction_config)?;
 
-            // we will remove the cancelled offer
-            self.offers.remove(deps.storage, order_key)?;
-        }
+        // the listing of the nft ends when it is escrowed by the auction contract
+        self.listings
+            .remove(deps.storage, listing_key(&nft.contract_address, &token_id))?;
 
         Ok(Response::new()
-            .add_attribute("method", "cancel_all_offer")
-            .add_attribute("user", info.sender.to_string())
-            .add_attribute("cancelled_at", env.block.time.to_string()))
+            .add_messages(messages)
+            .add_attribute("method", "list_nft_in_auction")
+            .add_attribute("contract_address", nft.contract_address)
+            .add_attribute("token_id", token_id)
+            .add_attribute("auction_type", auction_type)
+            .add_attribute("seller", info.sender))
     }
 
-    pub fn execute_edit_vaura_token(
+    // the messages transferring a nft to the auction contract of the auction type
+    // and creating its auction, the registered code id is sent with the config
+    // so that a migrated auction contract can decide to process it or reject it
+    fn auction_messages(
+        &self,
+        deps: &DepsMut,
+        seller: &Addr,
+        nft: &NFT,
+        auction_type: &str,
+        auction_config: Binary,
+    ) -> Result<Vec<WasmMsg>, ContractError> {
+        let auction_contract = self
+            .auction_contracts
+            .idx
+            .name
+            .item(deps.storage, auction_type.to_string())?
+            .map(|(_, auction_contract)| auction_contract)
+            .ok_or(ContractError::CustomError {
+                val: ("Auction type is not supported".to_string()),
+            })?;
+
+        Ok(vec![
+            WasmMsg::Execute {
+                contract_addr: nft.contract_address.to_string(),
+                msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                    recipient: auction_contract.contract_address.to_string(),
+                    token_id: nft.token_id.clone().unwrap_or_default(),
+                })?,
+                funds: vec![],
+            },
+            WasmMsg::Execute {
+                contract_addr: auction_contract.contract_address.to_string(),
+                msg: to_binary(&AuctionContractExecuteMsg::CreateAuction {
+                    seller: seller.to_string(),
+                    nft: nft.clone(),
+                    auction_config,
+                    code_id: auction_contract.code_id,
+                })?,
+                funds: vec![],
+            },
+        ])
+    }
+
+    // save again a page of the listings, then of the offers, so that the listings and offers
+    // stored before an upgrade are added to the indexes of this version
+    pub fn execute_rebuild_indexes(
         &self,
         deps: DepsMut,
-        _env: Env,
         info: MessageInfo,
-        token_address: String,
+        start_after: Option<RebuildIndexesCursor>,
+        limit: Option<u32>,
     ) -> Result<Response, ContractError> {
-        // get owner
-        let mut conf = self.config.load(deps.storage)?;
-
         // check if the sender is the owner
+        let conf = self.config.load(deps.storage)?;
         if conf.owner != info.sender {
             return Err(ContractError::Unauthorized {});
         }
+        let limit = limit.unwrap_or(30).clamp(1, 100) as usize;
+
+        // the listings are rebuilt before the offers
+        let (listings_start, offers_start) = match start_after {
+            None => (Some(None), None),
+            Some(RebuildIndexesCursor::Listing {
+                contract_address,
+                token_id,
+            }) => (
+                Some(Some(Bound::exclusive(listing_key(
+                    &deps.api.addr_validate(&contract_address)?,
+                    &token_id,
+                )))),
+                None,
+            ),
+            Some(RebuildIndexesCursor::Offer {
+                offerer,
+                contract_address,
+                token_id,
+            }) => (
+                None,
+                Some(Bound::exclusive(order_key(
+                    &deps.api.addr_validate(&offerer)?,
+                    &deps.api.addr_validate(&contract_address)?,
+                    &token_id,
+                ))),
+            ),
+        };
 
-        // update vaura address in config
-        conf.vaura_address = Addr::unchecked(&token_address);
-        // conf.vaura_address = deps.api.addr_validate(&token_address)?;
+        let mut next: Option<RebuildIndexesCursor> = None;
+        let mut rebuilt_listings = 0;
+        let mut rebuilt_offers = 0;
+
+        // the listings are skipped when the page starts after an offer
+        if let Some(listings_start) = listings_start {
+            let listings = self
+                .listings
+                .range(deps.storage, listings_start, None, Order::Ascending)
+                .take(limit)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (listing_key, listing) in &listings {
+                self.listings
+                    .save(deps.storage, listing_key.clone(), listing)?;
+            }
+            rebuilt_listings = listings.len();
+            if rebuilt_listings == limit {
+                next = listings
+                    .last()
+                    .map(|(_, listing)| RebuildIndexesCursor::Listing {
+                        contract_address: listing.contract_address.to_string(),
+                        token_id: listing.token_id.clone(),
+                    });
+            }
+        }
 
-        // save config
-        self.config.save(deps.storage, &conf)?;
+        // the offers fill the rest of the page
+        if next.is_none() {
+            let offers = self
+                .offers
+                .range(deps.storage, offers_start, None, Order::Ascending)
+                .take(limit - rebuilt_listings)
+                .collect::<StdResult<Vec<_>>>()?;
+            for (order_key, offer) in &offers {
+                self.offers.save(deps.storage, order_key.clone(), offer)?;
+            }
+            rebuilt_offers = offers.len();
+            if rebuilt_listings + rebuilt_offers == limit {
+                next = offers
+                    .last()
+                    .map(|(order_key, _)| RebuildIndexesCursor::Offer {
+                        offerer: order_key.0.to_string(),
+                        contract_address: order_key.1.to_string(),
+                        token_id: order_key.2.clone(),
+                    });
+            }
+        }
 
         Ok(Response::new()
-            .add_attribute("method", "edit_vaura_token")
-            .add_attribute("vaura_token_address", token_address))
+            .set_data(to_binary(&RebuildIndexesResponse { next })?)
+            .add_attribute("method", "rebuild_indexes")
+            .add_attribute("listings", rebuilt_listings.to_string())
+            .add_attribute("offers", rebuilt_offers.to_string()))
     }
 
-    // function to process payment transfer with royalty
+    // function to process payment transfer with royalty and protocol fee
+    // the payment is transferred from the sender by allowance,
+    // or from the funds held by this contract if the sender is None
+    // returns the payment messages and the protocol fee charged
     fn payment_with_royalty(
         &self,
         deps: &DepsMut,
         nft_contract_address: &Addr,
         nft_id: &str,
         token: PaymentAsset,
-        sender: &Addr,
+        sender: Option<&Addr>,
         recipient: &Addr,
-    ) -> Vec<CosmosMsg> {
-        // create empty vector of CosmosMsg
-        let mut res_messages: Vec<CosmosMsg> = vec![];
-
-        // Extract information from token
-        let (is_native, token_info, amount) = match token {
-            PaymentAsset::Cw20 {
-                contract_address: token_address,
-                amount,
-            } => (false, token_address.to_string(), Uint128::from(amount)),
-            PaymentAsset::Native { denom, amount } => (true, denom, Uint128::from(amount)),
-        };
+    ) -> StdResult<(Vec<CosmosMsg>, Uint128)> {
+        let amount = Uint128::from(token.amount());
 
-        // get cw2981 royalties info
-        let royalty_query_msg = Cw2981QueryMsg::Extension {
-            msg: cw2981_royalties::msg::Cw2981QueryMsg::RoyaltyInfo {
-                token_id: nft_id.into(),
-                sale_price: amount,
+        // the protocol fee is paid to the fee collector
+        let config = self.config.load(deps.storage)?;
+        let protocol_fee = config
+            .protocol_fee(amount)
+            .filter(|(_, fee_amount)| !fee_amount.is_zero());
+
+        let res_messages = payment_with_royalty(
+            deps.as_ref(),
+            &NftPayment {
+                nft_contract_address,
+                token_id: nft_id,
+                token: &token,
+                sender,
+                recipient,
+                protocol_fee: protocol_fee
+                    .as_ref()
+                    .map(|(fee_collector, fee_amount)| (fee_collector, *fee_amount)),
             },
-        };
+        )?;
 
-        let royalty_info_rsp: Result<RoyaltiesInfoResponse, cosmwasm_std::StdError> =
-            deps.querier.query(&QueryRequest::Wasm(WasmQuery::Smart {
-                contract_addr: nft_contract_address.to_string(),
-                msg: to_binary(&royalty_query_msg).unwrap(),
-            }));
+        Ok((
+            res_messages,
+            protocol_fee
+                .map(|(_, fee_amount)| fee_amount)
+                .unwrap_or_default(),
+        ))
+    }
 
-        let (creator, royalty_amount): (Option<Addr>, Option<Uint128>) = match royalty_info_rsp {
-            Ok(RoyaltiesInfoResponse {
-                address,
-                royalty_amount,
-            }) => {
-                if address.is_empty() || royalty_amount == Uint128::zero() {
-                    (None, None)
-                } else {
-                    (
-                        Some(deps.api.addr_validate(&address).unwrap()),
-                        Some(royalty_amount),
-                    )
-                }
+    // record a sale in the history and the stats of its nft and its collection,
+    // the oldest sale is removed when a history is full
+    fn record_sale(&self, storage: &mut dyn Storage, mut sale: Sale) -> StdResult<()> {
+        sale.id = self.sale_count.may_load(storage)?.unwrap_or_default() + 1;
+        self.sale_count.save(storage, &sale.id)?;
+
+        let mut stats = self
+            .collection_stats
+            .may_load(storage, sale.contract_address.clone())?
+            .unwrap_or_default();
+        stats.add_sale(&sale.price);
+        self.collection_stats
+            .save(storage, sale.contract_address.clone(), &stats)?;
+
+        let nft = (sale.contract_address.clone(), sale.token_id.clone());
+        let count = self
+            .nft_sale_counts
+            .may_load(storage, nft.clone())?
+            .unwrap_or_default();
+        if count >= MAX_NFT_SALES {
+            let oldest = self
+                .nft_sales
+                .prefix(nft.clone())
+                .keys(storage, None, None, Order::Ascending)
+                .next()
+                .transpose()?;
+            if let Some(id) = oldest {
+                self.nft_sales
+                    .remove(storage, (nft.0.clone(), nft.1.clone(), id));
             }
-            Err(_) => (None, None),
-        };
-
-        // there is no royalty, creator is the recipient, or royalty amount is 0
-        if creator.is_none()
-            || *creator.as_ref().unwrap() == *recipient
-            || royalty_amount.is_none()
-            || royalty_amount.unwrap().is_zero()
-        {
-            match &is_native {
-                false => {
-                    // execute cw20 transfer msg from info.sender to recipient
-                    let transfer_response = WasmMsg::Execute {
-                        contract_addr: deps.api.addr_validate(&token_info).unwrap().to_string(),
-                        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                            owner: sender.to_string(),
-                            recipient: recipient.to_string(),
-                            amount,
-                        })
-                        .unwrap(),
-                        funds: vec![],
-                    };
-                    res_messages.push(transfer_response.into());
-                }
-                true => {
-                    // transfer all funds to recipient
-                    let transfer_response = BankMsg::Send {
-                        to_address: recipient.to_string(),
-                        amount: vec![Coin {
-                            denom: token_info,
-                            amount,
-                        }],
-                    };
-                    res_messages.push(transfer_response.into());
-                }
-            }
-        } else if let (Some(creator), Some(royalty_amount)) = (creator, royalty_amount) {
-            match &is_native {
-                false => {
-                    // execute cw20 transfer transfer royalty to creator
-                    let transfer_token_creator_response = WasmMsg::Execute {
-                        contract_addr: deps.api.addr_validate(&token_info).unwrap().to_string(),
-                        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                            owner: sender.to_string(),
-                            recipient: creator.to_string(),
-                            amount: royalty_amount,
-                        })
-                        .unwrap(),
-                        funds: vec![],
-                    };
-                    res_messages.push(transfer_token_creator_response.into());
-
-                    // execute cw20 transfer remaining funds to recipient
-                    let transfer_token_seller_msg = WasmMsg::Execute {
-                        contract_addr: deps.api.addr_validate(&token_info).unwrap().to_string(),
-                        msg: to_binary(&Cw20ExecuteMsg::TransferFrom {
-                            owner: sender.to_string(),
-                            recipient: recipient.to_string(),
-                            amount: amount - royalty_amount,
-                        })
-                        .unwrap(),
-                        funds: vec![],
-                    };
-                    res_messages.push(transfer_token_seller_msg.into());
-                }
-                true => {
-                    // transfer royalty to creator
-                    let transfer_token_creator_response = BankMsg::Send {
-                        to_address: creator.to_string(),
-                        amount: vec![Coin {
-                            denom: token_info.clone(),
-                            amount: royalty_amount,
-                        }],
-                    };
-                    res_messages.push(transfer_token_creator_response.into());
-
-                    // transfer remaining funds to recipient
-                    let transfer_token_seller_msg = BankMsg::Send {
-                        to_address: recipient.to_string(),
-                        amount: vec![Coin {
-                            denom: token_info,
-                            amount: amount - royalty_amount,
-                        }],
-                    };
-                    res_messages.push(transfer_token_seller_msg.into());
-                }
+        } else {
+            self.nft_sale_counts
+                .save(storage, nft.clone(), &(count + 1))?;
+        }
+        self.nft_sales
+            .save(storage, (nft.0, nft.1, sale.id), &sale)?;
+
+        let collection = sale.contract_address.clone();
+        let count = self
+            .collection_sale_counts
+            .may_load(storage, collection.clone())?
+            .unwrap_or_default();
+        if count >= MAX_COLLECTION_SALES {
+            let oldest = self
+                .collection_sales
+                .prefix(collection.clone())
+                .keys(storage, None, None, Order::Ascending)
+                .next()
+                .transpose()?;
+            if let Some(id) = oldest {
+                self.collection_sales
+                    .remove(storage, (collection.clone(), id));
             }
+        } else {
+            self.collection_sale_counts
+                .save(storage, collection.clone(), &(count + 1))?;
         }
-
-        res_messages
+        self.collection_sales
+            .save(storage, (collection, sale.id), &sale)
     }
 }
+
+// create the message to transfer the escrowed nft of a listing back to the seller
+fn return_escrowed_nft_msg(listing: &Listing) -> StdResult<WasmMsg> {
+    Ok(WasmMsg::Execute {
+        contract_addr: listing.contract_address.to_string(),
+        msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+            recipient: listing.seller.to_string(),
+            token_id: listing.token_id.clone(),
+        })?,
+        funds: vec![],
+    })
+}
+
+// check if the cw2981 metadata of a nft contains all the traits
+fn nft_has_traits(
+    deps: &DepsMut,
+    contract_address: &Addr,
+    token_id: &str,
+    traits: &[NftTrait],
+) -> bool {
+    let nft_info: StdResult<cw721::NftInfoResponse<Cw2981Extension>> =
+        deps.querier.query_wasm_smart(
+            contract_address,
+            &Cw721QueryMsg::NftInfo {
+                token_id: token_id.to_string(),
+            },
+        );
+
+    let attributes = match nft_info {
+        Ok(cw721::NftInfoResponse {
+            extension:
+                Some(Metadata {
+                    attributes: Some(attributes),
+                    ..
+                }),
+            ..
+        }) => attributes,
+        _ => return false,
+    };
+
+    traits.iter().all(|t| {
+        attributes
+            .iter()
+            .any(|attribute| attribute.trait_type == t.trait_type && attribute.value == t.value)
+    })
+}
+
+// derive the address of a compressed secp256k1 public key with the bech32 prefix
+fn pubkey_to_address(public_key: &[u8], prefix: &str) -> StdResult<Addr> {
+    let hash = Ripemd160::digest(Sha256::digest(public_key));
+    let address = bech32::encode(prefix, hash.to_base32(), Variant::Bech32)
+        .map_err(|err| StdError::generic_err(err.to_string()))?;
+    Ok(Addr::unchecked(address))
+}
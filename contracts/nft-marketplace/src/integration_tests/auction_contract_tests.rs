@@ -0,0 +1,908 @@
+use crate::msg::{
+    AuctionContractsResponse, ExecuteMsg, ListingResponse, ListingsResponse, QueryMsg,
+};
+use crate::state::{AuctionConfig, AuctionContract, AuctionState};
+
+use crate::test_setup::env::{
+    instantiate_contracts, mint_and_approve_nft, nft_auction_contract_template, ContractInfo,
+    NATIVE_DENOM, OWNER, USER_1, USER_2,
+};
+
+use anyhow::Result as AnyResult;
+use cosmwasm_std::{coin, to_binary, Addr, Binary, StdResult};
+use cw_multi_test::{App, AppResponse, Executor};
+
+use cw2981_royalties::{Metadata, QueryMsg as Cw2981QueryMsg};
+use cw721::{Expiration as Cw721Expiration, OwnerOfResponse};
+use cw721_base::msg::ExecuteMsg as Cw721ExecuteMsg;
+use nft_auction::msg::{
+    ExecuteMsg as AuctionExecuteMsg, MigrateMsg as AuctionMigrateMsg, QueryMsg as AuctionQueryMsg,
+};
+use nft_auction::state::{AuctionConfigInput, AuctionOrder, NFT as AuctionNFT};
+
+const MOCK_NFT_TOKEN_ID: &str = "token1";
+const ENGLISH_AUCTION: &str = "english_auction";
+
+fn add_auction_contract(
+    app: &mut App,
+    sender: &str,
+    marketplace_address: &str,
+    auction_address: &str,
+    code_id: u64,
+) -> AnyResult<AppResponse> {
+    app.execute_contract(
+        Addr::unchecked(sender),
+        Addr::unchecked(marketplace_address),
+        &ExecuteMsg::AddAuctionContract {
+            contract_address: auction_address.to_string(),
+            code_id: code_id as u32,
+            name: ENGLISH_AUCTION.to_string(),
+        },
+        &[],
+    )
+}
+
+fn english_auction_config(app: &App) -> Binary {
+    to_binary(&AuctionConfigInput::EnglishAuction {
+        start_price: coin(1000, NATIVE_DENOM),
+        step_percentage: None,
+        buyout_price: None,
+        start_time: None,
+        end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+    })
+    .unwrap()
+}
+
+fn list_nft_in_auction(
+    app: &mut App,
+    cw2981_address: &str,
+    marketplace_address: &str,
+    auction_type: &str,
+) -> AnyResult<AppResponse> {
+    let list_msg = ExecuteMsg::ListNftInAuction {
+        contract_address: cw2981_address.to_string(),
+        token_id: MOCK_NFT_TOKEN_ID.to_string(),
+        auction_type: auction_type.to_string(),
+        auction_config: english_auction_config(app),
+    };
+    app.execute_contract(
+        Addr::unchecked(USER_1),
+        Addr::unchecked(marketplace_address),
+        &list_msg,
+        &[],
+    )
+}
+
+fn query_nft_owner(app: &App, cw2981_address: &str) -> String {
+    let res: OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            cw2981_address,
+            &Cw2981QueryMsg::OwnerOf {
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    res.owner
+}
+
+mod auction_contract_registry {
+    use super::*;
+
+    #[test]
+    fn owner_can_add_and_remove_auction_contract() {
+        let (mut app, contracts) = instantiate_contracts();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let auction_address = contracts[4].contract_addr.clone();
+        let auction_code_id = contracts[4].contract_code_id;
+
+        // only the owner can add an auction contract
+        let res = add_auction_contract(
+            &mut app,
+            USER_1,
+            &marketplace_address,
+            &auction_address,
+            auction_code_id,
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Unauthorized"
+        );
+
+        // the code id must be the code id of the auction contract
+        let res = add_auction_contract(
+            &mut app,
+            OWNER,
+            &marketplace_address,
+            &auction_address,
+            auction_code_id + 1,
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Code id does not match the auction contract\""
+        );
+
+        add_auction_contract(
+            &mut app,
+            OWNER,
+            &marketplace_address,
+            &auction_address,
+            auction_code_id,
+        )
+        .unwrap();
+
+        let res: AuctionContractsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                marketplace_address.clone(),
+                &QueryMsg::AuctionContracts {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res.auction_contracts,
+            vec![AuctionContract {
+                contract_address: Addr::unchecked(auction_address.clone()),
+                code_id: auction_code_id as u32,
+                name: ENGLISH_AUCTION.to_string(),
+            }]
+        );
+
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::RemoveAuctionContract {
+                contract_address: auction_address,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let res: AuctionContractsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                marketplace_address,
+                &QueryMsg::AuctionContracts {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(res.auction_contracts.is_empty());
+    }
+}
+
+mod list_nft_in_auction {
+    use super::*;
+
+    #[test]
+    fn user_can_list_nft_in_auction_contract() {
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let auction_address = contracts[4].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_1,
+            MOCK_NFT_TOKEN_ID,
+        );
+        add_auction_contract(
+            &mut app,
+            OWNER,
+            &marketplace_address,
+            &auction_address,
+            contracts[4].contract_code_id,
+        )
+        .unwrap();
+
+        list_nft_in_auction(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            ENGLISH_AUCTION,
+        )
+        .unwrap();
+
+        // the nft is held by the auction contract, which auctions it for the seller
+        assert_eq!(query_nft_owner(&app, &cw2981_address), auction_address);
+        let auction: AuctionOrder = app
+            .wrap()
+            .query_wasm_smart(
+                auction_address,
+                &AuctionQueryMsg::NftAuction {
+                    contract_address: cw2981_address,
+                    token_id: MOCK_NFT_TOKEN_ID.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(auction.offer[0].offerer, Addr::unchecked(USER_1));
+    }
+
+    #[test]
+    fn cannot_list_nft_with_unregistered_auction_type() {
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_1,
+            MOCK_NFT_TOKEN_ID,
+        );
+
+        let res = list_nft_in_auction(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            ENGLISH_AUCTION,
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Auction type is not supported\""
+        );
+        assert_eq!(query_nft_owner(&app, &cw2981_address), USER_1);
+    }
+
+    #[test]
+    fn migrated_auction_contract_decides_to_accept_config() {
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let auction_address = contracts[4].contract_addr.clone();
+        let old_code_id = contracts[4].contract_code_id;
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_1,
+            MOCK_NFT_TOKEN_ID,
+        );
+        add_auction_contract(
+            &mut app,
+            OWNER,
+            &marketplace_address,
+            &auction_address,
+            old_code_id,
+        )
+        .unwrap();
+
+        // the auction contract is upgraded to a new code
+        let new_code_id = app.store_code(nft_auction_contract_template());
+        app.migrate_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(auction_address.clone()),
+            &AuctionMigrateMsg {},
+            new_code_id,
+        )
+        .unwrap();
+
+        // the configs written for the old code are rejected by default
+        let res = list_nft_in_auction(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            ENGLISH_AUCTION,
+        );
+        // the error of the auction contract is the root cause of the failed listing
+        assert_eq!(
+            res.unwrap_err().root_cause().to_string(),
+            "Custom Error val: \"Auction config code id is not accepted\""
+        );
+
+        // the new code is a performance upgrade, it accepts the configs of the old code
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(auction_address.clone()),
+            &AuctionExecuteMsg::UpdateConfig {
+                marketplace: None,
+                accepted_code_ids: Some(vec![old_code_id as u32]),
+            },
+            &[],
+        )
+        .unwrap();
+
+        list_nft_in_auction(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            ENGLISH_AUCTION,
+        )
+        .unwrap();
+        assert_eq!(query_nft_owner(&app, &cw2981_address), auction_address);
+    }
+}
+
+mod english_auction {
+    use super::*;
+
+    const START_PRICE: u128 = 1000;
+
+    // register the auction contract, then the seller lists the nft in an english auction
+    fn list_english_auction(app: &mut App, contracts: &[ContractInfo]) -> AnyResult<AppResponse> {
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nft(
+            app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_1,
+            MOCK_NFT_TOKEN_ID,
+        );
+        add_auction_contract(
+            app,
+            OWNER,
+            &marketplace_address,
+            &contracts[4].contract_addr,
+            contracts[4].contract_code_id,
+        )
+        .unwrap();
+
+        let list_msg = ExecuteMsg::ListNft {
+            contract_address: cw2981_address,
+            token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            auction_config: AuctionConfig::EnglishAuction {
+                start_price: coin(START_PRICE, NATIVE_DENOM),
+                step_percentage: None,
+                buyout_price: None,
+                start_time: None,
+                end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+            },
+            escrow: false,
+            reserved_buyers: vec![],
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address),
+            &list_msg,
+            &[],
+        )
+    }
+
+    fn query_listing(app: &App, contracts: &[ContractInfo]) -> StdResult<ListingResponse> {
+        app.wrap().query_wasm_smart(
+            contracts[1].contract_addr.clone(),
+            &QueryMsg::Listing {
+                contract_address: contracts[0].contract_addr.clone(),
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            },
+        )
+    }
+
+    #[test]
+    fn listing_shows_the_state_of_the_auction() {
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let auction_address = contracts[4].contract_addr.clone();
+
+        list_english_auction(&mut app, &contracts).unwrap();
+
+        // the nft is auctioned by the auction contract
+        assert_eq!(query_nft_owner(&app, &cw2981_address), auction_address);
+        let listing = query_listing(&app, &contracts).unwrap();
+        assert_eq!(listing.listing.seller, USER_1);
+        let end_time = listing.auction.as_ref().unwrap().end_time;
+        assert_eq!(
+            listing.auction,
+            Some(AuctionState {
+                seller: Addr::unchecked(USER_1),
+                current_price: coin(START_PRICE, NATIVE_DENOM),
+                highest_bidder: None,
+                end_time,
+            })
+        );
+
+        // USER_2 bids in the auction contract
+        app.update_block(|block| block.time = block.time.plus_seconds(10));
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(auction_address),
+            &AuctionExecuteMsg::BidAuction {
+                nft: AuctionNFT {
+                    contract_address: Addr::unchecked(cw2981_address),
+                    token_id: Some(MOCK_NFT_TOKEN_ID.to_string()),
+                },
+                bid_price: START_PRICE * 2,
+            },
+            &[coin(START_PRICE * 2, NATIVE_DENOM)],
+        )
+        .unwrap();
+
+        let listing = query_listing(&app, &contracts).unwrap();
+        let auction = listing.auction.unwrap();
+        assert_eq!(auction.current_price, coin(START_PRICE * 2, NATIVE_DENOM));
+        assert_eq!(auction.highest_bidder, Some(Addr::unchecked(USER_2)));
+    }
+
+    #[test]
+    fn auctioned_nft_cannot_be_listed_bought_or_cancelled() {
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        list_english_auction(&mut app, &contracts).unwrap();
+
+        // the nft cannot be listed again
+        let res = list_nft_in_auction(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            ENGLISH_AUCTION,
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Nft is auctioned\""
+        );
+
+        // the nft is bid in the auction contract
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::Buy {
+                contract_address: cw2981_address.clone(),
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            },
+            &[coin(START_PRICE, NATIVE_DENOM)],
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Auctioned nft must be bid in the auction contract\""
+        );
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address),
+            &ExecuteMsg::Cancel {
+                contract_address: cw2981_address,
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            },
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Auction cannot be cancelled\""
+        );
+    }
+
+    #[test]
+    fn listing_ends_when_the_auction_is_settled() {
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        list_english_auction(&mut app, &contracts).unwrap();
+
+        // there is no bid, the seller settles the auction and receives the nft back
+        app.update_block(|block| block.time = block.time.plus_seconds(2000));
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(contracts[4].contract_addr.clone()),
+            &AuctionExecuteMsg::SettleAuction {
+                nft: AuctionNFT {
+                    contract_address: Addr::unchecked(cw2981_address.clone()),
+                    token_id: Some(MOCK_NFT_TOKEN_ID.to_string()),
+                },
+            },
+            &[],
+        )
+        .unwrap();
+        assert_eq!(query_nft_owner(&app, &cw2981_address), USER_1);
+        assert!(query_listing(&app, &contracts).is_err());
+
+        // the settle removes the listing from the marketplace
+        let res: ListingsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                marketplace_address.clone(),
+                &QueryMsg::ListingsBySeller {
+                    seller: USER_1.to_string(),
+                    start_after_nft: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(res.listings.is_empty());
+
+        // the settled listing does not prevent the seller from listing the nft again
+        let approve_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::Approve {
+            spender: marketplace_address.clone(),
+            token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            expires: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(cw2981_address.clone()),
+            &approve_msg,
+            &[],
+        )
+        .unwrap();
+        list_nft_in_auction(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            ENGLISH_AUCTION,
+        )
+        .unwrap();
+    }
+}
+
+mod auction_conflicts {
+    use super::*;
+    use crate::order_state::NFT;
+    use cw_multi_test::{BankSudo, SudoMsg};
+
+    const OFFER_PRICE: u128 = 500;
+
+    // USER_2 offers for the nft with the bidding token of the marketplace
+    fn offer_nft(app: &mut App, contracts: &[ContractInfo]) {
+        app.sudo(SudoMsg::Bank(BankSudo::Mint {
+            to_address: USER_2.to_string(),
+            amount: vec![coin(OFFER_PRICE, NATIVE_DENOM)],
+        }))
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(contracts[2].contract_addr.clone()),
+            &cw20::Cw20ExecuteMsg::Mint {
+                recipient: USER_2.to_string(),
+                amount: OFFER_PRICE.into(),
+            },
+            &[coin(OFFER_PRICE, NATIVE_DENOM)],
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(contracts[1].contract_addr.clone()),
+            &ExecuteMsg::OfferNft {
+                nft: NFT {
+                    contract_address: Addr::unchecked(contracts[0].contract_addr.clone()),
+                    token_id: Some(MOCK_NFT_TOKEN_ID.to_string()),
+                },
+                funds_amount: OFFER_PRICE,
+                end_funds_amount: None,
+                start_time: None,
+                end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+            },
+            &[],
+        )
+        .unwrap();
+    }
+
+    // the seller auctions the nft directly in the auction contract
+    fn auction_nft(app: &mut App, contracts: &[ContractInfo]) -> AnyResult<AppResponse> {
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let auction_address = contracts[4].contract_addr.clone();
+
+        let approve_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::Approve {
+            spender: auction_address.clone(),
+            token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            expires: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(cw2981_address.clone()),
+            &approve_msg,
+            &[],
+        )
+        .unwrap();
+
+        let auction_config = AuctionConfigInput::EnglishAuction {
+            start_price: coin(1000, NATIVE_DENOM),
+            step_percentage: None,
+            buyout_price: None,
+            start_time: None,
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(auction_address),
+            &AuctionExecuteMsg::AuctionNft {
+                nft: AuctionNFT {
+                    contract_address: Addr::unchecked(cw2981_address),
+                    token_id: Some(MOCK_NFT_TOKEN_ID.to_string()),
+                },
+                auction_config,
+            },
+            &[],
+        )
+    }
+
+    #[test]
+    fn auctioned_nft_cannot_be_listed_or_sold_to_an_offer() {
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_1,
+            MOCK_NFT_TOKEN_ID,
+        );
+        add_auction_contract(
+            &mut app,
+            OWNER,
+            &marketplace_address,
+            &contracts[4].contract_addr,
+            contracts[4].contract_code_id,
+        )
+        .unwrap();
+        offer_nft(&mut app, &contracts);
+
+        let list_msg = ExecuteMsg::ListNft {
+            contract_address: cw2981_address.clone(),
+            token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            auction_config: AuctionConfig::FixedPrice {
+                price: coin(1000, NATIVE_DENOM),
+                start_time: None,
+                end_time: None,
+            },
+            escrow: false,
+            reserved_buyers: vec![],
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address.clone()),
+            &list_msg,
+            &[],
+        )
+        .unwrap();
+
+        // the auction contract removes the listing when it escrows the nft
+        auction_nft(&mut app, &contracts).unwrap();
+        assert_eq!(
+            query_nft_owner(&app, &cw2981_address),
+            contracts[4].contract_addr
+        );
+        let res: StdResult<ListingResponse> = app.wrap().query_wasm_smart(
+            marketplace_address.clone(),
+            &QueryMsg::Listing {
+                contract_address: cw2981_address.clone(),
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            },
+        );
+        assert!(res.is_err());
+
+        // the nft cannot be listed again while it is auctioned
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address.clone()),
+            &list_msg,
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Nft is auctioned\""
+        );
+
+        // the offer cannot be accepted while the nft is auctioned
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address),
+            &ExecuteMsg::AcceptNftOffer {
+                offerer: USER_2.to_string(),
+                nft: NFT {
+                    contract_address: Addr::unchecked(cw2981_address),
+                    token_id: Some(MOCK_NFT_TOKEN_ID.to_string()),
+                },
+                funds_amount: OFFER_PRICE,
+            },
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Nft is auctioned\""
+        );
+    }
+
+    #[test]
+    fn auctioned_nft_cannot_be_swapped() {
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_1,
+            MOCK_NFT_TOKEN_ID,
+        );
+        add_auction_contract(
+            &mut app,
+            OWNER,
+            &marketplace_address,
+            &contracts[4].contract_addr,
+            contracts[4].contract_code_id,
+        )
+        .unwrap();
+
+        // USER_2 offers its nft for the nft of USER_1
+        let offered_nft = NFT {
+            contract_address: Addr::unchecked(cw2981_address.clone()),
+            token_id: Some("token2".to_string()),
+        };
+        let wanted_nft = NFT {
+            contract_address: Addr::unchecked(cw2981_address.clone()),
+            token_id: Some(MOCK_NFT_TOKEN_ID.to_string()),
+        };
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            "token2",
+        );
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::OfferSwap {
+                nft: wanted_nft.clone(),
+                offered_nfts: vec![offered_nft.clone()],
+                funds_amount: 0,
+                end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+            },
+            &[],
+        )
+        .unwrap();
+
+        auction_nft(&mut app, &contracts).unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address),
+            &ExecuteMsg::AcceptSwapOffer {
+                offerer: USER_2.to_string(),
+                nft: wanted_nft,
+                offered_nfts: vec![offered_nft],
+                funds_amount: 0,
+            },
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Nft is auctioned\""
+        );
+    }
+
+    #[test]
+    fn only_registered_auction_contract_can_remove_listing() {
+        let (mut app, contracts) = instantiate_contracts();
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(contracts[1].contract_addr.clone()),
+            &ExecuteMsg::RemoveAuctionedListing {
+                contract_address: contracts[0].contract_addr.clone(),
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            },
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Unauthorized"
+        );
+
+        // the auction contract which is not registered still auctions the nft,
+        // but the marketplace rejects its callback and the listing is kept
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_1,
+            MOCK_NFT_TOKEN_ID,
+        );
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::ListNft {
+                contract_address: cw2981_address.clone(),
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+                auction_config: AuctionConfig::FixedPrice {
+                    price: coin(1000, NATIVE_DENOM),
+                    start_time: None,
+                    end_time: None,
+                },
+                escrow: false,
+                reserved_buyers: vec![],
+            },
+            &[],
+        )
+        .unwrap();
+
+        let res = auction_nft(&mut app, &contracts).unwrap();
+        assert!(res.events.iter().any(|event| event
+            .attributes
+            .iter()
+            .any(|attr| attr.value == "reply_remove_auctioned_listing")));
+        assert_eq!(
+            query_nft_owner(&app, &cw2981_address),
+            contracts[4].contract_addr
+        );
+        let res: StdResult<ListingResponse> = app.wrap().query_wasm_smart(
+            marketplace_address,
+            &QueryMsg::Listing {
+                contract_address: cw2981_address,
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            },
+        );
+        assert!(res.is_ok());
+    }
+
+    #[test]
+    fn auction_contract_cannot_remove_escrowed_listing() {
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let auction_address = contracts[4].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_1,
+            MOCK_NFT_TOKEN_ID,
+        );
+        add_auction_contract(
+            &mut app,
+            OWNER,
+            &marketplace_address,
+            &auction_address,
+            contracts[4].contract_code_id,
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::ListNft {
+                contract_address: cw2981_address.clone(),
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+                auction_config: AuctionConfig::FixedPrice {
+                    price: coin(1000, NATIVE_DENOM),
+                    start_time: None,
+                    end_time: None,
+                },
+                escrow: true,
+                reserved_buyers: vec![],
+            },
+            &[],
+        )
+        .unwrap();
+
+        // the escrowed nft stays in the marketplace with its listing
+        let res = app.execute_contract(
+            Addr::unchecked(auction_address),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::RemoveAuctionedListing {
+                contract_address: cw2981_address.clone(),
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            },
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Escrowed listing cannot be removed\""
+        );
+        assert_eq!(query_nft_owner(&app, &cw2981_address), marketplace_address);
+        let res: StdResult<ListingResponse> = app.wrap().query_wasm_smart(
+            marketplace_address,
+            &QueryMsg::Listing {
+                contract_address: cw2981_address,
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+            },
+        );
+        assert!(res.is_ok());
+    }
+}
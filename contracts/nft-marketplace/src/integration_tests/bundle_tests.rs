@@ -0,0 +1,341 @@
+use crate::msg::{BundlesResponse, BuyNftItem, ExecuteMsg, QueryMsg, SalesResponse};
+use crate::order_state::PaymentAsset;
+use crate::state::{Bundle, SaleKind};
+
+use crate::test_setup::env::{
+    instantiate_contracts, mint_and_approve_nft, NATIVE_DENOM, OWNER, USER_1, USER_2,
+};
+
+use anyhow::Result as AnyResult;
+
+use cosmwasm_std::{Addr, Coin, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+
+use cw2981_royalties::{Metadata, QueryMsg as Cw721QueryMsg};
+use cw721_base::msg::ExecuteMsg as Cw721ExecuteMsg;
+
+const MOCK_NFT_TOKEN_IDS: [&str; 2] = ["token1", "token2"];
+
+// the price is not divisible by the number of nfts
+const MOCK_BUNDLE_PRICE: u128 = 1001;
+
+fn mint_and_approve_nfts(app: &mut App, cw2981_address: &str, marketplace_address: &str) {
+    for token_id in MOCK_NFT_TOKEN_IDS {
+        mint_and_approve_nft(app, cw2981_address, marketplace_address, USER_2, token_id);
+    }
+}
+
+fn create_bundle(
+    app: &mut App,
+    seller: &str,
+    cw2981_address: &str,
+    marketplace_address: &str,
+    token_ids: &[&str],
+) -> AnyResult<AppResponse> {
+    let list_msg = ExecuteMsg::ListBundle {
+        nfts: token_ids
+            .iter()
+            .map(|token_id| BuyNftItem {
+                contract_address: cw2981_address.to_string(),
+                token_id: token_id.to_string(),
+            })
+            .collect(),
+        price: Coin {
+            denom: NATIVE_DENOM.to_string(),
+            amount: Uint128::from(MOCK_BUNDLE_PRICE),
+        },
+        end_time: None,
+    };
+    app.execute_contract(
+        Addr::unchecked(seller),
+        Addr::unchecked(marketplace_address),
+        &list_msg,
+        &[],
+    )
+}
+
+fn query_nft_owner(app: &App, cw2981_address: &str, token_id: &str) -> String {
+    let res: cw721::OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            Addr::unchecked(cw2981_address),
+            &Cw721QueryMsg::OwnerOf {
+                token_id: token_id.to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    res.owner
+}
+
+mod bundle {
+    use super::*;
+
+    #[test]
+    fn cannot_bundle_nfts_of_other() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nfts(&mut app, &cw2981_address, &marketplace_address);
+
+        let res = create_bundle(
+            &mut app,
+            USER_1,
+            &cw2981_address,
+            &marketplace_address,
+            &MOCK_NFT_TOKEN_IDS,
+        );
+        assert!(res.is_err());
+
+        // the same nft cannot be bundled twice
+        let res = create_bundle(
+            &mut app,
+            USER_2,
+            &cw2981_address,
+            &marketplace_address,
+            &[MOCK_NFT_TOKEN_IDS[0], MOCK_NFT_TOKEN_IDS[0]],
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn bundle_price_must_cover_each_nft() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nfts(&mut app, &cw2981_address, &marketplace_address);
+
+        // a price of 1 cannot be apportioned to 2 nfts
+        let list_msg = ExecuteMsg::ListBundle {
+            nfts: MOCK_NFT_TOKEN_IDS
+                .iter()
+                .map(|token_id| BuyNftItem {
+                    contract_address: cw2981_address.to_string(),
+                    token_id: token_id.to_string(),
+                })
+                .collect(),
+            price: Coin {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::from(1u128),
+            },
+            end_time: None,
+        };
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &list_msg,
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            "Custom Error val: \"Invalid bundle price\""
+        );
+    }
+
+    #[test]
+    fn buy_bundle_with_royalty() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nfts(&mut app, &cw2981_address, &marketplace_address);
+        create_bundle(
+            &mut app,
+            USER_2,
+            &cw2981_address,
+            &marketplace_address,
+            &MOCK_NFT_TOKEN_IDS,
+        )
+        .unwrap();
+
+        let res: BundlesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::Bundles {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.bundles.len(), 1);
+        assert_eq!(res.bundles[0].id, 1);
+        assert_eq!(res.bundles[0].nfts.len(), 2);
+
+        // the funds must match the price
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::BuyBundle { bundle_id: 1 },
+            &[Coin {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::from(MOCK_BUNDLE_PRICE - 1),
+            }],
+        );
+        assert!(res.is_err());
+
+        let seller_balance = app.wrap().query_balance(USER_2, NATIVE_DENOM).unwrap();
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::BuyBundle { bundle_id: 1 },
+            &[Coin {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::from(MOCK_BUNDLE_PRICE),
+            }],
+        );
+        assert!(res.is_ok());
+
+        // all nfts are transferred to the buyer
+        for token_id in MOCK_NFT_TOKEN_IDS {
+            assert_eq!(query_nft_owner(&app, &cw2981_address, token_id), OWNER);
+        }
+
+        // the royalty (20%) of each nft is paid on its share of the price (501 and 500)
+        let res = app.wrap().query_balance(USER_2, NATIVE_DENOM).unwrap();
+        assert_eq!(res.amount - seller_balance.amount, Uint128::from(801u128));
+
+        // each nft is recorded as a sale of its share of the price, the latest sale first
+        let res: SalesResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::SaleHistory {
+                    contract_address: cw2981_address.clone(),
+                    token_id: None,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        let sales: Vec<(String, PaymentAsset, SaleKind)> = res
+            .sales
+            .into_iter()
+            .map(|sale| (sale.token_id, sale.price, sale.kind))
+            .collect();
+        assert_eq!(
+            sales,
+            vec![
+                (
+                    MOCK_NFT_TOKEN_IDS[1].to_string(),
+                    PaymentAsset::Native {
+                        denom: NATIVE_DENOM.to_string(),
+                        amount: 500
+                    },
+                    SaleKind::Bundle
+                ),
+                (
+                    MOCK_NFT_TOKEN_IDS[0].to_string(),
+                    PaymentAsset::Native {
+                        denom: NATIVE_DENOM.to_string(),
+                        amount: 501
+                    },
+                    SaleKind::Bundle
+                ),
+            ]
+        );
+
+        // the bought bundle is removed
+        let res: cosmwasm_std::StdResult<Bundle> = app.wrap().query_wasm_smart(
+            Addr::unchecked(&marketplace_address),
+            &QueryMsg::Bundle { bundle_id: 1 },
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn only_seller_can_cancel_bundle() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nfts(&mut app, &cw2981_address, &marketplace_address);
+        create_bundle(
+            &mut app,
+            USER_2,
+            &cw2981_address,
+            &marketplace_address,
+            &MOCK_NFT_TOKEN_IDS,
+        )
+        .unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::CancelBundle { bundle_id: 1 },
+            &[],
+        );
+        assert!(res.is_err());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::CancelBundle { bundle_id: 1 },
+            &[],
+        );
+        assert!(res.is_ok());
+
+        // the cancelled bundle cannot be bought
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::BuyBundle { bundle_id: 1 },
+            &[Coin {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::from(MOCK_BUNDLE_PRICE),
+            }],
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn anyone_can_cancel_stale_bundle() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nfts(&mut app, &cw2981_address, &marketplace_address);
+        create_bundle(
+            &mut app,
+            USER_2,
+            &cw2981_address,
+            &marketplace_address,
+            &MOCK_NFT_TOKEN_IDS,
+        )
+        .unwrap();
+
+        // the seller transfers a nft of the bundle, so the bundle cannot be bought anymore
+        let transfer_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::TransferNft {
+            recipient: OWNER.to_string(),
+            token_id: MOCK_NFT_TOKEN_IDS[0].to_string(),
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&cw2981_address),
+            &transfer_msg,
+            &[],
+        )
+        .unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::CancelBundle { bundle_id: 1 },
+            &[],
+        );
+        assert!(res.is_ok());
+
+        let res: cosmwasm_std::StdResult<Bundle> = app.wrap().query_wasm_smart(
+            Addr::unchecked(&marketplace_address),
+            &QueryMsg::Bundle { bundle_id: 1 },
+        );
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use crate::msg::{Cw20HookMsg, ExecuteMsg, PaymentTokensResponse, QueryMsg};
+use crate::order_state::CW20;
+use crate::state::AuctionConfig;
+
+use crate::test_setup::env::{
+    instantiate_contracts, mint_and_approve_nft, OWNER, TOKEN_INITIAL_BALANCE, USER_1, USER_2,
+};
+
+use anyhow::Result as AnyResult;
+
+use cosmwasm_std::{to_binary, Addr, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+
+use cw20::{BalanceResponse, Cw20ExecuteMsg, Cw20QueryMsg};
+use cw2981_royalties::QueryMsg as Cw721QueryMsg;
+
+const MOCK_NFT_TOKEN_ID_1: &str = "token1";
+
+const MOCK_CW20_PRICE: u128 = 1000;
+
+fn create_cw20_listing(
+    app: &mut App,
+    token_id: &str,
+    seller: &str,
+    cw2981_address: String,
+    marketplace_address: String,
+    cw20_address: String,
+) -> AnyResult<AppResponse> {
+    let list_msg = ExecuteMsg::ListNft {
+        contract_address: cw2981_address,
+        token_id: token_id.to_string(),
+        auction_config: AuctionConfig::Cw20FixedPrice {
+            price: CW20 {
+                contract_address: Addr::unchecked(cw20_address),
+                amount: MOCK_CW20_PRICE,
+            },
+            start_time: None,
+            end_time: None,
+        },
+        escrow: false,
+        reserved_buyers: vec![],
+    };
+
+    (*app).execute_contract(
+        Addr::unchecked(seller.to_string()),
+        Addr::unchecked(marketplace_address),
+        &list_msg,
+        &[],
+    )
+}
+
+fn buy_with_cw20(
+    app: &mut App,
+    token_id: &str,
+    buyer: &str,
+    cw2981_address: String,
+    marketplace_address: String,
+    cw20_address: String,
+    amount: u128,
+) -> AnyResult<AppResponse> {
+    // the buyer sends cw20 token to the marketplace with the buy message attached
+    let send_msg = Cw20ExecuteMsg::Send {
+        contract: marketplace_address,
+        amount: Uint128::from(amount),
+        msg: to_binary(&Cw20HookMsg::Buy {
+            contract_address: cw2981_address,
+            token_id: token_id.to_string(),
+        })
+        .unwrap(),
+    };
+
+    (*app).execute_contract(
+        Addr::unchecked(buyer.to_string()),
+        Addr::unchecked(cw20_address),
+        &send_msg,
+        &[],
+    )
+}
+
+fn query_cw20_balance(app: &App, cw20_address: &str, address: &str) -> Uint128 {
+    let res: BalanceResponse = app
+        .wrap()
+        .query_wasm_smart(
+            Addr::unchecked(cw20_address),
+            &Cw20QueryMsg::Balance {
+                address: address.to_string(),
+            },
+        )
+        .unwrap();
+    res.balance
+}
+
+mod cw20_listing {
+    use super::*;
+
+    #[test]
+    fn only_owner_can_whitelist_payment_token() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let vaura_address = contracts[2].contract_addr.clone();
+        let cw20_address = contracts[3].contract_addr.clone();
+
+        let add_payment_token_msg = ExecuteMsg::AddPaymentToken {
+            token_address: vaura_address,
+        };
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address.clone()),
+            &add_payment_token_msg,
+            &[],
+        );
+        assert!(res.is_err());
+
+        // only the token whitelisted in setup is returned
+        let res: PaymentTokensResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(marketplace_address),
+                &QueryMsg::PaymentTokens {
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.tokens, vec![Addr::unchecked(cw20_address)]);
+    }
+
+    #[test]
+    fn cannot_list_with_not_whitelisted_cw20() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let vaura_address = contracts[2].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            MOCK_NFT_TOKEN_ID_1,
+        );
+
+        // the vaura token is not whitelisted to price listings
+        let res = create_cw20_listing(
+            &mut app,
+            MOCK_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address,
+            marketplace_address,
+            vaura_address,
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn buy_cw20_listing_with_royalty() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[3].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            MOCK_NFT_TOKEN_ID_1,
+        );
+
+        let res = create_cw20_listing(
+            &mut app,
+            MOCK_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+            cw20_address.clone(),
+        );
+        assert!(res.is_ok());
+
+        // the listing cannot be bought with native token
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::Buy {
+                contract_address: cw2981_address.clone(),
+                token_id: MOCK_NFT_TOKEN_ID_1.to_string(),
+            },
+            &[],
+        );
+        assert!(res.is_err());
+
+        // the amount must match the price
+        let res = buy_with_cw20(
+            &mut app,
+            MOCK_NFT_TOKEN_ID_1,
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+            cw20_address.clone(),
+            MOCK_CW20_PRICE - 1,
+        );
+        assert!(res.is_err());
+
+        let res = buy_with_cw20(
+            &mut app,
+            MOCK_NFT_TOKEN_ID_1,
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+            cw20_address.clone(),
+            MOCK_CW20_PRICE,
+        );
+        assert!(res.is_ok());
+
+        // assert NFT is transfered to USER_1
+        let res: cw721::OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw2981_address),
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: MOCK_NFT_TOKEN_ID_1.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, USER_1.to_string(), "Invalid owner");
+
+        // the royalty (20%) is paid to the creator (OWNER), the rest to the seller (USER_2)
+        assert_eq!(
+            query_cw20_balance(&app, &cw20_address, OWNER),
+            Uint128::from(200u128)
+        );
+        assert_eq!(
+            query_cw20_balance(&app, &cw20_address, USER_2),
+            Uint128::from(TOKEN_INITIAL_BALANCE + 800)
+        );
+        assert_eq!(
+            query_cw20_balance(&app, &cw20_address, USER_1),
+            Uint128::from(TOKEN_INITIAL_BALANCE - MOCK_CW20_PRICE)
+        );
+        assert_eq!(
+            query_cw20_balance(&app, &cw20_address, &marketplace_address),
+            Uint128::zero()
+        );
+    }
+}
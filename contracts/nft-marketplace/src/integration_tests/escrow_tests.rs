@@ -0,0 +1,183 @@
+use crate::msg::{BuyNftItem, ExecuteMsg, QueryMsg, ValidateResponse};
+use crate::state::AuctionConfig;
+
+use crate::test_setup::env::{
+    instantiate_contracts, mint_and_approve_nft, NATIVE_DENOM, OWNER, USER_2,
+};
+
+use anyhow::Result as AnyResult;
+
+use cosmwasm_std::{Addr, Coin, Uint128};
+use cw721::Expiration as Cw721Expiration;
+use cw_multi_test::{App, AppResponse, Executor};
+
+use cw2981_royalties::QueryMsg as Cw721QueryMsg;
+
+const MOCK_NFT_TOKEN_ID_1: &str = "token1";
+
+const MOCK_PRICE: u128 = 1000;
+
+fn create_escrowed_listing(
+    app: &mut App,
+    cw2981_address: &str,
+    marketplace_address: &str,
+    end_time: Option<Cw721Expiration>,
+) -> AnyResult<AppResponse> {
+    let list_msg = ExecuteMsg::ListNft {
+        contract_address: cw2981_address.to_string(),
+        token_id: MOCK_NFT_TOKEN_ID_1.to_string(),
+        auction_config: AuctionConfig::FixedPrice {
+            price: Coin {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::from(MOCK_PRICE),
+            },
+            start_time: None,
+            end_time,
+        },
+        escrow: true,
+        reserved_buyers: vec![],
+    };
+    app.execute_contract(
+        Addr::unchecked(USER_2),
+        Addr::unchecked(marketplace_address),
+        &list_msg,
+        &[],
+    )
+}
+
+fn query_nft_owner(app: &App, cw2981_address: &str) -> String {
+    let res: cw721::OwnerOfResponse = app
+        .wrap()
+        .query_wasm_smart(
+            Addr::unchecked(cw2981_address),
+            &Cw721QueryMsg::OwnerOf {
+                token_id: MOCK_NFT_TOKEN_ID_1.to_string(),
+                include_expired: None,
+            },
+        )
+        .unwrap();
+    res.owner
+}
+
+mod escrow {
+    use super::*;
+
+    #[test]
+    fn escrowed_nft_is_returned_on_cancel() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            MOCK_NFT_TOKEN_ID_1,
+        );
+        let res = create_escrowed_listing(&mut app, &cw2981_address, &marketplace_address, None);
+        assert!(res.is_ok());
+
+        // the nft is held by the marketplace and the listing stays valid
+        assert_eq!(query_nft_owner(&app, &cw2981_address), marketplace_address);
+        let res: ValidateResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::ValidateListing {
+                    contract_address: cw2981_address.clone(),
+                    token_id: MOCK_NFT_TOKEN_ID_1.to_string(),
+                },
+            )
+            .unwrap();
+        assert!(res.valid);
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::Cancel {
+                contract_address: cw2981_address.clone(),
+                token_id: MOCK_NFT_TOKEN_ID_1.to_string(),
+            },
+            &[],
+        );
+        assert!(res.is_ok());
+
+        assert_eq!(query_nft_owner(&app, &cw2981_address), USER_2);
+    }
+
+    #[test]
+    fn buy_escrowed_listing() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            MOCK_NFT_TOKEN_ID_1,
+        );
+        create_escrowed_listing(&mut app, &cw2981_address, &marketplace_address, None).unwrap();
+
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::Buy {
+                contract_address: cw2981_address.clone(),
+                token_id: MOCK_NFT_TOKEN_ID_1.to_string(),
+            },
+            &[Coin {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::from(MOCK_PRICE),
+            }],
+        );
+        assert!(res.is_ok());
+
+        assert_eq!(query_nft_owner(&app, &cw2981_address), OWNER);
+    }
+
+    #[test]
+    fn escrowed_nft_is_returned_when_expired_listing_is_pruned() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            MOCK_NFT_TOKEN_ID_1,
+        );
+        let end_time = Cw721Expiration::AtTime(app.block_info().time.plus_seconds(100));
+        create_escrowed_listing(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            Some(end_time),
+        )
+        .unwrap();
+
+        app.update_block(|block| block.time = block.time.plus_seconds(200));
+
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::PruneListings {
+                listings: vec![BuyNftItem {
+                    contract_address: cw2981_address.clone(),
+                    token_id: MOCK_NFT_TOKEN_ID_1.to_string(),
+                }],
+            },
+            &[],
+        );
+        assert!(res.is_ok());
+
+        assert_eq!(query_nft_owner(&app, &cw2981_address), USER_2);
+    }
+}
@@ -1,7 +1,10 @@
 use crate::contract::*;
-use crate::msg::{ExecuteMsg, InstantiateMsg, ListingsResponse, QueryMsg};
+use crate::msg::{
+    CollectionStatsResponse, ExecuteMsg, InstantiateMsg, ListingResponse, ListingsResponse,
+    QueryMsg,
+};
 use crate::order_state::{OrderComponents, NFT};
-use crate::state::{contract, AuctionConfig, Config};
+use crate::state::{contract, AuctionConfig, Config, MAX_SCANNED_LISTINGS};
 use crate::test_setup::env::{instantiate_contracts, NATIVE_DENOM, NATIVE_DENOM_2, OWNER, USER_1};
 use crate::ContractError;
 
@@ -244,6 +247,8 @@ mod listing {
                 start_time,
                 end_time,
             },
+            escrow: false,
+            reserved_buyers: vec![],
         };
         let info = mock_info(sender, &coins(1000, "uaura"));
         execute(deps, mock_env(), info, msg)
@@ -291,6 +296,8 @@ mod listing {
                 start_time: None,
                 end_time: None,
             },
+            escrow: false,
+            reserved_buyers: vec![],
         };
         let info = mock_info("another_user", &[]);
         let response = execute(deps.as_mut(), mock_env(), info, msg);
@@ -362,7 +369,7 @@ mod listing {
                 "1".to_string(),
             )
             .unwrap();
-        assert_eq!(listing.token_id, "1");
+        assert_eq!(listing.listing.token_id, "1");
 
         // cancel the listing
         let msg = ExecuteMsg::Cancel {
@@ -511,6 +518,104 @@ mod listing {
         assert_eq!(query_res.listings.len(), 4);
     }
 
+    #[test]
+    fn collection_stats_are_paged() {
+        let mut deps = mock_deps();
+
+        for i in 0..MAX_SCANNED_LISTINGS + 1 {
+            create_listing(
+                deps.as_mut(),
+                "owner",
+                Addr::unchecked(MOCK_CW2981_ADDR),
+                &format!("{:0>8}", i),
+                None,
+                None,
+            )
+            .unwrap();
+        }
+
+        // the first page stops after the maximum number of scanned listings
+        let query_msg = QueryMsg::CollectionStats {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            start_after: None,
+        };
+        let res = from_binary::<CollectionStatsResponse>(
+            &query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.listing_count, MAX_SCANNED_LISTINGS as u64);
+        let next = res.next.unwrap();
+        assert_eq!(next.token_id, format!("{:0>8}", MAX_SCANNED_LISTINGS - 1));
+
+        // the next page has the last listing
+        let query_msg = QueryMsg::CollectionStats {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            start_after: Some(next),
+        };
+        let res = from_binary::<CollectionStatsResponse>(
+            &query(deps.as_ref(), mock_env(), query_msg).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(res.listing_count, 1);
+        assert_eq!(res.next, None);
+    }
+
+    #[test]
+    fn listing_is_invalid_only_if_the_nft_contract_answers_so() {
+        let mut deps = mock_deps();
+
+        create_listing(
+            deps.as_mut(),
+            "owner",
+            Addr::unchecked(MOCK_CW2981_ADDR),
+            "1",
+            None,
+            None,
+        )
+        .unwrap();
+
+        let is_valid = |deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>| {
+            contract()
+                .query_validate_listing(
+                    deps.as_ref(),
+                    mock_env(),
+                    Addr::unchecked(MOCK_CW2981_ADDR),
+                    "1".to_string(),
+                )
+                .unwrap()
+                .valid
+        };
+        assert!(is_valid(&deps));
+
+        // a failing nft contract tells nothing about the nft
+        deps.querier.update_wasm(|_| {
+            cosmwasm_std::SystemResult::Ok(ContractResult::Err("out of gas".to_string()))
+        });
+        assert!(is_valid(&deps));
+
+        // the seller still owns the nft, but the approval is revoked
+        deps.querier.update_wasm(|query| match query {
+            WasmQuery::Smart { msg, .. } => match from_binary::<Cw721QueryMsg>(msg).unwrap() {
+                Cw721QueryMsg::OwnerOf { .. } => {
+                    let result = ContractResult::Ok(
+                        to_binary(&OwnerOfResponse {
+                            owner: "owner".to_string(),
+                            approvals: vec![],
+                        })
+                        .unwrap(),
+                    );
+                    cosmwasm_std::SystemResult::Ok(result)
+                }
+                _ => {
+                    let result = ContractResult::Err("Approval not found".to_string());
+                    cosmwasm_std::SystemResult::Ok(result)
+                }
+            },
+            _ => panic!("Unexpected query"),
+        });
+        assert!(!is_valid(&deps));
+    }
+
     #[test]
     fn cannot_buy_non_existent_listing() {
         let mut deps = mock_deps();
@@ -888,6 +993,1093 @@ mod listing {
     }
 }
 
+mod dutch_auction {
+    use super::*;
+    use crate::order_state::PaymentAsset;
+
+    fn create_dutch_listing(
+        deps: DepsMut,
+        sender: &str,
+        token_id: &str,
+        start_price: u128,
+        end_price: u128,
+        start_time: Cw721Expiration,
+        end_time: Cw721Expiration,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::ListNft {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: token_id.to_string(),
+            auction_config: AuctionConfig::DutchAuction {
+                start_price: cosmwasm_std::coin(start_price, "uaura"),
+                end_price: cosmwasm_std::coin(end_price, "uaura"),
+                start_time,
+                end_time,
+            },
+            escrow: false,
+            reserved_buyers: vec![],
+        };
+        let info = mock_info(sender, &[]);
+        execute(deps, mock_env(), info, msg)
+    }
+
+    #[test]
+    fn cannot_create_invalid_dutch_auction() {
+        let mut deps = mock_deps();
+
+        // the price must decline
+        let response = create_dutch_listing(
+            deps.as_mut(),
+            "owner",
+            "2",
+            100,
+            1000,
+            Cw721Expiration::AtTime(Timestamp::from_seconds(1000)),
+            Cw721Expiration::AtTime(Timestamp::from_seconds(2000)),
+        );
+        assert!(response.is_err());
+
+        // the times must be AtTime
+        let response = create_dutch_listing(
+            deps.as_mut(),
+            "owner",
+            "2",
+            1000,
+            100,
+            Cw721Expiration::AtHeight(1000),
+            Cw721Expiration::AtHeight(2000),
+        );
+        assert!(response.is_err());
+
+        // the start time must be before the end time
+        let response = create_dutch_listing(
+            deps.as_mut(),
+            "owner",
+            "2",
+            1000,
+            100,
+            Cw721Expiration::AtTime(Timestamp::from_seconds(2000)),
+            Cw721Expiration::AtTime(Timestamp::from_seconds(1000)),
+        );
+        assert!(response.is_err());
+    }
+
+    #[test]
+    fn price_declines_over_time() {
+        let mut deps = mock_deps();
+
+        create_dutch_listing(
+            deps.as_mut(),
+            "owner",
+            "2",
+            1000,
+            100,
+            Cw721Expiration::AtTime(Timestamp::from_seconds(1000)),
+            Cw721Expiration::AtTime(Timestamp::from_seconds(2000)),
+        )
+        .unwrap();
+
+        let query_price = |seconds: u64| -> PaymentAsset {
+            let mut env = mock_env();
+            env.block.time = Timestamp::from_seconds(seconds);
+            let query_msg = QueryMsg::ListingPrice {
+                contract_address: MOCK_CW2981_ADDR.to_string(),
+                token_id: "2".to_string(),
+            };
+            from_binary(&query(deps.as_ref(), env, query_msg).unwrap()).unwrap()
+        };
+
+        let expected_price = |amount: u128| PaymentAsset::Native {
+            denom: "uaura".to_string(),
+            amount,
+        };
+        assert_eq!(query_price(500), expected_price(1000));
+        assert_eq!(query_price(1000), expected_price(1000));
+        assert_eq!(query_price(1500), expected_price(550));
+        assert_eq!(query_price(2000), expected_price(100));
+        assert_eq!(query_price(3000), expected_price(100));
+    }
+
+    #[test]
+    fn cannot_buy_dutch_auction_below_current_price() {
+        let mut deps = mock_deps();
+
+        create_dutch_listing(
+            deps.as_mut(),
+            "owner",
+            "2",
+            1000,
+            100,
+            Cw721Expiration::AtTime(Timestamp::from_seconds(1000)),
+            Cw721Expiration::AtTime(Timestamp::from_seconds(2000)),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::Buy {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "2".to_string(),
+        };
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1500);
+
+        // funds are less than the current price
+        let response = execute(
+            deps.as_mut(),
+            env.clone(),
+            mock_info("buyer", &coins(500, "uaura")),
+            msg,
+        );
+        match response {
+            Ok(_) => panic!("Expected error"),
+            Err(ContractError::InsufficientFunds {}) => {}
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+
+        // the coins of another denom would not be refunded
+        create_dutch_listing(
+            deps.as_mut(),
+            "owner",
+            "3",
+            1000,
+            100,
+            Cw721Expiration::AtTime(Timestamp::from_seconds(1000)),
+            Cw721Expiration::AtTime(Timestamp::from_seconds(2000)),
+        )
+        .unwrap();
+        let msg = ExecuteMsg::Buy {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "3".to_string(),
+        };
+        let response = execute(
+            deps.as_mut(),
+            env,
+            mock_info(
+                "buyer",
+                &[
+                    Coin::new(1000, "uaura"),
+                    Coin::new(100, NATIVE_DENOM_2.to_string()),
+                ],
+            ),
+            msg,
+        );
+        match response {
+            Ok(_) => panic!("Expected error"),
+            Err(ContractError::InsufficientFunds {}) => {}
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn buy_dutch_auction_at_current_price() {
+        let mut deps = mock_deps();
+
+        create_dutch_listing(
+            deps.as_mut(),
+            "owner",
+            "2",
+            1000,
+            100,
+            Cw721Expiration::AtTime(Timestamp::from_seconds(1000)),
+            Cw721Expiration::AtTime(Timestamp::from_seconds(2000)),
+        )
+        .unwrap();
+
+        let msg = ExecuteMsg::Buy {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "2".to_string(),
+        };
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(1500);
+
+        // the exceeded funds are refunded to the buyer
+        let response = execute(
+            deps.as_mut(),
+            env,
+            mock_info("buyer", &coins(600, "uaura")),
+            msg,
+        )
+        .unwrap();
+        assert_eq!(3, response.messages.len());
+        assert_eq!(
+            response.messages[1],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".to_string(),
+                amount: vec![cosmwasm_std::coin(550, "uaura")],
+            })),
+            "should transfer the current price to owner"
+        );
+        assert_eq!(
+            response.messages[2],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer".to_string(),
+                amount: vec![cosmwasm_std::coin(50, "uaura")],
+            })),
+            "should refund the rest to buyer"
+        );
+    }
+
+    #[test]
+    fn cannot_buy_dutch_auction_after_end_time() {
+        let mut deps = mock_deps();
+
+        create_dutch_listing(
+            deps.as_mut(),
+            "owner",
+            "2",
+            1000,
+            100,
+            Cw721Expiration::AtTime(Timestamp::from_seconds(1000)),
+            Cw721Expiration::AtTime(Timestamp::from_seconds(2000)),
+        )
+        .unwrap();
+
+        let mut env = mock_env();
+        env.block.time = Timestamp::from_seconds(2001);
+
+        // the listing is expired after the end time
+        let listing = contract()
+            .query_listing(
+                deps.as_ref(),
+                Addr::unchecked(MOCK_CW2981_ADDR),
+                "2".to_string(),
+            )
+            .unwrap();
+        assert!(listing.listing.is_expired(&env.block));
+
+        let msg = ExecuteMsg::Buy {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "2".to_string(),
+        };
+        let response = execute(
+            deps.as_mut(),
+            env,
+            mock_info("buyer", &coins(1000, "uaura")),
+            msg,
+        );
+        match response {
+            Ok(_) => panic!("Expected error"),
+            Err(ContractError::CustomError { .. }) => {}
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+    }
+}
+
+mod batch {
+    use super::*;
+    use crate::msg::{BuyNftItem, ListNftItem};
+
+    fn list_nfts(deps: DepsMut, token_ids: &[&str]) -> Result<Response, ContractError> {
+        let items = token_ids
+            .iter()
+            .map(|token_id| ListNftItem {
+                contract_address: MOCK_CW2981_ADDR.to_string(),
+                token_id: token_id.to_string(),
+                auction_config: AuctionConfig::FixedPrice {
+                    price: Coin {
+                        denom: "uaura".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                    start_time: None,
+                    end_time: None,
+                },
+                escrow: false,
+                reserved_buyers: vec![],
+            })
+            .collect();
+        execute(
+            deps,
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::ListNfts { items },
+        )
+    }
+
+    fn buy_nfts(deps: DepsMut, token_ids: &[&str], funds: u128) -> Result<Response, ContractError> {
+        let items = token_ids
+            .iter()
+            .map(|token_id| BuyNftItem {
+                contract_address: MOCK_CW2981_ADDR.to_string(),
+                token_id: token_id.to_string(),
+            })
+            .collect();
+        execute(
+            deps,
+            mock_env(),
+            mock_info("buyer", &coins(funds, "uaura")),
+            ExecuteMsg::BuyNfts { items },
+        )
+    }
+
+    #[test]
+    fn owner_can_list_many_nfts() {
+        let mut deps = mock_deps();
+
+        let response = list_nfts(deps.as_mut(), &["1", "2", "3"]).unwrap();
+        assert_eq!(3, response.events.len());
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListingsByContractAddress {
+                contract_address: MOCK_CW2981_ADDR.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let listings: ListingsResponse = from_binary(&res).unwrap();
+        assert_eq!(3, listings.listings.len());
+    }
+
+    #[test]
+    fn cannot_buy_many_nfts_without_enough_funds() {
+        let mut deps = mock_deps();
+
+        list_nfts(deps.as_mut(), &["1", "2"]).unwrap();
+
+        // the funds must cover the sum of the prices
+        match buy_nfts(deps.as_mut(), &["1", "2"], 150) {
+            Ok(_) => panic!("Expected error"),
+            Err(ContractError::InsufficientFunds {}) => {}
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn cannot_buy_same_nft_twice() {
+        let mut deps = mock_deps();
+
+        list_nfts(deps.as_mut(), &["1"]).unwrap();
+
+        let response = buy_nfts(deps.as_mut(), &["1", "1"], 200);
+        assert!(response.is_err());
+    }
+
+    #[test]
+    fn buy_many_nfts_and_refund_exceeded_funds() {
+        let mut deps = mock_deps();
+
+        list_nfts(deps.as_mut(), &["1", "2", "3"]).unwrap();
+
+        let response = buy_nfts(deps.as_mut(), &["1", "2"], 250).unwrap();
+
+        // nft 1 pays royalty to the creator, nft 2 has 0 royalty, the last message is the refund
+        assert_eq!(6, response.messages.len());
+        assert_eq!(2, response.events.len());
+        assert_eq!(
+            response.messages[5],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "buyer".to_string(),
+                amount: vec![cosmwasm_std::coin(50, "uaura")],
+            })),
+            "should refund the exceeded funds to buyer"
+        );
+
+        // only the listing which is not bought remains
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListingsByContractAddress {
+                contract_address: MOCK_CW2981_ADDR.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let listings: ListingsResponse = from_binary(&res).unwrap();
+        assert_eq!(1, listings.listings.len());
+        assert_eq!("3", listings.listings[0].token_id);
+    }
+}
+
+mod protocol_fee {
+    use super::*;
+
+    fn update_config(
+        deps: DepsMut,
+        sender: &str,
+        protocol_fee_bps: Option<u16>,
+        fee_collector: Option<&str>,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::UpdateConfig {
+            protocol_fee_bps,
+            fee_collector: fee_collector.map(|addr| addr.to_string()),
+            registry_mode: None,
+        };
+        execute(deps, mock_env(), mock_info(sender, &[]), msg)
+    }
+
+    #[test]
+    fn only_owner_can_update_config() {
+        let mut deps = mock_deps();
+
+        let response = update_config(deps.as_mut(), "buyer", Some(250), Some("treasury"));
+        match response {
+            Ok(_) => panic!("Expected error"),
+            Err(ContractError::Unauthorized {}) => {}
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+
+        update_config(deps.as_mut(), "owner", Some(250), Some("treasury")).unwrap();
+
+        let res = query(deps.as_ref(), mock_env(), QueryMsg::Config {}).unwrap();
+        let config: Config = from_binary(&res).unwrap();
+        assert_eq!(config.protocol_fee_bps, 250);
+        assert_eq!(config.fee_collector, Some(Addr::unchecked("treasury")));
+    }
+
+    #[test]
+    fn cannot_update_invalid_config() {
+        let mut deps = mock_deps();
+
+        // the fee cannot be greater than 10% of the price
+        let response = update_config(deps.as_mut(), "owner", Some(1001), Some("treasury"));
+        assert!(response.is_err());
+
+        // the fee cannot be charged without a collector
+        let response = update_config(deps.as_mut(), "owner", Some(250), None);
+        assert!(response.is_err());
+
+        // the maximum fee is accepted
+        update_config(deps.as_mut(), "owner", Some(1000), Some("treasury")).unwrap();
+    }
+
+    #[test]
+    fn buy_listing_with_protocol_fee() {
+        let mut deps = mock_deps();
+
+        update_config(deps.as_mut(), "owner", Some(250), Some("treasury")).unwrap();
+
+        let msg = ExecuteMsg::ListNft {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "1".to_string(),
+            auction_config: AuctionConfig::FixedPrice {
+                price: Coin {
+                    denom: "uaura".to_string(),
+                    amount: Uint128::from(1000u128),
+                },
+                start_time: None,
+                end_time: None,
+            },
+            escrow: false,
+            reserved_buyers: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        let msg = ExecuteMsg::Buy {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "1".to_string(),
+        };
+        let mock_info_buyer = mock_info("buyer", &coins(1000, "uaura"));
+        let response = execute(deps.as_mut(), mock_env(), mock_info_buyer, msg).unwrap();
+
+        // the fee (2.5%) is paid to the collector, the royalty to the creator, the rest to the seller
+        assert_eq!(4, response.messages.len());
+        assert_eq!(
+            response.messages[1],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "treasury".to_string(),
+                amount: vec![cosmwasm_std::coin(25, "uaura")],
+            })),
+            "should transfer protocol fee to fee collector"
+        );
+        assert_eq!(
+            response.messages[2],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "creator".to_string(),
+                amount: vec![cosmwasm_std::coin(10, "uaura")],
+            })),
+            "should transfer royalty to creator"
+        );
+        assert_eq!(
+            response.messages[3],
+            SubMsg::new(CosmosMsg::Bank(BankMsg::Send {
+                to_address: "owner".to_string(),
+                amount: vec![cosmwasm_std::coin(965, "uaura")],
+            })),
+            "should transfer the rest to owner"
+        );
+        assert!(response
+            .attributes
+            .iter()
+            .any(|attr| attr.key == "protocol_fee" && attr.value == "25"));
+    }
+}
+
+mod sorted_listings {
+    use super::*;
+    use crate::msg::{ListingsCursor, ListingsFilter, SortOrder, SortedListingsResponse};
+
+    fn list_nft(deps: DepsMut, token_id: &str, price: u128, start_time: Option<Cw721Expiration>) {
+        let msg = ExecuteMsg::ListNft {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: token_id.to_string(),
+            auction_config: AuctionConfig::FixedPrice {
+                price: Coin {
+                    denom: "uaura".to_string(),
+                    amount: Uint128::from(price),
+                },
+                start_time,
+                end_time: None,
+            },
+            escrow: false,
+            reserved_buyers: vec![],
+        };
+        execute(deps, mock_env(), mock_info("owner", &[]), msg).unwrap();
+    }
+
+    // token 1 is listed for 300, token 2 for 100 and token 3 for 200 from a future start time
+    fn list_nfts(mut deps: DepsMut) {
+        list_nft(deps.branch(), "1", 300, None);
+        list_nft(deps.branch(), "2", 100, None);
+        list_nft(
+            deps,
+            "3",
+            200,
+            Some(Cw721Expiration::AtTime(
+                mock_env().block.time.plus_seconds(100),
+            )),
+        );
+    }
+
+    fn query_listings(
+        deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+        filter: ListingsFilter,
+        start_after: Option<ListingsCursor>,
+    ) -> Vec<String> {
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Listings {
+                contract_address: MOCK_CW2981_ADDR.to_string(),
+                filter,
+                start_after,
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+        let listings: SortedListingsResponse = from_binary(&res).unwrap();
+        listings
+            .listings
+            .into_iter()
+            .map(|listing| listing.token_id)
+            .collect()
+    }
+
+    #[test]
+    fn listings_are_sorted_by_price() {
+        let mut deps = mock_deps();
+        list_nfts(deps.as_mut());
+
+        let token_ids = query_listings(&deps, ListingsFilter::default(), None);
+        assert_eq!(token_ids, vec!["2", "3"]);
+
+        // the next page starts after the last listing
+        let cursor = ListingsCursor {
+            denom: "uaura".to_string(),
+            price: 200,
+            token_id: "3".to_string(),
+        };
+        let token_ids = query_listings(&deps, ListingsFilter::default(), Some(cursor));
+        assert_eq!(token_ids, vec!["1"]);
+
+        let filter = ListingsFilter {
+            order: SortOrder::Descending,
+            ..ListingsFilter::default()
+        };
+        let token_ids = query_listings(&deps, filter.clone(), None);
+        assert_eq!(token_ids, vec!["1", "3"]);
+
+        let cursor = ListingsCursor {
+            denom: "uaura".to_string(),
+            price: 200,
+            token_id: "3".to_string(),
+        };
+        let token_ids = query_listings(&deps, filter, Some(cursor));
+        assert_eq!(token_ids, vec!["2"]);
+    }
+
+    #[test]
+    fn listings_are_filtered() {
+        let mut deps = mock_deps();
+        list_nfts(deps.as_mut());
+
+        let filter = ListingsFilter {
+            min_price: Some(150),
+            max_price: Some(300),
+            ..ListingsFilter::default()
+        };
+        assert_eq!(query_listings(&deps, filter, None), vec!["3", "1"]);
+
+        // the listing of token 3 is not started yet
+        let filter = ListingsFilter {
+            active: true,
+            ..ListingsFilter::default()
+        };
+        assert_eq!(query_listings(&deps, filter, None), vec!["2", "1"]);
+
+        let filter = ListingsFilter {
+            seller: Some("other".to_string()),
+            ..ListingsFilter::default()
+        };
+        assert!(query_listings(&deps, filter, None).is_empty());
+
+        let filter = ListingsFilter {
+            denom: Some(NATIVE_DENOM_2.to_string()),
+            ..ListingsFilter::default()
+        };
+        assert!(query_listings(&deps, filter, None).is_empty());
+    }
+
+    #[test]
+    fn listings_of_a_denom_are_bounded_by_prices() {
+        let mut deps = mock_deps();
+        list_nfts(deps.as_mut());
+
+        let filter = ListingsFilter {
+            denom: Some(NATIVE_DENOM.to_string()),
+            min_price: Some(150),
+            max_price: Some(u128::MAX),
+            ..ListingsFilter::default()
+        };
+        assert_eq!(query_listings(&deps, filter.clone(), None), vec!["3", "1"]);
+
+        // a cursor before the min price starts the page at the min price
+        let cursor = ListingsCursor {
+            denom: NATIVE_DENOM.to_string(),
+            price: 100,
+            token_id: "2".to_string(),
+        };
+        assert_eq!(query_listings(&deps, filter, Some(cursor)), vec!["3", "1"]);
+
+        let filter = ListingsFilter {
+            denom: Some(NATIVE_DENOM.to_string()),
+            max_price: Some(200),
+            order: SortOrder::Descending,
+            ..ListingsFilter::default()
+        };
+        assert_eq!(query_listings(&deps, filter.clone(), None), vec!["3", "2"]);
+
+        // a cursor after the max price starts the page at the max price
+        let cursor = ListingsCursor {
+            denom: NATIVE_DENOM.to_string(),
+            price: 300,
+            token_id: "1".to_string(),
+        };
+        assert_eq!(
+            query_listings(&deps, filter.clone(), Some(cursor)),
+            vec!["3", "2"]
+        );
+
+        let cursor = ListingsCursor {
+            denom: NATIVE_DENOM.to_string(),
+            price: 200,
+            token_id: "3".to_string(),
+        };
+        assert_eq!(query_listings(&deps, filter, Some(cursor)), vec!["2"]);
+    }
+
+    #[test]
+    fn listings_are_paged_by_scanned_listings() {
+        let mut deps = mock_deps();
+        for i in 0..MAX_SCANNED_LISTINGS + 1 {
+            list_nft(deps.as_mut(), &format!("{:0>8}", i), 100, None);
+        }
+
+        // no listing of the page matches the seller, the next page starts after the scanned ones
+        let query_page = |start_after: Option<ListingsCursor>| {
+            let res = query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::Listings {
+                    contract_address: MOCK_CW2981_ADDR.to_string(),
+                    filter: ListingsFilter {
+                        seller: Some("other".to_string()),
+                        ..ListingsFilter::default()
+                    },
+                    start_after,
+                    limit: None,
+                },
+            )
+            .unwrap();
+            from_binary::<SortedListingsResponse>(&res).unwrap()
+        };
+        let res = query_page(None);
+        assert!(res.listings.is_empty());
+        let next = res.next.unwrap();
+        assert_eq!(next.token_id, format!("{:0>8}", MAX_SCANNED_LISTINGS - 1));
+
+        let res = query_page(Some(next));
+        assert!(res.listings.is_empty());
+        assert_eq!(res.next, None);
+    }
+}
+
+mod listings_by_seller {
+    use super::*;
+
+    fn query_listings_by_seller(
+        deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+        seller: &str,
+        start_after_nft: Option<NFT>,
+    ) -> Vec<String> {
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListingsBySeller {
+                seller: seller.to_string(),
+                start_after_nft,
+                limit: Some(2),
+            },
+        )
+        .unwrap();
+        let listings: ListingsResponse = from_binary(&res).unwrap();
+        listings
+            .listings
+            .into_iter()
+            .map(|listing| listing.token_id)
+            .collect()
+    }
+
+    #[test]
+    fn can_query_listings_by_seller() {
+        let mut deps = mock_deps();
+
+        for token_id in ["1", "2", "3"] {
+            let msg = ExecuteMsg::ListNft {
+                contract_address: MOCK_CW2981_ADDR.to_string(),
+                token_id: token_id.to_string(),
+                auction_config: AuctionConfig::FixedPrice {
+                    price: Coin {
+                        denom: "uaura".to_string(),
+                        amount: Uint128::from(100u128),
+                    },
+                    start_time: None,
+                    end_time: None,
+                },
+                escrow: false,
+                reserved_buyers: vec![],
+            };
+            execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        }
+
+        assert_eq!(
+            query_listings_by_seller(&deps, "owner", None),
+            vec!["3", "2"]
+        );
+
+        // the next page starts after the last listing
+        let start_after_nft = NFT {
+            contract_address: Addr::unchecked(MOCK_CW2981_ADDR),
+            token_id: Some("2".to_string()),
+        };
+        assert_eq!(
+            query_listings_by_seller(&deps, "owner", Some(start_after_nft)),
+            vec!["1"]
+        );
+
+        assert!(query_listings_by_seller(&deps, "other", None).is_empty());
+    }
+}
+
+mod collection_registry {
+    use super::*;
+    use crate::msg::RegisteredCollectionsResponse;
+    use crate::state::{CollectionStatus, RegistryMode};
+
+    fn register_collection(
+        deps: DepsMut,
+        sender: &str,
+        status: CollectionStatus,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::RegisterCollection {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            name: "Mock collection".to_string(),
+            description: None,
+            status,
+        };
+        execute(deps, mock_env(), mock_info(sender, &[]), msg)
+    }
+
+    fn list_nft(deps: DepsMut) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::ListNft {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "1".to_string(),
+            auction_config: AuctionConfig::FixedPrice {
+                price: Coin {
+                    denom: "uaura".to_string(),
+                    amount: Uint128::from(100u128),
+                },
+                start_time: None,
+                end_time: None,
+            },
+            escrow: false,
+            reserved_buyers: vec![],
+        };
+        execute(deps, mock_env(), mock_info("owner", &[]), msg)
+    }
+
+    fn assert_not_allowed(res: Result<Response, ContractError>) {
+        match res {
+            Err(ContractError::CustomError { val }) => {
+                assert_eq!(val, "Collection is not allowed")
+            }
+            _ => panic!("Expected error"),
+        }
+    }
+
+    #[test]
+    fn only_owner_can_register_collection() {
+        let mut deps = mock_deps();
+
+        match register_collection(deps.as_mut(), "other", CollectionStatus::Blocked) {
+            Err(ContractError::Unauthorized {}) => {}
+            _ => panic!("Expected error"),
+        }
+        register_collection(deps.as_mut(), "owner", CollectionStatus::Blocked).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RegisteredCollections {
+                status: Some(CollectionStatus::Blocked),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let res: RegisteredCollectionsResponse = from_binary(&res).unwrap();
+        assert_eq!(res.collections.len(), 1);
+        assert_eq!(res.collections[0].name, "Mock collection");
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::RegisteredCollections {
+                status: Some(CollectionStatus::Allowed),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let res: RegisteredCollectionsResponse = from_binary(&res).unwrap();
+        assert!(res.collections.is_empty());
+    }
+
+    #[test]
+    fn cannot_list_blocked_collection() {
+        let mut deps = mock_deps();
+
+        register_collection(deps.as_mut(), "owner", CollectionStatus::Blocked).unwrap();
+        assert_not_allowed(list_nft(deps.as_mut()));
+
+        // the collection can be listed after it is unregistered
+        let msg = ExecuteMsg::UnregisterCollection {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        assert!(list_nft(deps.as_mut()).is_ok());
+    }
+
+    #[test]
+    fn cannot_buy_listing_of_collection_blocked_later() {
+        let mut deps = mock_deps();
+
+        list_nft(deps.as_mut()).unwrap();
+        register_collection(deps.as_mut(), "owner", CollectionStatus::Blocked).unwrap();
+
+        let msg = ExecuteMsg::Buy {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "1".to_string(),
+        };
+        assert_not_allowed(execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, "uaura")),
+            msg,
+        ));
+    }
+
+    #[test]
+    fn only_allowed_collection_can_be_listed_in_allow_mode() {
+        let mut deps = mock_deps();
+
+        let msg = ExecuteMsg::UpdateConfig {
+            protocol_fee_bps: None,
+            fee_collector: None,
+            registry_mode: Some(RegistryMode::Allow),
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+        assert_not_allowed(list_nft(deps.as_mut()));
+
+        register_collection(deps.as_mut(), "owner", CollectionStatus::Allowed).unwrap();
+        assert!(list_nft(deps.as_mut()).is_ok());
+    }
+}
+
+mod counter {
+    use super::*;
+
+    #[test]
+    fn increment_counter_cancels_all_listings() {
+        let mut deps = mock_deps();
+
+        let msg = ExecuteMsg::ListNft {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "1".to_string(),
+            auction_config: AuctionConfig::FixedPrice {
+                price: Coin {
+                    denom: "uaura".to_string(),
+                    amount: Uint128::from(100u128),
+                },
+                start_time: None,
+                end_time: None,
+            },
+            escrow: false,
+            reserved_buyers: vec![],
+        };
+        execute(deps.as_mut(), mock_env(), mock_info("owner", &[]), msg).unwrap();
+
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("owner", &[]),
+            ExecuteMsg::IncrementCounter {},
+        )
+        .unwrap();
+
+        // the cancelled listing is not returned
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::ListingsByContractAddress {
+                contract_address: MOCK_CW2981_ADDR.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap();
+        let listings: ListingsResponse = from_binary(&res).unwrap();
+        assert!(listings.listings.is_empty());
+
+        // the cancelled listing cannot be bought
+        let msg = ExecuteMsg::Buy {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "1".to_string(),
+        };
+        match execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info("buyer", &coins(100, "uaura")),
+            msg,
+        ) {
+            Ok(_) => panic!("Expected error"),
+            Err(ContractError::ListingNotActive {}) => {}
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+    }
+}
+
+mod private_listing {
+    use super::*;
+
+    fn create_private_listing(
+        deps: DepsMut,
+        reserved_buyers: Vec<&str>,
+    ) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::ListNft {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "1".to_string(),
+            auction_config: AuctionConfig::FixedPrice {
+                price: Coin {
+                    denom: "uaura".to_string(),
+                    amount: Uint128::from(100u128),
+                },
+                start_time: None,
+                end_time: None,
+            },
+            escrow: false,
+            reserved_buyers: reserved_buyers
+                .into_iter()
+                .map(|buyer| buyer.to_string())
+                .collect(),
+        };
+        execute(deps, mock_env(), mock_info("owner", &[]), msg)
+    }
+
+    fn buy_listing(deps: DepsMut, buyer: &str) -> Result<Response, ContractError> {
+        let msg = ExecuteMsg::Buy {
+            contract_address: MOCK_CW2981_ADDR.to_string(),
+            token_id: "1".to_string(),
+        };
+        execute(
+            deps,
+            mock_env(),
+            mock_info(buyer, &coins(100, "uaura")),
+            msg,
+        )
+    }
+
+    #[test]
+    fn owner_cannot_reserve_listing_for_self() {
+        let mut deps = mock_deps();
+
+        let response = create_private_listing(deps.as_mut(), vec!["buyer", "owner"]);
+        assert!(response.is_err());
+    }
+
+    #[test]
+    fn other_user_cannot_buy_private_listing() {
+        let mut deps = mock_deps();
+
+        create_private_listing(deps.as_mut(), vec!["buyer", "another_buyer"]).unwrap();
+
+        match buy_listing(deps.as_mut(), "another_user") {
+            Ok(_) => panic!("Expected error"),
+            Err(ContractError::CustomError { val }) => {
+                assert_eq!(val, "Listing is reserved for other buyers")
+            }
+            Err(e) => panic!("Unexpected error: {}", e),
+        }
+    }
+
+    #[test]
+    fn reserved_buyer_can_buy_private_listing() {
+        let mut deps = mock_deps();
+
+        create_private_listing(deps.as_mut(), vec!["buyer", "another_buyer"]).unwrap();
+
+        let res = query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::Listing {
+                contract_address: MOCK_CW2981_ADDR.to_string(),
+                token_id: "1".to_string(),
+            },
+        )
+        .unwrap();
+        let res: ListingResponse = from_binary(&res).unwrap();
+        assert_eq!(
+            res.listing.reserved_buyers,
+            vec![Addr::unchecked("buyer"), Addr::unchecked("another_buyer")]
+        );
+
+        let response = buy_listing(deps.as_mut(), "another_buyer").unwrap();
+        assert_eq!(
+            response.messages[0],
+            SubMsg::new(WasmMsg::Execute {
+                contract_addr: MOCK_CW2981_ADDR.to_string(),
+                msg: to_binary(&Cw2981ExecuteMsg::TransferNft {
+                    recipient: "another_buyer".to_string(),
+                    token_id: "1".to_string(),
+                })
+                .unwrap(),
+                funds: vec![],
+            }),
+            "should transfer nft to the reserved buyer"
+        );
+    }
+}
+
 // fn create_offer(
 //     deps: DepsMut,
 //     sender: &str,
@@ -1395,6 +2587,8 @@ mod accept_offer {
                 token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
             },
             funds_amount: MOCK_OFFER_CW20_PRICE,
+            end_funds_amount: None,
+            start_time: None,
             end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
         };
 
@@ -1571,6 +2765,8 @@ mod cancel_offer {
                 token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
             },
             funds_amount: MOCK_OFFER_CW20_PRICE,
+            end_funds_amount: None,
+            start_time: None,
             end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
         };
 
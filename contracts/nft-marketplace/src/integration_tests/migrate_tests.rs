@@ -0,0 +1,280 @@
+use crate::contract::{execute, instantiate, migrate, query};
+use crate::msg::{
+    CollectionOffersResponse, CollectionStatsResponse, ExecuteMsg, InstantiateMsg,
+    ListingsResponse, MigrateMsg, QueryMsg, RebuildIndexesCursor, RebuildIndexesResponse,
+};
+use crate::order_state::{
+    consideration_item, offer_item, order_key, Asset, ItemType, OrderComponents, OrderKey,
+    OrderType, PaymentAsset, COLLECTION_OFFER_TOKEN_ID, CW20, NFT,
+};
+use crate::state::{listing_key, AuctionConfig, Listing, ListingKey};
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env, mock_info, MockApi, MockQuerier};
+use cosmwasm_std::{coin, from_binary, Addr, MemoryStorage, OwnedDeps};
+use cw721::Expiration as Cw721Expiration;
+use cw_storage_plus::Map;
+
+const OWNER: &str = "owner";
+const OFFERER: &str = "offerer";
+const NFT_CONTRACT: &str = "nft_contract";
+const VAURA: &str = "vaura";
+const SELLER: &str = "seller";
+const NATIVE_DENOM: &str = "uaura";
+
+fn instantiate_marketplace() -> OwnedDeps<MemoryStorage, MockApi, MockQuerier> {
+    let mut deps = mock_dependencies();
+    instantiate(
+        deps.as_mut(),
+        mock_env(),
+        mock_info(OWNER, &[]),
+        InstantiateMsg {
+            owner: Addr::unchecked(OWNER),
+        },
+    )
+    .unwrap();
+    deps
+}
+
+fn collection_offer(amount: u128) -> OrderComponents {
+    let offerer = Addr::unchecked(OFFERER);
+    let contract_address = Addr::unchecked(NFT_CONTRACT);
+    OrderComponents {
+        order_type: OrderType::OFFER,
+        order_id: order_key(&offerer, &contract_address, COLLECTION_OFFER_TOKEN_ID),
+        offerer: offerer.clone(),
+        offer: vec![offer_item(
+            &ItemType::CW20,
+            &Asset::Cw20(CW20 {
+                contract_address: Addr::unchecked(VAURA),
+                amount,
+            }),
+            &amount,
+            &amount,
+        )],
+        consideration: vec![consideration_item(
+            &ItemType::CW721,
+            &Asset::Nft(NFT {
+                contract_address,
+                token_id: None,
+            }),
+            &1u128,
+            &1u128,
+            &offerer,
+        )],
+        start_time: None,
+        end_time: Some(Cw721Expiration::AtTime(
+            mock_env().block.time.plus_seconds(1000),
+        )),
+        counter: 0,
+    }
+}
+
+fn fixed_price_listing(token_id: &str, price: u128) -> Listing {
+    Listing {
+        contract_address: Addr::unchecked(NFT_CONTRACT),
+        token_id: token_id.to_string(),
+        auction_config: AuctionConfig::FixedPrice {
+            price: coin(price, NATIVE_DENOM),
+            start_time: None,
+            end_time: None,
+        },
+        seller: Addr::unchecked(SELLER),
+        buyer: None,
+        escrow: false,
+        reserved_buyers: vec![],
+        counter: 0,
+    }
+}
+
+// the listings are stored without the entries of the indexes created by the upgrade
+fn save_listings_before_upgrade(
+    deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+    listings: &[Listing],
+) {
+    for listing in listings {
+        Map::<ListingKey, Listing>::new("listings")
+            .save(
+                &mut deps.storage,
+                listing_key(&listing.contract_address, &listing.token_id),
+                listing,
+            )
+            .unwrap();
+    }
+}
+
+// the indexes are rebuilt by pages of one listing or offer after the migration
+fn migrate_and_rebuild_indexes(deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>) {
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+
+    let mut start_after = None;
+    loop {
+        let res = execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(OWNER, &[]),
+            ExecuteMsg::RebuildIndexes {
+                start_after,
+                limit: Some(1),
+            },
+        )
+        .unwrap();
+        let res: RebuildIndexesResponse = from_binary(&res.data.unwrap()).unwrap();
+        match res.next {
+            Some(next) => start_after = Some(next),
+            None => break,
+        }
+    }
+}
+
+fn query_collection_offers(
+    deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+) -> CollectionOffersResponse {
+    from_binary(
+        &query(
+            deps.as_ref(),
+            mock_env(),
+            QueryMsg::CollectionOffers {
+                contract_address: NFT_CONTRACT.to_string(),
+                start_after: None,
+                limit: None,
+            },
+        )
+        .unwrap(),
+    )
+    .unwrap()
+}
+
+#[test]
+fn rebuild_indexes_offers_stored_before_the_upgrade() {
+    let mut deps = instantiate_marketplace();
+
+    // the offer is stored without the entries of the indexes created by the upgrade
+    let offer = collection_offer(100);
+    Map::<OrderKey, OrderComponents>::new("orders")
+        .save(&mut deps.storage, offer.order_id.clone(), &offer)
+        .unwrap();
+    assert!(query_collection_offers(&deps).offers.is_empty());
+
+    migrate_and_rebuild_indexes(&mut deps);
+    assert_eq!(query_collection_offers(&deps).offers, vec![offer]);
+}
+
+#[test]
+fn rebuild_indexes_listings_stored_before_the_upgrade() {
+    let mut deps = instantiate_marketplace();
+    save_listings_before_upgrade(
+        &mut deps,
+        &[fixed_price_listing("1", 300), fixed_price_listing("2", 200)],
+    );
+
+    let query_stats = |deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>| {
+        from_binary::<CollectionStatsResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::CollectionStats {
+                    contract_address: NFT_CONTRACT.to_string(),
+                    start_after: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+    };
+    assert_eq!(query_stats(&deps).listing_count, 0);
+
+    migrate_and_rebuild_indexes(&mut deps);
+    let stats = query_stats(&deps);
+    assert_eq!(stats.listing_count, 2);
+    assert_eq!(
+        stats.floor_prices,
+        vec![PaymentAsset::Native {
+            denom: NATIVE_DENOM.to_string(),
+            amount: 200
+        }]
+    );
+}
+
+#[test]
+fn rebuild_indexes_listings_by_seller() {
+    let mut deps = instantiate_marketplace();
+    let listings = [fixed_price_listing("1", 300), fixed_price_listing("2", 200)];
+    save_listings_before_upgrade(&mut deps, &listings);
+
+    let query_seller_listings = |deps: &OwnedDeps<MemoryStorage, MockApi, MockQuerier>| {
+        from_binary::<ListingsResponse>(
+            &query(
+                deps.as_ref(),
+                mock_env(),
+                QueryMsg::ListingsBySeller {
+                    seller: SELLER.to_string(),
+                    start_after_nft: None,
+                    limit: None,
+                },
+            )
+            .unwrap(),
+        )
+        .unwrap()
+        .listings
+    };
+    assert!(query_seller_listings(&deps).is_empty());
+
+    // the listings of a seller are returned in descending order
+    migrate_and_rebuild_indexes(&mut deps);
+    assert_eq!(
+        query_seller_listings(&deps),
+        vec![listings[1].clone(), listings[0].clone()]
+    );
+}
+
+#[test]
+fn indexes_are_rebuilt_by_pages_by_the_owner() {
+    let mut deps = instantiate_marketplace();
+    save_listings_before_upgrade(
+        &mut deps,
+        &[fixed_price_listing("1", 300), fixed_price_listing("2", 200)],
+    );
+    let offer = collection_offer(100);
+    Map::<OrderKey, OrderComponents>::new("orders")
+        .save(&mut deps.storage, offer.order_id.clone(), &offer)
+        .unwrap();
+
+    // the migration does not rebuild the indexes
+    migrate(deps.as_mut(), mock_env(), MigrateMsg {}).unwrap();
+    assert!(query_collection_offers(&deps).offers.is_empty());
+
+    let rebuild = |deps: &mut OwnedDeps<MemoryStorage, MockApi, MockQuerier>,
+                   sender: &str,
+                   start_after: Option<RebuildIndexesCursor>| {
+        execute(
+            deps.as_mut(),
+            mock_env(),
+            mock_info(sender, &[]),
+            ExecuteMsg::RebuildIndexes {
+                start_after,
+                limit: Some(2),
+            },
+        )
+        .map(|res| {
+            from_binary::<RebuildIndexesResponse>(&res.data.unwrap())
+                .unwrap()
+                .next
+        })
+    };
+    assert!(rebuild(&mut deps, OFFERER, None).is_err());
+
+    // the first page has the two listings, the second one the offer
+    let next = rebuild(&mut deps, OWNER, None).unwrap();
+    assert_eq!(
+        next,
+        Some(RebuildIndexesCursor::Listing {
+            contract_address: NFT_CONTRACT.to_string(),
+            token_id: "2".to_string(),
+        })
+    );
+    assert!(query_collection_offers(&deps).offers.is_empty());
+
+    let next = rebuild(&mut deps, OWNER, next).unwrap();
+    assert_eq!(next, None);
+    assert_eq!(query_collection_offers(&deps).offers, vec![offer]);
+}
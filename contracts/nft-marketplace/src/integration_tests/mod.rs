@@ -1,4 +1,13 @@
 #![cfg(test)]
+pub mod auction_contract_tests;
+pub mod bundle_tests;
+pub mod cw20_listing_tests;
+pub mod escrow_tests;
 #[cfg(test)]
 pub mod listing_tests;
+pub mod migrate_tests;
 pub mod offer_tests;
+pub mod prune_tests;
+pub mod sale_history_tests;
+pub mod signed_order_tests;
+pub mod sweep_tests;
@@ -1,5 +1,8 @@
-use crate::msg::{ExecuteMsg, OffersResponse, QueryMsg};
+use crate::msg::{
+    CollectionOffersCursor, CollectionOffersResponse, ExecuteMsg, OffersResponse, QueryMsg,
+};
 use crate::order_state::NFT;
+use crate::ContractError;
 
 use crate::test_setup::env::{instantiate_contracts, NATIVE_DENOM, OWNER, USER_1, USER_2};
 
@@ -17,6 +20,8 @@ const MOCK_OFFER_NFT_TOKEN_ID_2: &str = "token2";
 
 const MOCK_OFFER_CW20_PRICE: u128 = 10000000;
 
+const FEE_COLLECTOR: &str = "aura1000000000000000000000000000000000000fee";
+
 fn mint_nft(app: &mut App, token_id: &str, owner: &str, cw2981_address: String) {
     let mint_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::Mint(MintMsg {
         token_id: token_id.to_string(),
@@ -52,6 +57,8 @@ fn create_offer(
             token_id: Some(token_id.to_string()),
         },
         funds_amount: MOCK_OFFER_CW20_PRICE,
+        end_funds_amount: None,
+        start_time: None,
         end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
     };
 
@@ -67,7 +74,8 @@ fn create_offer(
 mod accept_offer {
     use super::*;
 
-    use crate::state::{AuctionConfig, Listing};
+    use crate::msg::ListingResponse;
+    use crate::state::AuctionConfig;
     use cosmwasm_std::StdResult;
     use cw20::{BalanceResponse, Cw20QueryMsg};
 
@@ -376,6 +384,167 @@ mod accept_offer {
         );
     }
 
+    #[test]
+    fn protocol_fee_with_offer() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[2].contract_addr.clone();
+
+        // OWNER sets the protocol fee to 2.5%
+        let update_config_msg = ExecuteMsg::UpdateConfig {
+            protocol_fee_bps: Some(250),
+            fee_collector: Some(FEE_COLLECTOR.to_string()),
+            registry_mode: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &update_config_msg,
+            &[],
+        )
+        .unwrap();
+
+        // prepare mint cw2981 message to OWNER
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            OWNER,
+            cw2981_address.clone(),
+        );
+
+        // transfer NFT to USER_1
+        let transfer_msg = cw721::Cw721ExecuteMsg::TransferNft {
+            recipient: USER_1.to_string(),
+            token_id: MOCK_OFFER_NFT_TOKEN_ID_1.to_string(),
+        };
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&cw2981_address),
+            &transfer_msg,
+            &[],
+        )
+        .unwrap();
+
+        // execute mint function to convert native token to twilight token
+        let _response = app
+            .execute_contract(
+                Addr::unchecked(USER_2.to_string()),
+                Addr::unchecked(&cw20_address),
+                &cw20::Cw20ExecuteMsg::Mint {
+                    recipient: USER_2.to_string(),
+                    amount: Uint128::from(100000000u128),
+                },
+                &[Coin {
+                    amount: Uint128::from(100000000u128),
+                    denom: NATIVE_DENOM.to_string(),
+                }],
+            )
+            .unwrap();
+
+        // USER_2 offers Nft
+        let res = create_offer(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+        );
+        assert!(res.is_ok());
+
+        // USER_1 approve NFT to marketplace
+        let approve_msg = cw721::Cw721ExecuteMsg::ApproveAll {
+            operator: marketplace_address.clone(),
+            expires: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&cw2981_address),
+            &approve_msg,
+            &[],
+        )
+        .unwrap();
+
+        // USER_1 accepts offer of USER_2
+        let accept_offer_msg = ExecuteMsg::AcceptNftOffer {
+            offerer: USER_2.to_string(),
+            nft: NFT {
+                contract_address: Addr::unchecked(&cw2981_address),
+                token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
+            },
+            funds_amount: MOCK_OFFER_CW20_PRICE,
+        };
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &accept_offer_msg,
+            &[],
+        );
+        assert!(res.is_ok());
+
+        // assert NFT is transfered to USER_2
+        let res: cw721::OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw2981_address),
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: MOCK_OFFER_NFT_TOKEN_ID_1.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, USER_2.to_string(), "Invalid owner");
+
+        // assert token is transfered to USER_1
+        let res: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw20_address.clone()),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: USER_1.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res.balance,
+            Uint128::from(MOCK_OFFER_CW20_PRICE).multiply_ratio(775u128, 1000u128),
+            "Token is not transfered to seller"
+        );
+
+        // assert royalty is transfered to OWNER
+        let res: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw20_address.clone()),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: OWNER.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res.balance,
+            Uint128::from(MOCK_OFFER_CW20_PRICE).multiply_ratio(20u128, 100u128),
+            "Royalty is not transfered to owner"
+        );
+
+        // assert protocol fee is transfered to FEE_COLLECTOR
+        let res: cw20::BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw20_address),
+                &cw20::Cw20QueryMsg::Balance {
+                    address: FEE_COLLECTOR.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res.balance,
+            Uint128::from(MOCK_OFFER_CW20_PRICE).multiply_ratio(25u128, 1000u128),
+            "Protocol fee is not transfered to fee collector"
+        );
+    }
+
     #[test]
     fn remove_listing_after_accept_offer() {
         // get integration test app and contracts
@@ -443,6 +612,8 @@ mod accept_offer {
                 start_time: None,
                 end_time: None,
             },
+            escrow: false,
+            reserved_buyers: vec![],
         };
 
         // OWNER list the token
@@ -456,7 +627,7 @@ mod accept_offer {
         assert!(res.is_ok());
 
         // query the listing
-        let res: Listing = app
+        let res: ListingResponse = app
             .wrap()
             .query_wasm_smart(
                 Addr::unchecked(marketplace_address.clone()),
@@ -467,8 +638,8 @@ mod accept_offer {
             )
             .unwrap();
 
-        assert_eq!(res.token_id, MOCK_OFFER_NFT_TOKEN_ID_1.to_string());
-        assert_eq!(res.contract_address, cw2981_address);
+        assert_eq!(res.listing.token_id, MOCK_OFFER_NFT_TOKEN_ID_1.to_string());
+        assert_eq!(res.listing.contract_address, cw2981_address);
 
         // Mint 1000000000 native token to USER_1
         app.sudo(cw_multi_test::SudoMsg::Bank(
@@ -505,6 +676,8 @@ mod accept_offer {
                 token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
             },
             funds_amount: MOCK_OFFER_CW20_PRICE,
+            end_funds_amount: None,
+            start_time: None,
             end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
         };
 
@@ -571,7 +744,7 @@ mod accept_offer {
         assert!(res.is_ok());
 
         // query the listing
-        let res: StdResult<Listing> = app.wrap().query_wasm_smart(
+        let res: StdResult<ListingResponse> = app.wrap().query_wasm_smart(
             Addr::unchecked(marketplace_address),
             &QueryMsg::Listing {
                 contract_address: cw2981_address.clone(),
@@ -611,6 +784,72 @@ mod accept_offer {
         assert_eq!(res.balance, Uint128::from(MOCK_OFFER_CW20_PRICE));
     }
 
+    #[test]
+    fn cannot_accept_offer_after_increment_counter() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        let res = create_offer(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+        );
+        assert!(res.is_ok());
+
+        // USER_1 cancels all its offers
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::IncrementCounter {},
+            &[],
+        )
+        .unwrap();
+
+        // the cancelled offer is not returned
+        let res: OffersResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(marketplace_address.clone()),
+                &QueryMsg::UserOffers {
+                    offerer: USER_1.to_string(),
+                    start_after_nft: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(res.offers.is_empty());
+
+        // the cancelled offer cannot be accepted
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address),
+            &ExecuteMsg::AcceptNftOffer {
+                offerer: USER_1.to_string(),
+                nft: NFT {
+                    contract_address: Addr::unchecked(cw2981_address),
+                    token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
+                },
+                funds_amount: MOCK_OFFER_CW20_PRICE,
+            },
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().root_cause().to_string(),
+            "Custom Error val: \"Offer is cancelled\""
+        );
+    }
+
     #[test]
     fn cannot_accept_offer_of_owned_nft() {
         // get integration test app and contracts
@@ -690,3 +929,1474 @@ mod accept_offer {
         );
     }
 }
+
+mod collection_offer {
+    use super::*;
+
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+
+    fn mint_vaura(app: &mut App, user: &str, cw20_address: String) {
+        // execute mint function to convert native token to twilight token
+        app.execute_contract(
+            Addr::unchecked(user.to_string()),
+            Addr::unchecked(cw20_address),
+            &cw20::Cw20ExecuteMsg::Mint {
+                recipient: user.to_string(),
+                amount: Uint128::from(100000000u128),
+            },
+            &[Coin {
+                amount: Uint128::from(100000000u128),
+                denom: NATIVE_DENOM.to_string(),
+            }],
+        )
+        .unwrap();
+    }
+
+    fn create_collection_offer(
+        app: &mut App,
+        owner: &str,
+        cw2981_address: String,
+        marketplace_address: String,
+        funds_amount: u128,
+    ) -> AnyResult<AppResponse> {
+        // the offer is not bound to a token
+        let offer_nft_msg = ExecuteMsg::OfferNft {
+            nft: NFT {
+                contract_address: Addr::unchecked(cw2981_address),
+                token_id: None,
+            },
+            funds_amount,
+            end_funds_amount: None,
+            start_time: None,
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+
+        (*app).execute_contract(
+            Addr::unchecked(owner.to_string()),
+            Addr::unchecked(marketplace_address),
+            &offer_nft_msg,
+            &[],
+        )
+    }
+
+    fn query_collection_offers(
+        app: &App,
+        cw2981_address: String,
+        marketplace_address: String,
+        start_after: Option<CollectionOffersCursor>,
+        limit: Option<u32>,
+    ) -> CollectionOffersResponse {
+        app.wrap()
+            .query_wasm_smart(
+                Addr::unchecked(marketplace_address),
+                &QueryMsg::CollectionOffers {
+                    contract_address: cw2981_address,
+                    start_after,
+                    limit,
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn cannot_offer_invalid_collection() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[2].contract_addr.clone();
+
+        mint_vaura(&mut app, USER_1, cw20_address.clone());
+
+        // the vaura token is not a collection of nft
+        let res = create_collection_offer(
+            &mut app,
+            USER_1,
+            cw20_address,
+            marketplace_address,
+            MOCK_OFFER_CW20_PRICE,
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn collection_offers_are_sorted_by_price() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[2].contract_addr.clone();
+
+        mint_vaura(&mut app, USER_1, cw20_address.clone());
+        mint_vaura(&mut app, USER_2, cw20_address);
+
+        let res = create_collection_offer(
+            &mut app,
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+            MOCK_OFFER_CW20_PRICE,
+        );
+        assert!(res.is_ok());
+
+        let res = create_collection_offer(
+            &mut app,
+            USER_2,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+            MOCK_OFFER_CW20_PRICE * 3,
+        );
+        assert!(res.is_ok());
+
+        // the best offer is returned first
+        let res = query_collection_offers(
+            &app,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+            None,
+            None,
+        );
+        assert_eq!(res.next, None);
+        let offerers: Vec<Addr> = res.offers.into_iter().map(|o| o.offerer).collect();
+        assert_eq!(
+            offerers,
+            vec![Addr::unchecked(USER_2), Addr::unchecked(USER_1)]
+        );
+
+        let res = query_collection_offers(
+            &app,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+            None,
+            Some(1),
+        );
+        let next = res.next.unwrap();
+        assert_eq!(
+            next,
+            CollectionOffersCursor {
+                amount: MOCK_OFFER_CW20_PRICE * 3,
+                offerer: USER_2.to_string(),
+            }
+        );
+
+        // the page continues from the cursor even if its offer is cancelled
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::CancelOffer {
+                nfts: vec![NFT {
+                    contract_address: Addr::unchecked(cw2981_address.clone()),
+                    token_id: None,
+                }],
+            },
+            &[],
+        )
+        .unwrap();
+
+        let res = query_collection_offers(
+            &app,
+            cw2981_address,
+            marketplace_address,
+            Some(next),
+            Some(1),
+        );
+        let offerers: Vec<Addr> = res.offers.into_iter().map(|o| o.offerer).collect();
+        assert_eq!(offerers, vec![Addr::unchecked(USER_1)]);
+    }
+
+    #[test]
+    fn holder_can_accept_collection_offer() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[2].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            OWNER,
+            cw2981_address.clone(),
+        );
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_2,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        mint_vaura(&mut app, USER_1, cw20_address.clone());
+
+        let res = create_collection_offer(
+            &mut app,
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+            MOCK_OFFER_CW20_PRICE,
+        );
+        assert!(res.is_ok());
+
+        // USER_2 approves marketplace to transfer nft token
+        let approve_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::ApproveAll {
+            operator: marketplace_address.clone(),
+            expires: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(cw2981_address.clone()),
+            &approve_msg,
+            &[],
+        )
+        .unwrap();
+
+        // the collection offer cannot be accepted as an offer of a nft
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::AcceptNftOffer {
+                offerer: USER_1.to_string(),
+                nft: NFT {
+                    contract_address: Addr::unchecked(cw2981_address.clone()),
+                    token_id: None,
+                },
+                funds_amount: MOCK_OFFER_CW20_PRICE,
+            },
+            &[],
+        );
+        assert!(res.is_err());
+
+        // USER_2 cannot sell the nft of OWNER
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::AcceptCollectionOffer {
+                offerer: USER_1.to_string(),
+                nft: NFT {
+                    contract_address: Addr::unchecked(cw2981_address.clone()),
+                    token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
+                },
+                funds_amount: MOCK_OFFER_CW20_PRICE,
+            },
+            &[],
+        );
+        assert!(res.is_err());
+
+        // USER_2 sells its nft to USER_1
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address.clone()),
+            &ExecuteMsg::AcceptCollectionOffer {
+                offerer: USER_1.to_string(),
+                nft: NFT {
+                    contract_address: Addr::unchecked(cw2981_address.clone()),
+                    token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_2.to_string()),
+                },
+                funds_amount: MOCK_OFFER_CW20_PRICE,
+            },
+            &[],
+        );
+        assert!(res.is_ok());
+
+        // assert NFT is transfered to USER_1
+        let res: cw721::OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw2981_address.clone()),
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: MOCK_OFFER_NFT_TOKEN_ID_2.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, USER_1.to_string());
+
+        // assert token is transfered to USER_2 after the royalty
+        let res: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw20_address),
+                &Cw20QueryMsg::Balance {
+                    address: USER_2.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res.balance,
+            Uint128::from(MOCK_OFFER_CW20_PRICE).multiply_ratio(80u128, 100u128)
+        );
+
+        // the collection offer is removed after it is accepted
+        let res = query_collection_offers(&app, cw2981_address, marketplace_address, None, None);
+        assert!(res.offers.is_empty());
+    }
+}
+
+mod trait_offer {
+    use super::*;
+
+    use crate::order_state::{trait_offer_token_id, NftTrait, TraitNFT};
+    use cw2981_royalties::Trait;
+
+    fn mint_nft_with_background(app: &mut App, token_id: &str, owner: &str, cw2981_address: &str) {
+        let mint_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: Metadata {
+                attributes: Some(vec![Trait {
+                    display_type: None,
+                    trait_type: "Background".to_string(),
+                    value: if token_id == MOCK_OFFER_NFT_TOKEN_ID_1 {
+                        "Gold".to_string()
+                    } else {
+                        "Blue".to_string()
+                    },
+                }]),
+                ..Metadata::default()
+            },
+        });
+
+        app.execute_contract(
+            Addr::unchecked(OWNER.to_string()),
+            Addr::unchecked(cw2981_address),
+            &mint_msg,
+            &[],
+        )
+        .unwrap();
+    }
+
+    fn gold_background(cw2981_address: &str) -> TraitNFT {
+        TraitNFT {
+            contract_address: Addr::unchecked(cw2981_address),
+            traits: vec![NftTrait {
+                trait_type: "Background".to_string(),
+                value: "Gold".to_string(),
+            }],
+        }
+    }
+
+    #[test]
+    fn cannot_offer_without_traits() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        let offer_msg = ExecuteMsg::OfferTraitNft {
+            nft: TraitNFT {
+                contract_address: Addr::unchecked(cw2981_address),
+                traits: vec![],
+            },
+            funds_amount: MOCK_OFFER_CW20_PRICE,
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address),
+            &offer_msg,
+            &[],
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn holder_can_accept_trait_offer_with_matching_nft() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[2].contract_addr.clone();
+
+        // USER_2 holds a gold nft and a blue nft
+        mint_nft_with_background(&mut app, MOCK_OFFER_NFT_TOKEN_ID_1, USER_2, &cw2981_address);
+        mint_nft_with_background(&mut app, MOCK_OFFER_NFT_TOKEN_ID_2, USER_2, &cw2981_address);
+
+        // execute mint function to convert native token to twilight token
+        app.execute_contract(
+            Addr::unchecked(USER_1.to_string()),
+            Addr::unchecked(&cw20_address),
+            &cw20::Cw20ExecuteMsg::Mint {
+                recipient: USER_1.to_string(),
+                amount: Uint128::from(100000000u128),
+            },
+            &[Coin {
+                amount: Uint128::from(100000000u128),
+                denom: NATIVE_DENOM.to_string(),
+            }],
+        )
+        .unwrap();
+
+        // USER_1 offers any nft with a gold background
+        let offer_msg = ExecuteMsg::OfferTraitNft {
+            nft: gold_background(&cw2981_address),
+            funds_amount: MOCK_OFFER_CW20_PRICE,
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &offer_msg,
+            &[],
+        )
+        .unwrap();
+
+        // the offer is stored with the token id built from its traits
+        let res: OffersResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::NftOffers {
+                    contract_address: cw2981_address.clone(),
+                    token_id: trait_offer_token_id(&gold_background(&cw2981_address).traits),
+                    start_after_offerer: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.offers.len(), 1);
+
+        // USER_2 approves marketplace to transfer nft token
+        let approve_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::ApproveAll {
+            operator: marketplace_address.clone(),
+            expires: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&cw2981_address),
+            &approve_msg,
+            &[],
+        )
+        .unwrap();
+
+        // the blue nft does not match the offer
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::AcceptTraitOffer {
+                offerer: USER_1.to_string(),
+                nft: gold_background(&cw2981_address),
+                token_id: MOCK_OFFER_NFT_TOKEN_ID_2.to_string(),
+                funds_amount: MOCK_OFFER_CW20_PRICE,
+            },
+            &[],
+        );
+        assert!(res.is_err());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::AcceptTraitOffer {
+                offerer: USER_1.to_string(),
+                nft: gold_background(&cw2981_address),
+                token_id: MOCK_OFFER_NFT_TOKEN_ID_1.to_string(),
+                funds_amount: MOCK_OFFER_CW20_PRICE,
+            },
+            &[],
+        );
+        assert!(res.is_ok());
+
+        // assert the gold NFT is transfered to USER_1
+        let res: cw721::OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&cw2981_address),
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: MOCK_OFFER_NFT_TOKEN_ID_1.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, USER_1.to_string());
+    }
+
+    #[test]
+    fn different_traits_have_different_token_ids() {
+        let trait_of = |trait_type: &str, value: &str| NftTrait {
+            trait_type: trait_type.to_string(),
+            value: value.to_string(),
+        };
+
+        // the separators of the traits can be part of a trait
+        assert_ne!(
+            trait_offer_token_id(&[trait_of("Hat", "Red=Blue")]),
+            trait_offer_token_id(&[trait_of("Hat=Red", "Blue")])
+        );
+        assert_ne!(
+            trait_offer_token_id(&[trait_of("Hat", "Red,Eyes=Blue")]),
+            trait_offer_token_id(&[trait_of("Hat", "Red"), trait_of("Eyes", "Blue")])
+        );
+
+        // the order of the traits does not matter
+        assert_eq!(
+            trait_offer_token_id(&[trait_of("Hat", "Red"), trait_of("Eyes", "Blue")]),
+            trait_offer_token_id(&[trait_of("Eyes", "Blue"), trait_of("Hat", "Red")])
+        );
+    }
+
+    #[test]
+    fn offerer_can_cancel_trait_offer() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft_with_background(&mut app, MOCK_OFFER_NFT_TOKEN_ID_1, USER_2, &cw2981_address);
+
+        // USER_1 offers any nft with a gold background
+        let offer_msg = ExecuteMsg::OfferTraitNft {
+            nft: gold_background(&cw2981_address),
+            funds_amount: MOCK_OFFER_CW20_PRICE,
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &offer_msg,
+            &[],
+        )
+        .unwrap();
+
+        let cancel_msg = ExecuteMsg::CancelTraitOffer {
+            contract_address: cw2981_address.clone(),
+            traits: gold_background(&cw2981_address).traits,
+        };
+
+        // only the offerer can cancel its offer
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &cancel_msg,
+            &[],
+        );
+        assert!(res.is_err());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &cancel_msg,
+            &[],
+        );
+        assert!(res.is_ok());
+
+        let res: OffersResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::NftOffers {
+                    contract_address: cw2981_address.clone(),
+                    token_id: trait_offer_token_id(&gold_background(&cw2981_address).traits),
+                    start_after_offerer: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert!(res.offers.is_empty());
+    }
+}
+
+mod swap_offer {
+    use super::*;
+
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+
+    const MOCK_SWEETENER_AMOUNT: u128 = 1000;
+
+    fn approve_nft(
+        app: &mut App,
+        token_id: &str,
+        owner: &str,
+        cw2981_address: &str,
+        spender: &str,
+    ) {
+        // the swap offer requires a never expired approval
+        let approve_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::Approve {
+            spender: spender.to_string(),
+            token_id: token_id.to_string(),
+            expires: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(owner),
+            Addr::unchecked(cw2981_address),
+            &approve_msg,
+            &[],
+        )
+        .unwrap();
+    }
+
+    fn nft(cw2981_address: &str, token_id: &str) -> NFT {
+        NFT {
+            contract_address: Addr::unchecked(cw2981_address),
+            token_id: Some(token_id.to_string()),
+        }
+    }
+
+    fn create_swap_offer(
+        app: &mut App,
+        offerer: &str,
+        cw2981_address: &str,
+        marketplace_address: &str,
+        offered_token_id: &str,
+    ) -> AnyResult<AppResponse> {
+        // the offerer offers a nft and some vaura token for token 2
+        let offer_swap_msg = ExecuteMsg::OfferSwap {
+            nft: nft(cw2981_address, MOCK_OFFER_NFT_TOKEN_ID_2),
+            offered_nfts: vec![nft(cw2981_address, offered_token_id)],
+            funds_amount: MOCK_SWEETENER_AMOUNT,
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+        app.execute_contract(
+            Addr::unchecked(offerer),
+            Addr::unchecked(marketplace_address),
+            &offer_swap_msg,
+            &[],
+        )
+    }
+
+    fn query_nft_owner(app: &App, cw2981_address: &str, token_id: &str) -> String {
+        let res: cw721::OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw2981_address),
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: token_id.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        res.owner
+    }
+
+    #[test]
+    fn cannot_offer_nft_of_other() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            OWNER,
+            cw2981_address.clone(),
+        );
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_2,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        // USER_1 cannot offer the nft of OWNER
+        let res = create_swap_offer(
+            &mut app,
+            USER_1,
+            &cw2981_address,
+            &marketplace_address,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn owner_can_accept_swap_offer() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[2].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_1,
+            cw2981_address.clone(),
+        );
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_2,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        // execute mint function to convert native token to twilight token
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&cw20_address),
+            &cw20::Cw20ExecuteMsg::Mint {
+                recipient: USER_1.to_string(),
+                amount: Uint128::from(100000000u128),
+            },
+            &[Coin {
+                amount: Uint128::from(100000000u128),
+                denom: NATIVE_DENOM.to_string(),
+            }],
+        )
+        .unwrap();
+
+        approve_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_1,
+            &cw2981_address,
+            &marketplace_address,
+        );
+        let res = create_swap_offer(
+            &mut app,
+            USER_1,
+            &cw2981_address,
+            &marketplace_address,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+        );
+        assert!(res.is_ok());
+
+        let res: OffersResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::SwapOffers {
+                    contract_address: cw2981_address.clone(),
+                    token_id: MOCK_OFFER_NFT_TOKEN_ID_2.to_string(),
+                    start_after_offerer: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.offers.len(), 1);
+        assert_eq!(res.offers[0].offer.len(), 2);
+
+        approve_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_2,
+            USER_2,
+            &cw2981_address,
+            &marketplace_address,
+        );
+
+        // the accepted offer must match the stored offer
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::AcceptSwapOffer {
+                offerer: USER_1.to_string(),
+                nft: nft(&cw2981_address, MOCK_OFFER_NFT_TOKEN_ID_2),
+                offered_nfts: vec![nft(&cw2981_address, MOCK_OFFER_NFT_TOKEN_ID_1)],
+                funds_amount: MOCK_SWEETENER_AMOUNT + 1,
+            },
+            &[],
+        );
+        assert!(res.is_err());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::AcceptSwapOffer {
+                offerer: USER_1.to_string(),
+                nft: nft(&cw2981_address, MOCK_OFFER_NFT_TOKEN_ID_2),
+                offered_nfts: vec![nft(&cw2981_address, MOCK_OFFER_NFT_TOKEN_ID_1)],
+                funds_amount: MOCK_SWEETENER_AMOUNT,
+            },
+            &[],
+        );
+        assert!(res.is_ok());
+
+        // the nfts are swapped
+        assert_eq!(
+            query_nft_owner(&app, &cw2981_address, MOCK_OFFER_NFT_TOKEN_ID_1),
+            USER_2
+        );
+        assert_eq!(
+            query_nft_owner(&app, &cw2981_address, MOCK_OFFER_NFT_TOKEN_ID_2),
+            USER_1
+        );
+
+        // the sweetener is paid to USER_2 after the royalty (20%)
+        let res: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw20_address),
+                &Cw20QueryMsg::Balance {
+                    address: USER_2.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.balance, Uint128::from(800u128));
+    }
+
+    #[test]
+    fn swap_offer_does_not_collide_with_offer_of_other_nft() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        // the token id of an nft can look like the key of a swap offer
+        let lookalike_token_id = format!("swap:{}", MOCK_OFFER_NFT_TOKEN_ID_2);
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_1,
+            cw2981_address.clone(),
+        );
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_2,
+            USER_2,
+            cw2981_address.clone(),
+        );
+        mint_nft(
+            &mut app,
+            &lookalike_token_id,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        // USER_1 offers funds for the lookalike nft and swaps its nft for token 2
+        let res = create_offer(
+            &mut app,
+            &lookalike_token_id,
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+        );
+        assert!(res.is_ok());
+        approve_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_1,
+            &cw2981_address,
+            &marketplace_address,
+        );
+        let res = create_swap_offer(
+            &mut app,
+            USER_1,
+            &cw2981_address,
+            &marketplace_address,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+        );
+        assert!(res.is_ok());
+
+        let query_user_offers = |app: &App, swap: bool| -> OffersResponse {
+            let msg = if swap {
+                QueryMsg::UserSwapOffers {
+                    offerer: USER_1.to_string(),
+                    start_after_nft: None,
+                    limit: None,
+                }
+            } else {
+                QueryMsg::UserOffers {
+                    offerer: USER_1.to_string(),
+                    start_after_nft: None,
+                    limit: None,
+                }
+            };
+            app.wrap()
+                .query_wasm_smart(Addr::unchecked(&marketplace_address), &msg)
+                .unwrap()
+        };
+        assert_eq!(query_user_offers(&app, false).offers.len(), 1);
+        assert_eq!(query_user_offers(&app, true).offers.len(), 1);
+
+        // USER_1 cancels its swap offer, the offer for the lookalike nft is kept
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::CancelSwapOffer {
+                nfts: vec![nft(&cw2981_address, MOCK_OFFER_NFT_TOKEN_ID_2)],
+            },
+            &[],
+        );
+        assert!(res.is_ok());
+
+        let res = query_user_offers(&app, false);
+        assert_eq!(res.offers.len(), 1);
+        assert_eq!(res.offers[0].order_id.2, lookalike_token_id);
+        assert!(query_user_offers(&app, true).offers.is_empty());
+
+        // the swap offer cannot be cancelled twice
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::CancelSwapOffer {
+                nfts: vec![nft(&cw2981_address, MOCK_OFFER_NFT_TOKEN_ID_2)],
+            },
+            &[],
+        );
+        assert!(res.is_err());
+    }
+}
+
+mod time_varying_offer {
+    use super::*;
+
+    use cw20::{BalanceResponse, Cw20QueryMsg};
+
+    fn create_time_varying_offer(
+        app: &mut App,
+        cw2981_address: &str,
+        marketplace_address: &str,
+        start_time: Option<Cw721Expiration>,
+    ) -> AnyResult<AppResponse> {
+        // the offered amount increases from 1000 to 2000 in 1000 seconds
+        let offer_nft_msg = ExecuteMsg::OfferNft {
+            nft: NFT {
+                contract_address: Addr::unchecked(cw2981_address),
+                token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
+            },
+            funds_amount: 1000,
+            end_funds_amount: Some(2000),
+            start_time,
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(marketplace_address),
+            &offer_nft_msg,
+            &[],
+        )
+    }
+
+    #[test]
+    fn cannot_offer_time_varying_amount_without_start_time() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        let res = create_time_varying_offer(&mut app, &cw2981_address, &marketplace_address, None);
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn accept_time_varying_offer_at_current_amount() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[2].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        // execute mint function to convert native token to twilight token
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&cw20_address),
+            &cw20::Cw20ExecuteMsg::Mint {
+                recipient: USER_1.to_string(),
+                amount: Uint128::from(100000000u128),
+            },
+            &[Coin {
+                amount: Uint128::from(100000000u128),
+                denom: NATIVE_DENOM.to_string(),
+            }],
+        )
+        .unwrap();
+
+        let start_time = Cw721Expiration::AtTime(app.block_info().time);
+        let res = create_time_varying_offer(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            Some(start_time),
+        );
+        assert!(res.is_ok());
+
+        // USER_2 approves marketplace to transfer nft token
+        let approve_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::ApproveAll {
+            operator: marketplace_address.clone(),
+            expires: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(cw2981_address.clone()),
+            &approve_msg,
+            &[],
+        )
+        .unwrap();
+
+        // the offered amount is 1500 at the half of the offer duration
+        app.update_block(|block| block.time = block.time.plus_seconds(500));
+
+        let accept_msg = |funds_amount: u128| ExecuteMsg::AcceptNftOffer {
+            offerer: USER_1.to_string(),
+            nft: NFT {
+                contract_address: Addr::unchecked(cw2981_address.clone()),
+                token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
+            },
+            funds_amount,
+        };
+
+        // the sender cannot expect more than the current amount
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address.clone()),
+            &accept_msg(1600),
+            &[],
+        );
+        assert!(res.is_err());
+
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address.clone()),
+            &accept_msg(1400),
+            &[],
+        );
+        assert!(res.is_ok());
+
+        // the current amount is paid to USER_2 after the royalty (20%)
+        let res: BalanceResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(cw20_address),
+                &Cw20QueryMsg::Balance {
+                    address: USER_2.to_string(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.balance, Uint128::from(1200u128));
+    }
+
+    #[test]
+    fn cannot_accept_offer_before_start_time() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[2].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        // execute mint function to convert native token to twilight token
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&cw20_address),
+            &cw20::Cw20ExecuteMsg::Mint {
+                recipient: USER_1.to_string(),
+                amount: Uint128::from(100000000u128),
+            },
+            &[Coin {
+                amount: Uint128::from(100000000u128),
+                denom: NATIVE_DENOM.to_string(),
+            }],
+        )
+        .unwrap();
+
+        // the offer of a constant amount starts in 100 seconds
+        let offer_nft_msg = ExecuteMsg::OfferNft {
+            nft: NFT {
+                contract_address: Addr::unchecked(&cw2981_address),
+                token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
+            },
+            funds_amount: 1000,
+            end_funds_amount: None,
+            start_time: Some(Cw721Expiration::AtTime(
+                app.block_info().time.plus_seconds(100),
+            )),
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &offer_nft_msg,
+            &[],
+        )
+        .unwrap();
+
+        // USER_2 approves marketplace to transfer nft token
+        let approve_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::ApproveAll {
+            operator: marketplace_address.clone(),
+            expires: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(cw2981_address.clone()),
+            &approve_msg,
+            &[],
+        )
+        .unwrap();
+
+        let accept_msg = ExecuteMsg::AcceptNftOffer {
+            offerer: USER_1.to_string(),
+            nft: NFT {
+                contract_address: Addr::unchecked(cw2981_address.clone()),
+                token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
+            },
+            funds_amount: 1000,
+        };
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address.clone()),
+            &accept_msg,
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().source().unwrap().to_string(),
+            ContractError::CustomError {
+                val: "Offer not started".to_string()
+            }
+            .to_string()
+        );
+
+        app.update_block(|block| block.time = block.time.plus_seconds(100));
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(marketplace_address),
+            &accept_msg,
+            &[],
+        );
+        assert!(res.is_ok());
+    }
+}
+
+mod offers_received {
+    use super::*;
+
+    use crate::msg::ReceivedOffersResponse;
+    use crate::state::{MAX_SCANNED_NFTS, MAX_SCANNED_OFFERS};
+
+    fn query_offers_received(
+        app: &App,
+        marketplace_address: &str,
+        collections: Vec<String>,
+        start_after_nft: Option<NFT>,
+    ) -> ReceivedOffersResponse {
+        app.wrap()
+            .query_wasm_smart(
+                Addr::unchecked(marketplace_address),
+                &QueryMsg::OffersReceived {
+                    owner: USER_2.to_string(),
+                    collections,
+                    start_after_nft,
+                    limit: Some(1),
+                },
+            )
+            .unwrap()
+    }
+
+    #[test]
+    fn owner_can_query_best_offers_of_its_nfts() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+        );
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_2,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        // USER_1 offers the first nft, and any nft of the collection for less
+        create_offer(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+        )
+        .unwrap();
+        let collection_offer_msg = ExecuteMsg::OfferNft {
+            nft: NFT {
+                contract_address: Addr::unchecked(&cw2981_address),
+                token_id: None,
+            },
+            funds_amount: MOCK_OFFER_CW20_PRICE / 2,
+            end_funds_amount: None,
+            start_time: None,
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &collection_offer_msg,
+            &[],
+        )
+        .unwrap();
+
+        // the offer of the first nft is better than the collection offer
+        let res = query_offers_received(
+            &app,
+            &marketplace_address,
+            vec![cw2981_address.clone()],
+            None,
+        );
+        assert_eq!(res.offers.len(), 1);
+        let received_offer = &res.offers[0];
+        assert_eq!(
+            received_offer.nft.token_id,
+            Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string())
+        );
+        assert_eq!(received_offer.offer.offer_amount(), MOCK_OFFER_CW20_PRICE);
+        assert_eq!(res.next, Some(received_offer.nft.clone()));
+
+        // the second nft only has the collection offer
+        let res = query_offers_received(
+            &app,
+            &marketplace_address,
+            vec![cw2981_address.clone()],
+            res.next,
+        );
+        assert_eq!(res.offers.len(), 1);
+        assert_eq!(
+            res.offers[0].nft.token_id,
+            Some(MOCK_OFFER_NFT_TOKEN_ID_2.to_string())
+        );
+        assert_eq!(
+            res.offers[0].offer.offer_amount(),
+            MOCK_OFFER_CW20_PRICE / 2
+        );
+
+        // there is no more nft
+        let res = query_offers_received(&app, &marketplace_address, vec![cw2981_address], res.next);
+        assert!(res.offers.is_empty());
+        assert_eq!(res.next, None);
+    }
+
+    #[test]
+    fn page_ends_after_max_scanned_nfts() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        // USER_2 holds one more nft than scanned in a page, only the last one has an offer
+        let token_ids: Vec<String> = (0..=MAX_SCANNED_NFTS)
+            .map(|i| format!("token{:03}", i))
+            .collect();
+        for token_id in token_ids.iter() {
+            mint_nft(&mut app, token_id, USER_2, cw2981_address.clone());
+        }
+        create_offer(
+            &mut app,
+            &token_ids[MAX_SCANNED_NFTS],
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+        )
+        .unwrap();
+
+        let nft = |token_id: &str| NFT {
+            contract_address: Addr::unchecked(&cw2981_address),
+            token_id: Some(token_id.to_string()),
+        };
+
+        // the first page scans the nfts without offer
+        let res = query_offers_received(
+            &app,
+            &marketplace_address,
+            vec![cw2981_address.clone()],
+            None,
+        );
+        assert!(res.offers.is_empty());
+        assert_eq!(res.next, Some(nft(&token_ids[MAX_SCANNED_NFTS - 1])));
+
+        // the next page continues with the last nft
+        let res = query_offers_received(
+            &app,
+            &marketplace_address,
+            vec![cw2981_address.clone()],
+            res.next,
+        );
+        assert_eq!(res.offers.len(), 1);
+        assert_eq!(res.offers[0].nft, nft(&token_ids[MAX_SCANNED_NFTS]));
+    }
+
+    #[test]
+    fn page_ends_before_nft_with_more_offers_than_budget() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        // the first nft has a cancelled offer, the second one has as many offers as the budget
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+        );
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_2,
+            USER_2,
+            cw2981_address.clone(),
+        );
+        create_offer(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+        )
+        .unwrap();
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::IncrementCounter {},
+            &[],
+        )
+        .unwrap();
+        for i in 0..MAX_SCANNED_OFFERS {
+            create_offer(
+                &mut app,
+                MOCK_OFFER_NFT_TOKEN_ID_2,
+                &format!("offerer{:03}", i),
+                cw2981_address.clone(),
+                marketplace_address.clone(),
+            )
+            .unwrap();
+        }
+
+        let nft = |token_id: &str| NFT {
+            contract_address: Addr::unchecked(&cw2981_address),
+            token_id: Some(token_id.to_string()),
+        };
+
+        // the offers of the second nft exceed the budget left after the first nft
+        let res = query_offers_received(
+            &app,
+            &marketplace_address,
+            vec![cw2981_address.clone()],
+            None,
+        );
+        assert!(res.offers.is_empty());
+        assert_eq!(res.next, Some(nft(MOCK_OFFER_NFT_TOKEN_ID_1)));
+
+        // the next page starts with the second nft and compares all its offers
+        let res = query_offers_received(
+            &app,
+            &marketplace_address,
+            vec![cw2981_address.clone()],
+            res.next,
+        );
+        assert_eq!(res.offers.len(), 1);
+        assert_eq!(res.offers[0].nft, nft(MOCK_OFFER_NFT_TOKEN_ID_2));
+    }
+}
+
+mod collection_registry {
+    use super::*;
+
+    use crate::state::CollectionStatus;
+
+    #[test]
+    fn cannot_offer_blocked_collection() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        // OWNER blocks the collection
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::RegisterCollection {
+                contract_address: cw2981_address.clone(),
+                name: "Blocked collection".to_string(),
+                description: Some("A spoofed collection".to_string()),
+                status: CollectionStatus::Blocked,
+            },
+            &[],
+        )
+        .unwrap();
+
+        let res = create_offer(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_1,
+            cw2981_address,
+            marketplace_address,
+        );
+        assert_eq!(
+            res.unwrap_err().root_cause().to_string(),
+            "Custom Error val: \"Collection is not allowed\""
+        );
+    }
+
+    #[test]
+    fn cannot_accept_offer_of_collection_blocked_later() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_2,
+            cw2981_address.clone(),
+        );
+
+        // USER_1 makes an offer before the collection is blocked
+        let res = create_offer(
+            &mut app,
+            MOCK_OFFER_NFT_TOKEN_ID_1,
+            USER_1,
+            cw2981_address.clone(),
+            marketplace_address.clone(),
+        );
+        assert!(res.is_ok());
+
+        // OWNER blocks the collection
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::RegisterCollection {
+                contract_address: cw2981_address.clone(),
+                name: "Blocked collection".to_string(),
+                description: Some("A spoofed collection".to_string()),
+                status: CollectionStatus::Blocked,
+            },
+            &[],
+        )
+        .unwrap();
+
+        // USER_2 cannot accept the offer anymore
+        let res = app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::AcceptNftOffer {
+                offerer: USER_1.to_string(),
+                nft: NFT {
+                    contract_address: Addr::unchecked(&cw2981_address),
+                    token_id: Some(MOCK_OFFER_NFT_TOKEN_ID_1.to_string()),
+                },
+                funds_amount: MOCK_OFFER_CW20_PRICE,
+            },
+            &[],
+        );
+        assert_eq!(
+            res.unwrap_err().root_cause().to_string(),
+            "Custom Error val: \"Collection is not allowed\""
+        );
+    }
+}
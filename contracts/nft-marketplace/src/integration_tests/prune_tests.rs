@@ -0,0 +1,133 @@
+use crate::msg::{BuyNftItem, ExecuteMsg, ListingsResponse, QueryMsg, ValidateResponse};
+
+use crate::test_setup::env::{instantiate_contracts, mint_and_list_nft, OWNER, USER_1, USER_2};
+
+const MOCK_PRICE: u128 = 1000;
+
+use cosmwasm_std::Addr;
+use cw_multi_test::{App, Executor};
+
+use cw2981_royalties::Metadata;
+use cw721_base::msg::ExecuteMsg as Cw721ExecuteMsg;
+
+fn is_valid_listing(
+    app: &App,
+    token_id: &str,
+    cw2981_address: &str,
+    marketplace_address: &str,
+) -> bool {
+    let res: ValidateResponse = app
+        .wrap()
+        .query_wasm_smart(
+            Addr::unchecked(marketplace_address),
+            &QueryMsg::ValidateListing {
+                contract_address: cw2981_address.to_string(),
+                token_id: token_id.to_string(),
+            },
+        )
+        .unwrap();
+    res.valid
+}
+
+mod prune_listings {
+    use super::*;
+
+    #[test]
+    fn anyone_can_prune_stale_listings() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        for token_id in ["1", "2", "3"] {
+            mint_and_list_nft(
+                &mut app,
+                &cw2981_address,
+                &marketplace_address,
+                USER_2,
+                token_id,
+                MOCK_PRICE,
+            );
+        }
+
+        // USER_2 transfers nft 2 to OWNER
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&cw2981_address),
+            &Cw721ExecuteMsg::<Metadata, Metadata>::TransferNft {
+                recipient: OWNER.to_string(),
+                token_id: "2".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        // USER_2 revokes the approval of nft 3
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&cw2981_address),
+            &Cw721ExecuteMsg::<Metadata, Metadata>::Revoke {
+                spender: marketplace_address.clone(),
+                token_id: "3".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        assert!(is_valid_listing(
+            &app,
+            "1",
+            &cw2981_address,
+            &marketplace_address
+        ));
+        assert!(!is_valid_listing(
+            &app,
+            "2",
+            &cw2981_address,
+            &marketplace_address
+        ));
+        assert!(!is_valid_listing(
+            &app,
+            "3",
+            &cw2981_address,
+            &marketplace_address
+        ));
+        assert!(!is_valid_listing(
+            &app,
+            "4",
+            &cw2981_address,
+            &marketplace_address
+        ));
+
+        // USER_1 prunes the listings
+        let listings = ["1", "2", "3", "4"]
+            .iter()
+            .map(|token_id| BuyNftItem {
+                contract_address: cw2981_address.clone(),
+                token_id: token_id.to_string(),
+            })
+            .collect();
+        let res = app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::PruneListings { listings },
+            &[],
+        );
+        assert!(res.is_ok());
+
+        // only the valid listing remains
+        let res: ListingsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::ListingsByContractAddress {
+                    contract_address: cw2981_address,
+                    start_after: None,
+                    limit: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.listings.len(), 1);
+        assert_eq!(res.listings[0].token_id, "1");
+    }
+}
@@ -0,0 +1,366 @@
+use crate::msg::{CollectionStatsResponse, ExecuteMsg, QueryMsg, SalesResponse};
+use crate::order_state::{PaymentAsset, NFT};
+use crate::state::{Sale, SaleKind, MAX_NFT_SALES};
+
+use crate::test_setup::env::{
+    approve_nft, instantiate_contracts, list_nft, mint_nft, NATIVE_DENOM, USER_1, USER_2,
+};
+
+use cosmwasm_std::{coins, Addr, Coin, Uint128};
+use cw_multi_test::{App, Executor};
+
+use cw721::Expiration as Cw721Expiration;
+
+const MOCK_NFT_TOKEN_ID: &str = "token1";
+
+const MOCK_LISTING_PRICE: u128 = 1000;
+const MOCK_OFFER_PRICE: u128 = 500;
+
+// the seller lists the nft and the buyer buys it
+fn list_and_buy(
+    app: &mut App,
+    cw2981_address: &str,
+    marketplace_address: &str,
+    seller: &str,
+    buyer: &str,
+) {
+    list_nft(
+        app,
+        cw2981_address,
+        marketplace_address,
+        seller,
+        MOCK_NFT_TOKEN_ID,
+        MOCK_LISTING_PRICE,
+    );
+
+    let buy_msg = ExecuteMsg::Buy {
+        contract_address: cw2981_address.to_string(),
+        token_id: MOCK_NFT_TOKEN_ID.to_string(),
+    };
+    app.execute_contract(
+        Addr::unchecked(buyer),
+        Addr::unchecked(marketplace_address),
+        &buy_msg,
+        &coins(MOCK_LISTING_PRICE, NATIVE_DENOM),
+    )
+    .unwrap();
+}
+
+fn query_sale_history(
+    app: &App,
+    cw2981_address: &str,
+    marketplace_address: &str,
+    token_id: Option<&str>,
+    start_after: Option<u64>,
+) -> Vec<Sale> {
+    let res: SalesResponse = app
+        .wrap()
+        .query_wasm_smart(
+            Addr::unchecked(marketplace_address),
+            &QueryMsg::SaleHistory {
+                contract_address: cw2981_address.to_string(),
+                token_id: token_id.map(|token_id| token_id.to_string()),
+                start_after,
+                limit: None,
+            },
+        )
+        .unwrap();
+    res.sales
+}
+
+mod sale_history {
+    use super::*;
+
+    #[test]
+    fn listing_and_offer_sales_are_recorded() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+        let cw20_address = contracts[2].contract_addr.clone();
+
+        mint_nft(&mut app, &cw2981_address, USER_2, MOCK_NFT_TOKEN_ID);
+
+        // USER_1 buys the nft listed by USER_2
+        list_and_buy(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            USER_1,
+        );
+
+        // USER_2 converts native token to vaura token to offer the nft back
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&cw20_address),
+            &cw20::Cw20ExecuteMsg::Mint {
+                recipient: USER_2.to_string(),
+                amount: Uint128::from(MOCK_OFFER_PRICE),
+            },
+            &[Coin {
+                amount: Uint128::from(MOCK_OFFER_PRICE),
+                denom: NATIVE_DENOM.to_string(),
+            }],
+        )
+        .unwrap();
+
+        let nft = NFT {
+            contract_address: Addr::unchecked(&cw2981_address),
+            token_id: Some(MOCK_NFT_TOKEN_ID.to_string()),
+        };
+        let offer_msg = ExecuteMsg::OfferNft {
+            nft: nft.clone(),
+            funds_amount: MOCK_OFFER_PRICE,
+            end_funds_amount: None,
+            start_time: None,
+            end_time: Cw721Expiration::AtTime(app.block_info().time.plus_seconds(1000)),
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &offer_msg,
+            &[],
+        )
+        .unwrap();
+
+        // USER_1 accepts the offer of USER_2
+        approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_1,
+            MOCK_NFT_TOKEN_ID,
+        );
+        let accept_msg = ExecuteMsg::AcceptNftOffer {
+            offerer: USER_2.to_string(),
+            nft,
+            funds_amount: MOCK_OFFER_PRICE,
+        };
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &accept_msg,
+            &[],
+        )
+        .unwrap();
+
+        // the latest sale is returned first
+        let sales = query_sale_history(
+            &app,
+            &cw2981_address,
+            &marketplace_address,
+            Some(MOCK_NFT_TOKEN_ID),
+            None,
+        );
+        assert_eq!(sales.len(), 2);
+        assert_eq!(
+            sales[0],
+            Sale {
+                id: 2,
+                contract_address: Addr::unchecked(&cw2981_address),
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+                price: PaymentAsset::Cw20 {
+                    contract_address: Addr::unchecked(&cw20_address),
+                    amount: MOCK_OFFER_PRICE,
+                },
+                buyer: Addr::unchecked(USER_2),
+                seller: Addr::unchecked(USER_1),
+                time: app.block_info().time,
+                kind: SaleKind::Offer,
+            }
+        );
+        assert_eq!(
+            sales[1],
+            Sale {
+                id: 1,
+                contract_address: Addr::unchecked(&cw2981_address),
+                token_id: MOCK_NFT_TOKEN_ID.to_string(),
+                price: PaymentAsset::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: MOCK_LISTING_PRICE,
+                },
+                buyer: Addr::unchecked(USER_1),
+                seller: Addr::unchecked(USER_2),
+                time: app.block_info().time,
+                kind: SaleKind::Listing,
+            }
+        );
+
+        // the collection history contains the same sales
+        let collection_sales =
+            query_sale_history(&app, &cw2981_address, &marketplace_address, None, None);
+        assert_eq!(collection_sales, sales);
+
+        // the history is paginated by the id of the sales
+        let sales = query_sale_history(&app, &cw2981_address, &marketplace_address, None, Some(2));
+        assert_eq!(sales.len(), 1);
+        assert_eq!(sales[0].id, 1);
+
+        // the last sale is the accepted offer
+        let last_sale: Option<Sale> = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::LastSale {
+                    contract_address: cw2981_address.clone(),
+                    token_id: Some(MOCK_NFT_TOKEN_ID.to_string()),
+                },
+            )
+            .unwrap();
+        assert_eq!(last_sale.unwrap().kind, SaleKind::Offer);
+    }
+
+    #[test]
+    fn only_latest_sales_are_kept() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft(&mut app, &cw2981_address, USER_2, MOCK_NFT_TOKEN_ID);
+
+        // the nft is sold back and forth between USER_1 and USER_2
+        for i in 0..=MAX_NFT_SALES {
+            let (seller, buyer) = if i % 2 == 0 {
+                (USER_2, USER_1)
+            } else {
+                (USER_1, USER_2)
+            };
+            list_and_buy(
+                &mut app,
+                &cw2981_address,
+                &marketplace_address,
+                seller,
+                buyer,
+            );
+        }
+
+        // the oldest sale is removed from the history of the nft
+        let mut sales = vec![];
+        let mut start_after = None;
+        loop {
+            let page = query_sale_history(
+                &app,
+                &cw2981_address,
+                &marketplace_address,
+                Some(MOCK_NFT_TOKEN_ID),
+                start_after,
+            );
+            match page.last() {
+                Some(sale) => start_after = Some(sale.id),
+                None => break,
+            }
+            sales.extend(page);
+        }
+        assert_eq!(sales.len() as u64, MAX_NFT_SALES);
+        assert_eq!(sales.first().unwrap().id, MAX_NFT_SALES + 1);
+        assert_eq!(sales.last().unwrap().id, 2);
+
+        // the collection history keeps more sales
+        let sales = query_sale_history(&app, &cw2981_address, &marketplace_address, None, Some(2));
+        assert_eq!(sales.len(), 1);
+        assert_eq!(sales[0].id, 1);
+    }
+}
+
+mod collection_stats {
+    use super::*;
+
+    #[test]
+    fn stats_include_active_listings_and_sales() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_nft(&mut app, &cw2981_address, USER_2, MOCK_NFT_TOKEN_ID);
+        mint_nft(&mut app, &cw2981_address, USER_2, "token2");
+        mint_nft(&mut app, &cw2981_address, USER_2, "token3");
+
+        list_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            "token2",
+            800,
+        );
+        list_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            "token3",
+            600,
+        );
+
+        // the nft listed by list_and_buy and the cheapest listing are bought
+        list_and_buy(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            USER_1,
+        );
+        app.execute_contract(
+            Addr::unchecked(USER_1),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::Buy {
+                contract_address: cw2981_address.clone(),
+                token_id: "token3".to_string(),
+            },
+            &coins(600, NATIVE_DENOM),
+        )
+        .unwrap();
+
+        let res: CollectionStatsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::CollectionStats {
+                    contract_address: cw2981_address.clone(),
+                    start_after: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(
+            res,
+            CollectionStatsResponse {
+                floor_prices: vec![PaymentAsset::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: 800,
+                }],
+                listing_count: 1,
+                volume: vec![PaymentAsset::Native {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: MOCK_LISTING_PRICE + 600,
+                }],
+                sale_count: 2,
+                next: None,
+            }
+        );
+
+        // the listings of a seller who increments its counter are not active
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::IncrementCounter {},
+            &[],
+        )
+        .unwrap();
+        let res: CollectionStatsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::CollectionStats {
+                    contract_address: cw2981_address,
+                    start_after: None,
+                },
+            )
+            .unwrap();
+        assert!(res.floor_prices.is_empty());
+        assert_eq!(res.listing_count, 0);
+        assert_eq!(res.sale_count, 2);
+    }
+}
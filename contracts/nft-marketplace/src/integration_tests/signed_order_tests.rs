@@ -0,0 +1,320 @@
+use crate::msg::{CollectionStatsResponse, CounterResponse, ExecuteMsg, QueryMsg};
+use crate::order_state::{
+    consideration_item, offer_item, Asset, ItemType, OrderComponents, OrderType, PaymentAsset,
+    NATIVE, NFT,
+};
+
+use crate::state::contract;
+use crate::test_setup::env::{instantiate_contracts, mint_and_approve_nft, NATIVE_DENOM, OWNER};
+
+use anyhow::Result as AnyResult;
+
+use bech32::{ToBase32, Variant};
+use cosmwasm_std::testing::mock_env;
+use cosmwasm_std::{Addr, Binary, Coin, Uint128};
+use cw_multi_test::{App, AppResponse, Executor};
+use k256::ecdsa::signature::hazmat::PrehashSigner;
+use k256::ecdsa::{Signature, SigningKey};
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+
+use cw2981_royalties::QueryMsg as Cw721QueryMsg;
+
+const MOCK_NFT_TOKEN_ID_1: &str = "token1";
+
+const MOCK_PRICE: u128 = 1000;
+
+fn signing_key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32]).unwrap()
+}
+
+fn public_key(key: &SigningKey) -> Binary {
+    Binary::from(key.verifying_key().to_bytes().as_slice())
+}
+
+// the address of the signing key, derived as a cosmos account address
+fn maker_address(key: &SigningKey) -> String {
+    let hash = Ripemd160::digest(Sha256::digest(public_key(key).as_slice()));
+    bech32::encode("aura", hash.to_base32(), Variant::Bech32).unwrap()
+}
+
+// the maker sells the nft for the native token
+fn listing_order(maker: &str, cw2981_address: &str) -> OrderComponents {
+    let nft = Asset::Nft(NFT {
+        contract_address: Addr::unchecked(cw2981_address),
+        token_id: Some(MOCK_NFT_TOKEN_ID_1.to_string()),
+    });
+    let price = Asset::Native(NATIVE {
+        denom: NATIVE_DENOM.to_string(),
+        amount: MOCK_PRICE,
+    });
+    OrderComponents {
+        order_type: OrderType::LISTING,
+        order_id: (
+            Addr::unchecked(maker),
+            Addr::unchecked(cw2981_address),
+            MOCK_NFT_TOKEN_ID_1.to_string(),
+        ),
+        offerer: Addr::unchecked(maker),
+        offer: vec![offer_item(&ItemType::CW721, &nft, &1u128, &1u128)],
+        consideration: vec![consideration_item(
+            &ItemType::NATIVE,
+            &price,
+            &MOCK_PRICE,
+            &MOCK_PRICE,
+            &Addr::unchecked(maker),
+        )],
+        start_time: None,
+        end_time: None,
+        counter: 0,
+    }
+}
+
+fn sign_order(
+    app: &App,
+    key: &SigningKey,
+    marketplace_address: &str,
+    order: &OrderComponents,
+) -> Binary {
+    let order_hash: Binary = app
+        .wrap()
+        .query_wasm_smart(
+            Addr::unchecked(marketplace_address),
+            &QueryMsg::SignedOrderHash {
+                order: order.clone(),
+            },
+        )
+        .unwrap();
+    let signature: Signature = key.sign_prehash(order_hash.as_slice()).unwrap();
+    Binary::from(signature.as_ref())
+}
+
+fn fulfill_order(
+    app: &mut App,
+    marketplace_address: &str,
+    order: OrderComponents,
+    signature: Binary,
+    public_key: Binary,
+) -> AnyResult<AppResponse> {
+    app.execute_contract(
+        Addr::unchecked(OWNER),
+        Addr::unchecked(marketplace_address),
+        &ExecuteMsg::FulfillSignedOrder {
+            order,
+            signature,
+            public_key,
+        },
+        &[Coin {
+            denom: NATIVE_DENOM.to_string(),
+            amount: Uint128::from(MOCK_PRICE),
+        }],
+    )
+}
+
+mod signed_order {
+    use super::*;
+
+    #[test]
+    fn buy_signed_listing() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        let key = signing_key(1);
+        let maker = maker_address(&key);
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            &maker,
+            MOCK_NFT_TOKEN_ID_1,
+        );
+
+        let order = listing_order(&maker, &cw2981_address);
+        let signature = sign_order(&app, &key, &marketplace_address, &order);
+
+        let res = fulfill_order(
+            &mut app,
+            &marketplace_address,
+            order.clone(),
+            signature.clone(),
+            public_key(&key),
+        );
+        assert!(res.is_ok());
+
+        // assert NFT is transfered to OWNER
+        let res: cw721::OwnerOfResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&cw2981_address),
+                &Cw721QueryMsg::OwnerOf {
+                    token_id: MOCK_NFT_TOKEN_ID_1.to_string(),
+                    include_expired: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.owner, OWNER);
+
+        // the royalty (20%) is paid to the creator (OWNER), the rest to the maker
+        let res = app.wrap().query_balance(&maker, NATIVE_DENOM).unwrap();
+        assert_eq!(res.amount, Uint128::from(800u128));
+
+        // the sale is counted in the stats of the collection
+        let res: CollectionStatsResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::CollectionStats {
+                    contract_address: cw2981_address.clone(),
+                    start_after: None,
+                },
+            )
+            .unwrap();
+        assert_eq!(res.sale_count, 1);
+        assert_eq!(
+            res.volume,
+            vec![PaymentAsset::Native {
+                denom: NATIVE_DENOM.to_string(),
+                amount: MOCK_PRICE
+            }]
+        );
+
+        // the signed order cannot be fulfilled twice
+        let res = fulfill_order(
+            &mut app,
+            &marketplace_address,
+            order,
+            signature,
+            public_key(&key),
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn cannot_fulfill_order_signed_by_other_key() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        let key = signing_key(1);
+        let maker = maker_address(&key);
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            &maker,
+            MOCK_NFT_TOKEN_ID_1,
+        );
+
+        // the order is signed by another key
+        let other_key = signing_key(2);
+        let order = listing_order(&maker, &cw2981_address);
+        let signature = sign_order(&app, &other_key, &marketplace_address, &order);
+
+        let res = fulfill_order(
+            &mut app,
+            &marketplace_address,
+            order.clone(),
+            signature.clone(),
+            public_key(&key),
+        );
+        assert!(res.is_err());
+
+        // the public key of another key does not belong to the maker
+        let res = fulfill_order(
+            &mut app,
+            &marketplace_address,
+            order,
+            signature,
+            public_key(&other_key),
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn incremented_counter_invalidates_signed_orders() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        let key = signing_key(1);
+        let maker = maker_address(&key);
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            &maker,
+            MOCK_NFT_TOKEN_ID_1,
+        );
+
+        let order = listing_order(&maker, &cw2981_address);
+        let signature = sign_order(&app, &key, &marketplace_address, &order);
+
+        // the maker increments its counter after signing the order
+        app.execute_contract(
+            Addr::unchecked(&maker),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::IncrementCounter {},
+            &[],
+        )
+        .unwrap();
+
+        let res: CounterResponse = app
+            .wrap()
+            .query_wasm_smart(
+                Addr::unchecked(&marketplace_address),
+                &QueryMsg::Counter {
+                    user: maker.clone(),
+                },
+            )
+            .unwrap();
+        assert_eq!(res.counter, 1);
+
+        let res = fulfill_order(
+            &mut app,
+            &marketplace_address,
+            order,
+            signature,
+            public_key(&key),
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn cannot_fulfill_order_signed_for_other_chain() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        let key = signing_key(1);
+        let maker = maker_address(&key);
+        mint_and_approve_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            &maker,
+            MOCK_NFT_TOKEN_ID_1,
+        );
+
+        // the order is signed for the same marketplace address on another chain
+        let order = listing_order(&maker, &cw2981_address);
+        let mut env = mock_env();
+        env.block.chain_id = format!("{}-fork", app.block_info().chain_id);
+        env.contract.address = Addr::unchecked(&marketplace_address);
+        let order_hash = contract().signed_order_hash(&env, &order).unwrap();
+        let signature: Signature = key.sign_prehash(order_hash.as_slice()).unwrap();
+
+        let res = fulfill_order(
+            &mut app,
+            &marketplace_address,
+            order,
+            Binary::from(signature.as_ref()),
+            public_key(&key),
+        );
+        assert!(res.is_err());
+    }
+}
@@ -0,0 +1,195 @@
+use crate::contract::reply;
+use crate::msg::{BuyNftItem, ExecuteMsg, ListingResponse, QueryMsg};
+use crate::ContractError;
+
+use crate::test_setup::env::{
+    instantiate_contracts, mint_and_list_nft, NATIVE_DENOM, OWNER, USER_2,
+};
+
+use cosmwasm_std::testing::{mock_dependencies, mock_env};
+use cosmwasm_std::{Addr, Coin, Reply, StdResult, SubMsgResult, Uint128};
+use cw_multi_test::Executor;
+
+use cw2981_royalties::{Metadata, QueryMsg as Cw721QueryMsg};
+use cw721_base::msg::ExecuteMsg as Cw721ExecuteMsg;
+
+const MOCK_PRICE: u128 = 1000;
+
+mod sweep {
+    use super::*;
+
+    #[test]
+    fn sweep_skips_listings_which_cannot_be_bought() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        for token_id in ["1", "2", "3"] {
+            mint_and_list_nft(
+                &mut app,
+                &cw2981_address,
+                &marketplace_address,
+                USER_2,
+                token_id,
+                MOCK_PRICE,
+            );
+        }
+
+        // USER_2 transfers nft 2 to OWNER, the listing of nft 2 is stale
+        app.execute_contract(
+            Addr::unchecked(USER_2),
+            Addr::unchecked(&cw2981_address),
+            &Cw721ExecuteMsg::<Metadata, Metadata>::TransferNft {
+                recipient: OWNER.to_string(),
+                token_id: "2".to_string(),
+            },
+            &[],
+        )
+        .unwrap();
+
+        let seller_balance = app.wrap().query_balance(USER_2, NATIVE_DENOM).unwrap();
+        let buyer_balance = app.wrap().query_balance(OWNER, NATIVE_DENOM).unwrap();
+
+        // OWNER sweeps all listings and a listing which does not exist
+        let items = ["1", "2", "3", "4"]
+            .iter()
+            .map(|token_id| BuyNftItem {
+                contract_address: cw2981_address.clone(),
+                token_id: token_id.to_string(),
+            })
+            .collect();
+        let res = app
+            .execute_contract(
+                Addr::unchecked(OWNER),
+                Addr::unchecked(&marketplace_address),
+                &ExecuteMsg::SweepNfts { items },
+                &[Coin {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::from(MOCK_PRICE * 4),
+                }],
+            )
+            .unwrap();
+
+        // the response lists the filled and skipped listings
+        let filled: Vec<String> = res
+            .events
+            .iter()
+            .flat_map(|event| event.attributes.iter())
+            .filter(|attr| attr.key == "filled")
+            .map(|attr| attr.value.clone())
+            .collect();
+        assert_eq!(
+            filled,
+            vec![
+                format!("{}/1", cw2981_address),
+                format!("{}/3", cw2981_address)
+            ]
+        );
+
+        // assert the nfts are transfered to OWNER
+        for token_id in ["1", "3"] {
+            let res: cw721::OwnerOfResponse = app
+                .wrap()
+                .query_wasm_smart(
+                    Addr::unchecked(&cw2981_address),
+                    &Cw721QueryMsg::OwnerOf {
+                        token_id: token_id.to_string(),
+                        include_expired: None,
+                    },
+                )
+                .unwrap();
+            assert_eq!(res.owner, OWNER.to_string());
+        }
+
+        // the stale listing is not removed since its buy failed
+        let res: StdResult<ListingResponse> = app.wrap().query_wasm_smart(
+            Addr::unchecked(&marketplace_address),
+            &QueryMsg::Listing {
+                contract_address: cw2981_address.clone(),
+                token_id: "2".to_string(),
+            },
+        );
+        assert!(res.is_ok());
+
+        // USER_2 receives the prices after the royalty (20%) of the filled listings
+        let balance = app.wrap().query_balance(USER_2, NATIVE_DENOM).unwrap();
+        assert_eq!(
+            balance.amount,
+            seller_balance.amount + Uint128::from(MOCK_PRICE * 2 * 80 / 100)
+        );
+
+        // OWNER is refunded the unused funds and receives the royalty as the creator
+        let balance = app.wrap().query_balance(OWNER, NATIVE_DENOM).unwrap();
+        assert_eq!(
+            balance.amount,
+            buyer_balance.amount - Uint128::from(MOCK_PRICE * 2 * 80 / 100)
+        );
+
+        // the marketplace does not hold any funds
+        let balance = app
+            .wrap()
+            .query_balance(&marketplace_address, NATIVE_DENOM)
+            .unwrap();
+        assert_eq!(balance.amount, Uint128::zero());
+    }
+
+    #[test]
+    fn only_marketplace_can_execute_sweep_item() {
+        // get integration test app and contracts
+        let (mut app, contracts) = instantiate_contracts();
+        let cw2981_address = contracts[0].contract_addr.clone();
+        let marketplace_address = contracts[1].contract_addr.clone();
+
+        mint_and_list_nft(
+            &mut app,
+            &cw2981_address,
+            &marketplace_address,
+            USER_2,
+            "1",
+            MOCK_PRICE,
+        );
+
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::SweepItem {
+                buyer: OWNER.to_string(),
+                contract_address: cw2981_address,
+                token_id: "1".to_string(),
+            },
+            &[Coin {
+                denom: NATIVE_DENOM.to_string(),
+                amount: Uint128::from(MOCK_PRICE),
+            }],
+        );
+        assert!(res.is_err());
+
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(&marketplace_address),
+            &ExecuteMsg::FinalizeSweep {},
+            &[],
+        );
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn unknown_reply_id_is_rejected() {
+        let mut deps = mock_dependencies();
+
+        // the reply id of a sweep item is offset by the reserved sweep range
+        let res = reply(
+            deps.as_mut(),
+            mock_env(),
+            Reply {
+                id: 0,
+                result: SubMsgResult::Err("error".to_string()),
+            },
+        );
+        match res {
+            Err(ContractError::CustomError { val }) => assert_eq!(val, "Unknown reply id: 0"),
+            _ => panic!("Unexpected result"),
+        }
+    }
+}
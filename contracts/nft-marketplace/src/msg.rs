@@ -1,10 +1,14 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::Addr;
+use cosmwasm_std::{Addr, Binary, Coin};
+use cw20::Cw20ReceiveMsg;
 use cw721::Expiration;
 
 use crate::{
-    order_state::{OrderComponents, NFT},
-    state::{AuctionConfig, Listing},
+    order_state::{NftTrait, OrderComponents, PaymentAsset, TraitNFT, NFT},
+    state::{
+        AuctionConfig, AuctionContract, AuctionState, Bundle, CollectionStatus, Listing,
+        RegisteredCollection, RegistryMode, Sale,
+    },
 };
 
 #[cw_serde]
@@ -14,26 +18,77 @@ pub struct InstantiateMsg {
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    // List a NFT for sale
+    // List a NFT for sale, the NFT is transferred to this contract if escrow is true
+    // if reserved_buyers is not empty, only those addresses can buy the NFT
     ListNft {
         contract_address: String,
         token_id: String,
         auction_config: AuctionConfig,
+        #[serde(default)]
+        escrow: bool,
+        #[serde(default)]
+        reserved_buyers: Vec<String>,
     },
     // Buy a listed NFT
     Buy {
         contract_address: String,
         token_id: String,
     },
+    // List many NFTs for sale at once
+    ListNfts {
+        items: Vec<ListNftItem>,
+    },
+    // Buy many listed NFTs at once, the funds must cover the sum of the prices
+    BuyNfts {
+        items: Vec<BuyNftItem>,
+    },
+    // Buy many listed NFTs at once, the listings which cannot be bought are skipped
+    // and the unused funds are refunded
+    SweepNfts {
+        items: Vec<BuyNftItem>,
+    },
+    // Buy a listed NFT for the buyer of a sweep, only called by this contract
+    SweepItem {
+        buyer: String,
+        contract_address: String,
+        token_id: String,
+    },
+    // Refund the unused funds of a sweep, only called by this contract
+    FinalizeSweep {},
+    // Remove the listings which cannot be bought anymore, anyone can prune listings
+    // a listing is only pruned when the nft contract answers that the nft is not owned or approved
+    PruneListings {
+        listings: Vec<BuyNftItem>,
+    },
+    // List many NFTs to be sold together for one price, only native tokens are supported
+    ListBundle {
+        nfts: Vec<BuyNftItem>,
+        price: Coin,
+        end_time: Option<Expiration>,
+    },
+    // Buy all NFTs of a bundle
+    BuyBundle {
+        bundle_id: u64,
+    },
+    // Cancel a bundle, anyone can cancel a bundle which cannot be bought anymore
+    CancelBundle {
+        bundle_id: u64,
+    },
     // Cancel a listed NFT
     Cancel {
         contract_address: String,
         token_id: String,
     },
-    // Offer a Nft
+    // Offer a Nft, or any Nft of a collection if the token_id is None
+    // the offered amount changes linearly from funds_amount at start_time
+    // to end_funds_amount at end_time if end_funds_amount is set
     OfferNft {
         nft: NFT,
         funds_amount: u128,
+        #[serde(default)]
+        end_funds_amount: Option<u128>,
+        #[serde(default)]
+        start_time: Option<Expiration>,
         end_time: Expiration,
     },
     // Accept a Nft offer
@@ -42,14 +97,192 @@ pub enum ExecuteMsg {
         nft: NFT,
         funds_amount: u128,
     },
+    // Offer any Nft of a collection having all the traits
+    OfferTraitNft {
+        nft: TraitNFT,
+        funds_amount: u128,
+        end_time: Expiration,
+    },
+    // Offer Nfts, and optionally vaura token, in exchange for a Nft
+    OfferSwap {
+        nft: NFT,
+        offered_nfts: Vec<NFT>,
+        #[serde(default)]
+        funds_amount: u128,
+        end_time: Expiration,
+    },
+    // Accept a swap offer, the offered Nfts and funds must match the offer
+    AcceptSwapOffer {
+        offerer: String,
+        nft: NFT,
+        offered_nfts: Vec<NFT>,
+        #[serde(default)]
+        funds_amount: u128,
+    },
+    // Accept a collection offer by selling a Nft of the collection
+    AcceptCollectionOffer {
+        offerer: String,
+        nft: NFT,
+        funds_amount: u128,
+    },
+    // Accept a trait offer by selling a Nft having all the traits of the offer
+    AcceptTraitOffer {
+        offerer: String,
+        nft: TraitNFT,
+        token_id: String,
+        funds_amount: u128,
+    },
+    // Fulfill an order signed by its maker with a secp256k1 key, the order is not stored on chain
+    // a listing is bought with the attached funds, an offer is accepted by the owner of the Nft
+    FulfillSignedOrder {
+        order: OrderComponents,
+        signature: Binary,
+        public_key: Binary,
+    },
+    // Cancel all listings, bundles, offers and signed orders of the sender
+    IncrementCounter {},
     // Cancel offer of User
     CancelOffer {
         nfts: Vec<NFT>,
     },
+    // Cancel the swap offers of User for the wanted Nfts
+    CancelSwapOffer {
+        nfts: Vec<NFT>,
+    },
+    // Cancel a trait offer of User
+    CancelTraitOffer {
+        contract_address: String,
+        traits: Vec<NftTrait>,
+    },
     // edit contract address of vaura token
     EditVauraToken {
         token_address: String,
     },
+    // Buy a listed NFT priced in cw20 token, sent by the cw20 contract
+    Receive(Cw20ReceiveMsg),
+    // whitelist a cw20 token to price listings
+    AddPaymentToken {
+        token_address: String,
+    },
+    // remove a cw20 token from the whitelist
+    RemovePaymentToken {
+        token_address: String,
+    },
+    // update the protocol fee, the fee collector and the registry mode,
+    // the missing fields are unchanged, the protocol fee cannot be greater than 1000 bps (10%)
+    UpdateConfig {
+        protocol_fee_bps: Option<u16>,
+        fee_collector: Option<String>,
+        #[serde(default)]
+        registry_mode: Option<RegistryMode>,
+    },
+    // save again a page of the listings, then of the offers, stored before an upgrade,
+    // so that they are added to the indexes of this version, only the owner can rebuild the indexes,
+    // the next page starts after the next cursor returned in the data of the response
+    RebuildIndexes {
+        start_after: Option<RebuildIndexesCursor>,
+        limit: Option<u32>,
+    },
+    // allow or block a collection, or update its metadata
+    RegisterCollection {
+        contract_address: String,
+        name: String,
+        description: Option<String>,
+        status: CollectionStatus,
+    },
+    // remove a collection from the registry
+    UnregisterCollection {
+        contract_address: String,
+    },
+    // register the auction contract of an auction type, or update its code id after a migration
+    AddAuctionContract {
+        contract_address: String,
+        code_id: u32,
+        name: String,
+    },
+    // remove an auction contract, its auction type cannot be listed anymore
+    RemoveAuctionContract {
+        contract_address: String,
+    },
+    // List a NFT in the auction contract registered for the auction type,
+    // the NFT is transferred to the auction contract which validates the config
+    ListNftInAuction {
+        contract_address: String,
+        token_id: String,
+        auction_type: String,
+        auction_config: Binary,
+    },
+    // Remove the listing of a NFT auctioned by a registered auction contract,
+    // only the auction contract can execute it when it creates or settles the auction
+    RemoveAuctionedListing {
+        contract_address: String,
+        token_id: String,
+    },
+}
+
+// the message executed on an auction contract to create the auction of a listed NFT,
+// code_id is the code id registered for the auction contract when the listing is created
+#[cw_serde]
+pub enum AuctionContractExecuteMsg {
+    CreateAuction {
+        seller: String,
+        nft: NFT,
+        auction_config: Binary,
+        code_id: u32,
+    },
+}
+
+// the query of the state of an auction in an auction contract, it returns Option<AuctionState>
+#[cw_serde]
+pub enum AuctionContractQueryMsg {
+    AuctionState {
+        contract_address: String,
+        token_id: String,
+    },
+}
+
+#[cw_serde]
+pub struct ListNftItem {
+    pub contract_address: String,
+    pub token_id: String,
+    pub auction_config: AuctionConfig,
+    #[serde(default)]
+    pub escrow: bool,
+    #[serde(default)]
+    pub reserved_buyers: Vec<String>,
+}
+
+#[cw_serde]
+pub struct NftOffer {
+    pub nft: NFT,
+    pub funds_amount: u128,
+    pub end_funds_amount: Option<u128>,
+    pub start_time: Option<Expiration>,
+    pub end_time: Expiration,
+}
+
+#[cw_serde]
+pub struct SwapOffer {
+    pub nft: NFT,
+    pub offered_nfts: Vec<NFT>,
+    pub funds_amount: u128,
+    pub end_time: Expiration,
+}
+
+#[cw_serde]
+pub struct BuyNftItem {
+    pub contract_address: String,
+    pub token_id: String,
+}
+
+// messages attached to the cw20 tokens sent to this contract
+#[cw_serde]
+pub enum Cw20HookMsg {
+    // Buy a listed NFT priced in the sent cw20 token
+    Buy {
+        contract_address: String,
+        token_id: String,
+    },
 }
 
 #[cw_serde]
@@ -68,12 +301,73 @@ pub enum QueryMsg {
         start_after: Option<String>,
         limit: Option<u32>,
     },
-    // get listing by contract_address and token_id
-    #[returns(Listing)]
+    // get the listings of a collection sorted by price and matching the filter,
+    // a dutch auction is sorted and filtered by its lowest price,
+    // at most 300 listings are scanned in one page, the next page starts after the next cursor
+    #[returns(SortedListingsResponse)]
+    Listings {
+        contract_address: String,
+        #[serde(default)]
+        filter: ListingsFilter,
+        start_after: Option<ListingsCursor>,
+        limit: Option<u32>,
+    },
+    // get all listings of a seller
+    #[returns(ListingsResponse)]
+    ListingsBySeller {
+        seller: String,
+        start_after_nft: Option<NFT>,
+        limit: Option<u32>,
+    },
+    // get listing by contract_address and token_id, with the state of its auction if it is auctioned
+    #[returns(ListingResponse)]
     Listing {
         contract_address: String,
         token_id: String,
     },
+    // get the current price of a listing, the price of a dutch auction declines over time
+    #[returns(PaymentAsset)]
+    ListingPrice {
+        contract_address: String,
+        token_id: String,
+    },
+    // check if a listing can be bought, the seller must still own and approve the NFT
+    #[returns(ValidateResponse)]
+    ValidateListing {
+        contract_address: String,
+        token_id: String,
+    },
+    // get a bundle by its id
+    #[returns(Bundle)]
+    Bundle { bundle_id: u64 },
+    // get all bundles, sorted by id
+    #[returns(BundlesResponse)]
+    Bundles {
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // get a registered collection
+    #[returns(RegisteredCollection)]
+    RegisteredCollection { contract_address: String },
+    // get the registered collections, only the collections with the status if it is set
+    #[returns(RegisteredCollectionsResponse)]
+    RegisteredCollections {
+        status: Option<CollectionStatus>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // get the registered auction contracts
+    #[returns(AuctionContractsResponse)]
+    AuctionContracts {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    // get the whitelisted cw20 tokens
+    #[returns(PaymentTokensResponse)]
+    PaymentTokens {
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     // get the specific offer
     #[returns(OrderComponents)]
     Offer {
@@ -89,13 +383,112 @@ pub enum QueryMsg {
         start_after_offerer: Option<String>,
         limit: Option<u32>,
     },
-    // get all offers of a user
+    // get all swap offers for a nft
+    #[returns(OffersResponse)]
+    SwapOffers {
+        contract_address: String,
+        token_id: String,
+        start_after_offerer: Option<String>,
+        limit: Option<u32>,
+    },
+    // get all offers of a collection, sorted by the offered amount at the start time from the best one,
+    // a time-varying offer may offer more or less at the current block
+    #[returns(CollectionOffersResponse)]
+    CollectionOffers {
+        contract_address: String,
+        start_after: Option<CollectionOffersCursor>,
+        limit: Option<u32>,
+    },
+    // get all offers of a user, except the swap offers
     #[returns(OffersResponse)]
     UserOffers {
         offerer: String,
         start_after_nft: Option<NFT>,
         limit: Option<u32>,
     },
+    // get all swap offers of a user, a page is continued from the wanted nft of the last offer
+    #[returns(OffersResponse)]
+    UserSwapOffers {
+        offerer: String,
+        start_after_nft: Option<NFT>,
+        limit: Option<u32>,
+    },
+    // get the best active offer of each nft of an owner in the collections,
+    // which is the offer of the nft or the collection offering the most at the current block,
+    // at most 100 nfts and 100 of their offers are scanned in one page,
+    // the next page starts after the next nft of the response
+    #[returns(ReceivedOffersResponse)]
+    OffersReceived {
+        owner: String,
+        collections: Vec<String>,
+        start_after_nft: Option<NFT>,
+        limit: Option<u32>,
+    },
+    // get the counter of a user, stored with its listings, bundles and offers
+    #[returns(CounterResponse)]
+    Counter { user: String },
+    // get the hash of an order to be signed by its maker with the current counter
+    #[returns(Binary)]
+    SignedOrderHash { order: OrderComponents },
+    // get the latest sales of a nft, or of a collection if the token_id is None, the latest first
+    #[returns(SalesResponse)]
+    SaleHistory {
+        contract_address: String,
+        token_id: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    },
+    // get the floor prices, the number of active listings, the volume and the number of sales
+    // of a collection, at most 300 listings are scanned in one page,
+    // the next page starts after the next cursor of the response
+    #[returns(CollectionStatsResponse)]
+    CollectionStats {
+        contract_address: String,
+        #[serde(default)]
+        start_after: Option<ListingsCursor>,
+    },
+    // get the last sale of a nft, or of a collection if the token_id is None
+    #[returns(Option<Sale>)]
+    LastSale {
+        contract_address: String,
+        token_id: Option<String>,
+    },
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum SortOrder {
+    #[default]
+    Ascending,
+    Descending,
+}
+
+// the missing fields do not filter the listings
+#[cw_serde]
+#[derive(Default)]
+pub struct ListingsFilter {
+    pub seller: Option<String>,
+    pub denom: Option<String>, // the denom of a native token, or the address of a cw20 token
+    pub min_price: Option<u128>,
+    pub max_price: Option<u128>,
+    #[serde(default)]
+    pub active: bool, // only the listings which can be bought at the current block
+    #[serde(default)]
+    pub order: SortOrder, // the order of the prices
+}
+
+// the position of the last listing of a page, the listings are sorted by denom, price and token id
+#[cw_serde]
+pub struct ListingsCursor {
+    pub denom: String,
+    pub price: u128,
+    pub token_id: String,
+}
+
+#[cw_serde]
+pub struct ListingResponse {
+    pub listing: Listing,
+    pub auction: Option<AuctionState>, // the state of an auctioned nft until its auction is settled
 }
 
 #[cw_serde]
@@ -103,12 +496,101 @@ pub struct ListingsResponse {
     pub listings: Vec<Listing>,
 }
 
+#[cw_serde]
+pub struct SortedListingsResponse {
+    pub listings: Vec<Listing>,
+    pub next: Option<ListingsCursor>, // the cursor of the last scanned listing, none if all listings are scanned
+}
+
+#[cw_serde]
+pub struct BundlesResponse {
+    pub bundles: Vec<Bundle>,
+}
+
+#[cw_serde]
+pub struct RegisteredCollectionsResponse {
+    pub collections: Vec<RegisteredCollection>,
+}
+
+#[cw_serde]
+pub struct AuctionContractsResponse {
+    pub auction_contracts: Vec<AuctionContract>,
+}
+
+#[cw_serde]
+pub struct PaymentTokensResponse {
+    pub tokens: Vec<Addr>,
+}
+
+// the last listing or offer whose indexes are rebuilt
+#[cw_serde]
+pub enum RebuildIndexesCursor {
+    Listing {
+        contract_address: String,
+        token_id: String,
+    },
+    Offer {
+        offerer: String,
+        contract_address: String,
+        token_id: String,
+    },
+}
+
+#[cw_serde]
+pub struct RebuildIndexesResponse {
+    pub next: Option<RebuildIndexesCursor>, // none if all listings and offers are rebuilt
+}
+
 #[cw_serde]
 pub struct ValidateResponse {
     pub valid: bool,
 }
 
+#[cw_serde]
+pub struct SalesResponse {
+    pub sales: Vec<Sale>,
+}
+
+#[cw_serde]
+pub struct CollectionStatsResponse {
+    pub floor_prices: Vec<PaymentAsset>, // the lowest current price of each denom in the page
+    pub listing_count: u64,              // the number of active listings in the page
+    pub volume: Vec<PaymentAsset>,
+    pub sale_count: u64,
+    pub next: Option<ListingsCursor>, // the cursor of the last scanned listing, none if all listings are scanned
+}
+
+#[cw_serde]
+pub struct CounterResponse {
+    pub counter: u64,
+}
+
 #[cw_serde]
 pub struct OffersResponse {
     pub offers: Vec<OrderComponents>,
 }
+
+// the position of the last offer of a page, the collection offers are sorted by start amount and offerer
+#[cw_serde]
+pub struct CollectionOffersCursor {
+    pub amount: u128,
+    pub offerer: String,
+}
+
+#[cw_serde]
+pub struct CollectionOffersResponse {
+    pub offers: Vec<OrderComponents>,
+    pub next: Option<CollectionOffersCursor>, // the cursor of the last offer, none if all offers are returned
+}
+
+#[cw_serde]
+pub struct ReceivedOffer {
+    pub nft: NFT,
+    pub offer: OrderComponents,
+}
+
+#[cw_serde]
+pub struct ReceivedOffersResponse {
+    pub offers: Vec<ReceivedOffer>,
+    pub next: Option<NFT>, // the nft to continue the query after, none if all nfts are scanned
+}
@@ -1,179 +1,117 @@
 use cosmwasm_schema::cw_serde;
 use cosmwasm_std::Addr;
-use cw721::Expiration;
 use cw_storage_plus::{Index, IndexList, IndexedMap, MultiIndex};
+use sha2::{Digest, Sha256};
+
+// the assets and the order keys are shared with the auction contract
+pub use marketplace_types::asset::{
+    order_key, Asset, NftTrait, OrderKey, PaymentAsset, TraitNFT, CW20, NATIVE, NFT,
+};
+// the orders are shared with the other marketplace contracts
+pub use marketplace_types::order::{
+    consideration_item, offer_item, ConsiderationItem, ItemType, OfferItem, OrderComponents,
+    OrderType, Side, User,
+};
 
 pub type Nft = (Addr, String);
-pub type User = Addr;
 
-#[cw_serde]
-pub enum OrderType {
-    OFFER,
-    LISTING,
-}
-
-#[cw_serde]
-pub struct NFT {
-    pub contract_address: Addr,
-    pub token_id: Option<String>,
-}
-
-#[cw_serde]
-pub struct CW20 {
-    pub contract_address: Addr,
-    pub amount: u128,
-}
-
-#[cw_serde]
-pub struct NATIVE {
-    pub denom: String,
-    pub amount: u128,
-}
-
-#[cw_serde]
-pub enum Asset {
-    Nft(NFT),
-    Native(NATIVE),
-    Cw20(CW20),
-}
-
-#[cw_serde]
-pub enum PaymentAsset {
-    Native {
-        denom: String,
-        amount: u128,
-    },
-    Cw20 {
-        contract_address: Addr,
-        amount: u128,
-    },
-}
-
-impl From<Asset> for PaymentAsset {
-    fn from(asset: Asset) -> Self {
-        match asset {
-            Asset::Native(NATIVE { denom, amount }) => PaymentAsset::Native { denom, amount },
-            Asset::Cw20(CW20 {
-                contract_address,
-                amount,
-            }) => PaymentAsset::Cw20 {
-                contract_address,
-                amount,
-            },
-            _ => panic!("Asset is not a payment asset"),
+// a collection offer is not bound to a token, so it is stored with an empty token id
+pub const COLLECTION_OFFER_TOKEN_ID: &str = "";
+
+// a trait offer is stored with a token id built from its traits,
+// so that a user can make an offer for each set of traits of a collection
+// the traits are sorted, so that the same set of traits has the same token id,
+// and hashed with the length of each part, so that two sets of traits cannot have the same token id
+pub fn trait_offer_token_id(traits: &[NftTrait]) -> String {
+    let mut traits = traits.to_vec();
+    traits.sort();
+    traits.dedup();
+
+    let mut hasher = Sha256::new();
+    for t in traits.iter() {
+        for part in [&t.trait_type, &t.value] {
+            hasher.update((part.len() as u64).to_be_bytes());
+            hasher.update(part.as_bytes());
         }
     }
+    let hash: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    format!("traits:{}", hash)
 }
 
+// the payload signed by the maker of an order which is not stored in this contract
+// the signature is only valid for this marketplace on this chain
 #[cw_serde]
-pub enum Side {
-    OFFER,
-    CONSIDERATION,
-}
-
-#[cw_serde]
-pub enum ItemType {
-    NATIVE,
-    CW20,
-    CW721,
-}
-
-#[cw_serde]
-pub struct OfferItem {
-    pub item_type: ItemType,
-    pub item: Asset,
-    pub start_amount: u128,
-    pub end_amount: u128,
-}
-
-pub fn offer_item(
-    item_type: &ItemType,
-    item: &Asset,
-    start_amount: &u128,
-    end_amount: &u128,
-) -> OfferItem {
-    OfferItem {
-        item_type: item_type.clone(),
-        item: item.clone(),
-        start_amount: *start_amount,
-        end_amount: *end_amount,
-    }
-}
-
-#[cw_serde]
-pub struct ConsiderationItem {
-    pub item_type: ItemType,
-    pub item: Asset,
-    pub start_amount: u128,
-    pub end_amount: u128,
-    pub recipient: Addr,
-}
-
-pub fn consideration_item(
-    item_type: &ItemType,
-    item: &Asset,
-    start_amount: &u128,
-    end_amount: &u128,
-    recipient: &Addr,
-) -> ConsiderationItem {
-    ConsiderationItem {
-        item_type: item_type.clone(),
-        item: item.clone(),
-        start_amount: *start_amount,
-        end_amount: *end_amount,
-        recipient: recipient.clone(),
-    }
-}
-
-// the OrderKey includes the address and id of NFT
-// !DO NOT change the order of the fields
-pub type OrderKey = (Addr, Addr, String);
-
-pub fn order_key(user_address: &Addr, contract_address: &Addr, token_id: &str) -> OrderKey {
-    (
-        user_address.clone(),
-        contract_address.clone(),
-        token_id.to_owned(),
-    )
-}
-
-#[cw_serde]
-pub struct OrderComponents {
-    pub order_type: OrderType,
-    pub order_id: OrderKey,
-    pub offerer: User,
-    pub offer: Vec<OfferItem>,
-    pub consideration: Vec<ConsiderationItem>,
-    pub start_time: Option<Expiration>,
-    pub end_time: Option<Expiration>,
+pub struct SignedOrderPayload {
+    pub chain_id: String,
+    pub marketplace: Addr,
+    pub order: OrderComponents,
 }
 
 pub struct OfferIndexes<'a> {
     pub users: MultiIndex<'a, User, OrderComponents, OrderKey>,
     pub nfts: MultiIndex<'a, (Addr, String), OrderComponents, OrderKey>,
+    // offers of a nft (or of a collection, with the empty token id) sorted by the offered amount
+    pub prices: MultiIndex<'a, (Addr, String, u128), OrderComponents, OrderKey>,
 }
 
 impl<'a> IndexList<OrderComponents> for OfferIndexes<'a> {
     // this method returns a list of all indexes
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<OrderComponents>> + '_> {
-        let v: Vec<&dyn Index<OrderComponents>> = vec![&self.users, &self.nfts];
+        let v: Vec<&dyn Index<OrderComponents>> = vec![&self.users, &self.nfts, &self.prices];
         Box::new(v.into_iter())
     }
 }
 
-// helper function create a IndexedMap for listings
+// helper function create a IndexedMap for offers
 pub fn orders<'a>() -> IndexedMap<'a, OrderKey, OrderComponents, OfferIndexes<'a>> {
+    offers_map(
+        "orders",
+        "orders__user_address",
+        "orders__nft_identifier",
+        "orders__nft_price",
+    )
+}
+
+// helper function create a IndexedMap for swap offers
+// a swap offer is stored with the token id of the wanted nft in its own namespace,
+// so that it does not override an offer of funds for the same nft
+pub fn swap_orders<'a>() -> IndexedMap<'a, OrderKey, OrderComponents, OfferIndexes<'a>> {
+    offers_map(
+        "swap_orders",
+        "swap_orders__user_address",
+        "swap_orders__nft_identifier",
+        "swap_orders__nft_price",
+    )
+}
+
+fn offers_map<'a>(
+    namespace: &'a str,
+    users_namespace: &'a str,
+    nfts_namespace: &'a str,
+    prices_namespace: &'a str,
+) -> IndexedMap<'a, OrderKey, OrderComponents, OfferIndexes<'a>> {
     let indexes = OfferIndexes {
         users: MultiIndex::new(
-            |_pk: &[u8], l: &OrderComponents| (l.order_id.0.clone()),
-            "orders",
-            "orders__user_address",
+            |_pk: &[u8], l: &OrderComponents| l.order_id.0.clone(),
+            namespace,
+            users_namespace,
         ),
         nfts: MultiIndex::new(
             |_pk: &[u8], l: &OrderComponents| (l.order_id.1.clone(), l.order_id.2.clone()),
-            "orders",
-            "orders__nft_identifier",
+            namespace,
+            nfts_namespace,
+        ),
+        prices: MultiIndex::new(
+            |_pk: &[u8], l: &OrderComponents| {
+                (l.order_id.1.clone(), l.order_id.2.clone(), l.offer_amount())
+            },
+            namespace,
+            prices_namespace,
         ),
     };
-    IndexedMap::new("orders", indexes)
+    IndexedMap::new(namespace, indexes)
 }
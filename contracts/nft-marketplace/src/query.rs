@@ -1,10 +1,24 @@
-use cosmwasm_std::{Addr, Deps, Order, StdResult};
-use cw_storage_plus::Bound;
+use cosmwasm_std::{to_binary, Addr, Binary, Deps, Env, Order, StdError, StdResult, Storage};
+use cw721::Cw721QueryMsg;
+use cw_storage_plus::{Bound, IndexedMap, PrimaryKey};
+use sha2::{Digest, Sha256};
 
 use crate::{
-    msg::{ListingsResponse, OffersResponse},
-    order_state::{order_key, OrderComponents, OrderKey, NFT},
-    state::{listing_key, Listing, ListingKey, MarketplaceContract},
+    msg::{
+        AuctionContractQueryMsg, AuctionContractsResponse, BundlesResponse, CollectionOffersCursor,
+        CollectionOffersResponse, CollectionStatsResponse, CounterResponse, ListingResponse,
+        ListingsCursor, ListingsFilter, ListingsResponse, OffersResponse, PaymentTokensResponse,
+        ReceivedOffer, ReceivedOffersResponse, RegisteredCollectionsResponse, SalesResponse,
+        SortOrder, SortedListingsResponse, ValidateResponse,
+    },
+    order_state::{
+        order_key, OfferIndexes, OrderComponents, OrderKey, PaymentAsset, SignedOrderPayload,
+        COLLECTION_OFFER_TOKEN_ID, NFT,
+    },
+    state::{
+        listing_key, AuctionState, Bundle, CollectionStatus, Listing, ListingKey,
+        MarketplaceContract, Sale, MAX_SCANNED_LISTINGS, MAX_SCANNED_NFTS, MAX_SCANNED_OFFERS,
+    },
 };
 
 impl MarketplaceContract<'static> {
@@ -13,9 +27,206 @@ impl MarketplaceContract<'static> {
         deps: Deps,
         contract_address: Addr,
         token_id: String,
-    ) -> StdResult<Listing> {
+    ) -> StdResult<ListingResponse> {
         let listing_key = listing_key(&contract_address, &token_id);
-        self.listings.load(deps.storage, listing_key)
+        let listing = self.listings.load(deps.storage, listing_key)?;
+
+        // a cancelled listing is not returned
+        if self.is_cancelled_listing(deps.storage, &listing) {
+            return Err(StdError::not_found("Listing"));
+        }
+
+        // an auctioned listing is returned with the state of its auction until it is settled
+        if listing.auction_config.auction_type().is_some() {
+            let auction = self.auction_state(deps, &listing)?;
+            if auction.is_none() {
+                return Err(StdError::not_found("Listing"));
+            }
+            return Ok(ListingResponse { listing, auction });
+        }
+        Ok(ListingResponse {
+            listing,
+            auction: None,
+        })
+    }
+
+    // the state of the auction of an auctioned listing in the auction contract of its type,
+    // none if the listing is not auctioned or its auction is settled
+    pub fn auction_state(&self, deps: Deps, listing: &Listing) -> StdResult<Option<AuctionState>> {
+        let auction_type = match listing.auction_config.auction_type() {
+            Some(auction_type) => auction_type,
+            None => return Ok(None),
+        };
+        let auction_contract = match self
+            .auction_contracts
+            .idx
+            .name
+            .item(deps.storage, auction_type.to_string())?
+        {
+            Some((_, auction_contract)) => auction_contract,
+            None => return Ok(None),
+        };
+
+        let auction_state: Option<AuctionState> = deps.querier.query_wasm_smart(
+            auction_contract.contract_address,
+            &AuctionContractQueryMsg::AuctionState {
+                contract_address: listing.contract_address.to_string(),
+                token_id: listing.token_id.clone(),
+            },
+        )?;
+
+        // the nft may have been auctioned again since the listing was settled
+        Ok(auction_state.filter(|auction_state| auction_state.seller == listing.seller))
+    }
+
+    // the registered auction contract holding an active auction of a nft, none if the nft is not auctioned,
+    // an auction contract which cannot answer is ignored so that it cannot block the marketplace
+    pub fn auctioning_contract(
+        &self,
+        deps: Deps,
+        contract_address: &Addr,
+        token_id: &str,
+    ) -> StdResult<Option<Addr>> {
+        for auction_contract in
+            self.auction_contracts
+                .range(deps.storage, None, None, Order::Ascending)
+        {
+            let (auction_contract_address, _) = auction_contract?;
+            let auction_state: StdResult<Option<AuctionState>> = deps.querier.query_wasm_smart(
+                &auction_contract_address,
+                &AuctionContractQueryMsg::AuctionState {
+                    contract_address: contract_address.to_string(),
+                    token_id: token_id.to_string(),
+                },
+            );
+            if let Ok(Some(_)) = auction_state {
+                return Ok(Some(auction_contract_address));
+            }
+        }
+        Ok(None)
+    }
+
+    // query the price of a listing at the current block
+    pub fn query_listing_price(
+        self,
+        deps: Deps,
+        env: Env,
+        contract_address: Addr,
+        token_id: String,
+    ) -> StdResult<PaymentAsset> {
+        let listing_key = listing_key(&contract_address, &token_id);
+        let listing = self.listings.load(deps.storage, listing_key)?;
+        Ok(listing.current_price(&env.block))
+    }
+
+    // query if a listing can still be bought
+    pub fn query_validate_listing(
+        self,
+        deps: Deps,
+        env: Env,
+        contract_address: Addr,
+        token_id: String,
+    ) -> StdResult<ValidateResponse> {
+        let listing_key = listing_key(&contract_address, &token_id);
+        let valid = match self.listings.may_load(deps.storage, listing_key)? {
+            Some(listing) => self.is_valid_listing(deps, &env, &listing),
+            None => false,
+        };
+        Ok(ValidateResponse { valid })
+    }
+
+    // a listing is valid only if it is not expired or cancelled and the seller still owns the nft
+    // and approves this contract to transfer it, or the nft is escrowed by this contract
+    // an auctioned listing is valid until its auction is settled
+    // a listing is only invalid when the contracts answer so, a failing query keeps it valid
+    pub fn is_valid_listing(&self, deps: Deps, env: &Env, listing: &Listing) -> bool {
+        if listing.auction_config.auction_type().is_some() {
+            return !self.is_cancelled_listing(deps.storage, listing)
+                && self
+                    .auction_state(deps, listing)
+                    .map_or(true, |auction| auction.is_some());
+        }
+        if listing.is_expired(&env.block) || self.is_cancelled_listing(deps.storage, listing) {
+            return false;
+        }
+
+        // an escrowed nft is held by this contract
+        let expected_owner = if listing.escrow {
+            &env.contract.address
+        } else {
+            &listing.seller
+        };
+        self.is_owned_and_approved(
+            deps,
+            env,
+            expected_owner,
+            &listing.contract_address,
+            &listing.token_id,
+        )
+    }
+
+    // a bundle is valid only if it is not expired or cancelled and the seller still owns
+    // all its nfts and approves this contract to transfer them
+    pub fn is_valid_bundle(&self, deps: Deps, env: &Env, bundle: &Bundle) -> bool {
+        if let Some(end_time) = bundle.end_time {
+            if end_time.is_expired(&env.block) {
+                return false;
+            }
+        }
+        if self.counter(deps.storage, &bundle.seller).ok() != Some(bundle.counter) {
+            return false;
+        }
+        bundle.nfts.iter().all(|item| {
+            self.is_owned_and_approved(
+                deps,
+                env,
+                &bundle.seller,
+                &item.contract_address,
+                &item.token_id,
+            )
+        })
+    }
+
+    // the owner holds the nft, and approves this contract to transfer it unless it is this contract
+    // it is false only if the nft contract answers that the nft is not owned or not approved
+    fn is_owned_and_approved(
+        &self,
+        deps: Deps,
+        env: &Env,
+        owner: &Addr,
+        contract_address: &Addr,
+        token_id: &str,
+    ) -> bool {
+        let owner_response: StdResult<cw721::OwnerOfResponse> = deps.querier.query_wasm_smart(
+            contract_address,
+            &Cw721QueryMsg::OwnerOf {
+                token_id: token_id.to_string(),
+                include_expired: Some(false),
+            },
+        );
+        match owner_response {
+            Ok(owner_response) if owner_response.owner != *owner => return false,
+            Err(err) if is_not_found(&err) => return false,
+            Err(_) => return true,
+            Ok(_) => {}
+        }
+        if *owner == env.contract.address {
+            return true;
+        }
+
+        // the approval is cleared when the nft is transferred or the approval is revoked
+        let approval_response: StdResult<cw721::ApprovalResponse> = deps.querier.query_wasm_smart(
+            contract_address,
+            &Cw721QueryMsg::Approval {
+                token_id: token_id.to_string(),
+                spender: env.contract.address.to_string(),
+                include_expired: Some(false),
+            },
+        );
+        match approval_response {
+            Err(err) => !is_not_found(&err),
+            Ok(_) => true,
+        }
     }
 
     pub fn query_listings_by_contract_address(
@@ -35,11 +246,260 @@ impl MarketplaceContract<'static> {
             .prefix(contract_address)
             .range(deps.storage, start, None, Order::Ascending)
             .map(|item| item.map(|(_, listing)| listing))
+            .filter(|item| match item {
+                Ok(listing) => !self.is_cancelled_listing(deps.storage, listing),
+                Err(_) => true,
+            })
             .take(limit)
             .collect::<StdResult<Vec<_>>>()?;
         Ok(ListingsResponse { listings })
     }
 
+    // query the listings of a collection sorted by price, the cancelled listings are skipped
+    pub fn query_listings(
+        self,
+        deps: Deps,
+        env: Env,
+        contract_address: Addr,
+        filter: ListingsFilter,
+        start_after: Option<ListingsCursor>,
+        limit: Option<u32>,
+    ) -> StdResult<SortedListingsResponse> {
+        let limit = limit.unwrap_or(30).min(30) as usize;
+
+        // the listings are indexed by denom, lowest price and listing key
+        let cursor = start_after.map(|cursor| {
+            (
+                (cursor.denom, cursor.price),
+                listing_key(&contract_address, &cursor.token_id),
+            )
+                .joined_key()
+        });
+
+        // the listings of a denom are bounded by the price filters,
+        // the keys of a denom end before its length prefixed bytes followed by 0xff
+        let (filter_min, filter_max) = match filter.denom.as_deref() {
+            Some(denom) => (
+                Some((denom, filter.min_price.unwrap_or(0)).joined_extra_key(&[])),
+                Some(
+                    match filter
+                        .max_price
+                        .and_then(|max_price| max_price.checked_add(1))
+                    {
+                        Some(max_price) => (denom, max_price).joined_extra_key(&[]),
+                        None => denom.joined_extra_key(&[0xff]),
+                    },
+                ),
+            ),
+            None => (None, None),
+        };
+
+        // the page starts after the cursor, unless the cursor is out of the bounds of the filters
+        let (min, max, order) = match filter.order {
+            SortOrder::Ascending => {
+                let min = match (cursor, filter_min) {
+                    (Some(cursor), Some(filter_min)) if cursor < filter_min => {
+                        Some(Bound::InclusiveRaw(filter_min))
+                    }
+                    (Some(cursor), _) => Some(Bound::ExclusiveRaw(cursor)),
+                    (None, filter_min) => filter_min.map(Bound::InclusiveRaw),
+                };
+                (min, filter_max.map(Bound::ExclusiveRaw), Order::Ascending)
+            }
+            SortOrder::Descending => {
+                let max = match (cursor, filter_max) {
+                    (Some(cursor), Some(filter_max)) if cursor >= filter_max => Some(filter_max),
+                    (Some(cursor), _) => Some(cursor),
+                    (None, filter_max) => filter_max,
+                };
+                (
+                    filter_min.map(Bound::InclusiveRaw),
+                    max.map(Bound::ExclusiveRaw),
+                    Order::Descending,
+                )
+            }
+        };
+
+        // the prices of the listings of all denoms are only bounded without a denom filter
+        let matches = |listing: &Listing| {
+            let price = listing.lowest_price();
+            filter
+                .seller
+                .as_ref()
+                .map_or(true, |seller| listing.seller == *seller)
+                && (filter.denom.is_some()
+                    || (filter
+                        .min_price
+                        .map_or(true, |min_price| price.amount() >= min_price)
+                        && filter
+                            .max_price
+                            .map_or(true, |max_price| price.amount() <= max_price)))
+                && (!filter.active
+                    || (listing.is_started(&env.block) && !listing.is_expired(&env.block)))
+                && !self.is_cancelled_listing(deps.storage, listing)
+        };
+
+        // a page scans at most MAX_SCANNED_LISTINGS listings, so a selective filter may return less
+        // listings than the limit
+        let mut listings = vec![];
+        let mut scanned_listings = 0;
+        let mut last_listing = None;
+        for item in self
+            .listings
+            .idx
+            .prices
+            .sub_prefix(contract_address.clone())
+            .range(deps.storage, min, max, order)
+            .take(MAX_SCANNED_LISTINGS)
+        {
+            let (_, listing) = item?;
+            scanned_listings += 1;
+            if matches(&listing) {
+                listings.push(listing.clone());
+            }
+            last_listing = Some(listing);
+            if listings.len() == limit {
+                break;
+            }
+        }
+
+        // the next page continues after the last scanned listing
+        let next = match last_listing {
+            Some(listing)
+                if listings.len() == limit || scanned_listings == MAX_SCANNED_LISTINGS =>
+            {
+                let price = listing.lowest_price();
+                Some(ListingsCursor {
+                    denom: price.denom(),
+                    price: price.amount(),
+                    token_id: listing.token_id,
+                })
+            }
+            _ => None,
+        };
+
+        Ok(SortedListingsResponse { listings, next })
+    }
+
+    // query all listings of a specific seller
+    pub fn query_listings_by_seller(
+        self,
+        deps: Deps,
+        seller: Addr,
+        start_after_nft: Option<NFT>,
+        limit: Option<u32>,
+    ) -> StdResult<ListingsResponse> {
+        let limit = limit.unwrap_or(30).min(30) as usize;
+
+        // the listings are returned in descending order, so the page ends before the nft
+        let end: Option<Bound<ListingKey>> = start_after_nft.map(|nft| {
+            let listing_key = listing_key(&nft.contract_address, &nft.token_id.unwrap_or_default());
+            Bound::exclusive(listing_key)
+        });
+
+        // load listings
+        let listings = self
+            .listings
+            .idx
+            .sellers
+            .prefix(seller)
+            .range(deps.storage, None, end, Order::Descending)
+            .map(|item| item.map(|(_, listing)| listing))
+            .filter(|item| match item {
+                Ok(listing) => !self.is_cancelled_listing(deps.storage, listing),
+                Err(_) => true,
+            })
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+
+        // return listings
+        Ok(ListingsResponse { listings })
+    }
+
+    // query the collections registered by the owner, sorted by address
+    pub fn query_registered_collections(
+        self,
+        deps: Deps,
+        status: Option<CollectionStatus>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<RegisteredCollectionsResponse> {
+        let limit = limit.unwrap_or(30).min(30) as usize;
+        let start: Option<Bound<Addr>> =
+            start_after.map(|contract_address| Bound::exclusive(Addr::unchecked(contract_address)));
+        let collections = self
+            .collections
+            .range(deps.storage, start, None, Order::Ascending)
+            .map(|item| item.map(|(_, collection)| collection))
+            .filter(|item| match item {
+                Ok(collection) => status
+                    .as_ref()
+                    .map_or(true, |status| collection.status == *status),
+                Err(_) => true,
+            })
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(RegisteredCollectionsResponse { collections })
+    }
+
+    // query the registered auction contracts
+    pub fn query_auction_contracts(
+        self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<AuctionContractsResponse> {
+        let limit = limit.unwrap_or(30).min(30) as usize;
+        let start: Option<Bound<Addr>> =
+            start_after.map(|contract_address| Bound::exclusive(Addr::unchecked(contract_address)));
+        let auction_contracts = self
+            .auction_contracts
+            .range(deps.storage, start, None, Order::Ascending)
+            .map(|item| item.map(|(_, auction_contract)| auction_contract))
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(AuctionContractsResponse { auction_contracts })
+    }
+
+    // query the cw20 tokens which can be used to price listings
+    pub fn query_payment_tokens(
+        self,
+        deps: Deps,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<PaymentTokensResponse> {
+        let limit = limit.unwrap_or(30).min(30) as usize;
+        let start: Option<Bound<Addr>> =
+            start_after.map(|token_address| Bound::exclusive(Addr::unchecked(token_address)));
+        let tokens = self
+            .payment_tokens
+            .keys(deps.storage, start, None, Order::Ascending)
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(PaymentTokensResponse { tokens })
+    }
+
+    pub fn query_bundles(
+        self,
+        deps: Deps,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<BundlesResponse> {
+        let limit = limit.unwrap_or(30).min(30) as usize;
+        let start = start_after.map(Bound::exclusive);
+        let bundles = self
+            .bundles
+            .range(deps.storage, start, None, Order::Ascending)
+            .map(|item| item.map(|(_, bundle)| bundle))
+            .filter(|item| match item {
+                Ok(bundle) => self.counter(deps.storage, &bundle.seller) == Ok(bundle.counter),
+                Err(_) => true,
+            })
+            .take(limit)
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(BundlesResponse { bundles })
+    }
+
     // query information of a specific offer
     pub fn query_offer(
         self,
@@ -49,7 +509,13 @@ impl MarketplaceContract<'static> {
         offerer: Addr,
     ) -> StdResult<OrderComponents> {
         let order_key = order_key(&offerer, &contract_address, &token_id);
-        self.offers.load(deps.storage, order_key)
+        let offer = self.offers.load(deps.storage, order_key)?;
+
+        // a cancelled offer is not returned
+        if self.is_cancelled_offer(deps.storage, &offer) {
+            return Err(StdError::not_found("Offer"));
+        }
+        Ok(offer)
     }
 
     // query all offers of a specific nft
@@ -60,6 +526,44 @@ impl MarketplaceContract<'static> {
         token_id: String,
         start_after_offerer: Option<String>,
         limit: Option<u32>,
+    ) -> StdResult<OffersResponse> {
+        self.nft_offers(
+            &self.offers,
+            deps,
+            contract_address,
+            token_id,
+            start_after_offerer,
+            limit,
+        )
+    }
+
+    // query all swap offers for a specific nft
+    pub fn query_swap_offers(
+        self,
+        deps: Deps,
+        contract_address: Addr,
+        token_id: String,
+        start_after_offerer: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<OffersResponse> {
+        self.nft_offers(
+            &self.swap_offers,
+            deps,
+            contract_address,
+            token_id,
+            start_after_offerer,
+            limit,
+        )
+    }
+
+    fn nft_offers(
+        &self,
+        offers: &IndexedMap<'static, OrderKey, OrderComponents, OfferIndexes<'static>>,
+        deps: Deps,
+        contract_address: Addr,
+        token_id: String,
+        start_after_offerer: Option<String>,
+        limit: Option<u32>,
     ) -> StdResult<OffersResponse> {
         let limit = limit.unwrap_or(30).min(30) as usize;
 
@@ -73,13 +577,16 @@ impl MarketplaceContract<'static> {
         });
 
         // load offers
-        let offers = self
-            .offers
+        let offers = offers
             .idx
             .nfts
             .prefix((contract_address, token_id))
             .range(deps.storage, start, None, Order::Descending)
             .map(|item| item.map(|(_, order)| order))
+            .filter(|item| match item {
+                Ok(order) => !self.is_cancelled_offer(deps.storage, order),
+                Err(_) => true,
+            })
             .take(limit)
             .collect::<StdResult<Vec<_>>>()?;
 
@@ -87,6 +594,59 @@ impl MarketplaceContract<'static> {
         Ok(OffersResponse { offers })
     }
 
+    // query all offers of a collection, the offers are sorted by their start amount,
+    // so a time-varying offer is not ranked by its current amount
+    pub fn query_collection_offers(
+        self,
+        deps: Deps,
+        contract_address: Addr,
+        start_after: Option<CollectionOffersCursor>,
+        limit: Option<u32>,
+    ) -> StdResult<CollectionOffersResponse> {
+        let limit = limit.unwrap_or(30).min(30) as usize;
+
+        // the offers are sorted by amount, so the bound includes the amount of the cursor,
+        // the offer of the cursor may have been cancelled or replaced since the last page
+        let end: Option<Bound<(u128, OrderKey)>> = match start_after {
+            Some(cursor) => {
+                let order_key = order_key(
+                    &deps.api.addr_validate(&cursor.offerer)?,
+                    &contract_address,
+                    COLLECTION_OFFER_TOKEN_ID,
+                );
+                Some(Bound::exclusive((cursor.amount, order_key)))
+            }
+            None => None,
+        };
+
+        // load offers
+        let offers = self
+            .offers
+            .idx
+            .prices
+            .sub_prefix((contract_address, COLLECTION_OFFER_TOKEN_ID.to_string()))
+            .range(deps.storage, None, end, Order::Descending)
+            .map(|item| item.map(|(_, order)| order))
+            .filter(|item| match item {
+                Ok(order) => !self.is_cancelled_offer(deps.storage, order),
+                Err(_) => true,
+            })
+            .take(limit)
+            .collect::<StdResult<Vec<OrderComponents>>>()?;
+
+        // the next page continues after the last offer of a full page
+        let next = match offers.last() {
+            Some(offer) if offers.len() == limit => Some(CollectionOffersCursor {
+                amount: offer.offer_amount(),
+                offerer: offer.offerer.to_string(),
+            }),
+            _ => None,
+        };
+
+        // return offers
+        Ok(CollectionOffersResponse { offers, next })
+    }
+
     // query all offers of a specific user
     pub fn query_user_offers(
         self,
@@ -94,26 +654,387 @@ impl MarketplaceContract<'static> {
         offerer: Addr,
         start_after_nft: Option<NFT>,
         limit: Option<u32>,
+    ) -> StdResult<OffersResponse> {
+        self.user_offers(&self.offers, deps, offerer, start_after_nft, limit)
+    }
+
+    // query all swap offers of a specific user
+    pub fn query_user_swap_offers(
+        self,
+        deps: Deps,
+        offerer: Addr,
+        start_after_nft: Option<NFT>,
+        limit: Option<u32>,
+    ) -> StdResult<OffersResponse> {
+        self.user_offers(&self.swap_offers, deps, offerer, start_after_nft, limit)
+    }
+
+    fn user_offers(
+        &self,
+        offers: &IndexedMap<'static, OrderKey, OrderComponents, OfferIndexes<'static>>,
+        deps: Deps,
+        offerer: Addr,
+        start_after_nft: Option<NFT>,
+        limit: Option<u32>,
     ) -> StdResult<OffersResponse> {
         let limit = limit.unwrap_or(30).min(30) as usize;
 
         let start: Option<Bound<OrderKey>> = start_after_nft.map(|nft| {
-            let order_key = order_key(&offerer, &nft.contract_address, &nft.token_id.unwrap());
+            let order_key = order_key(
+                &offerer,
+                &nft.contract_address,
+                nft.token_id.as_deref().unwrap_or(COLLECTION_OFFER_TOKEN_ID),
+            );
             Bound::exclusive(order_key)
         });
 
         // load offers
-        let offers = self
-            .offers
+        let offers = offers
             .idx
             .users
             .prefix(offerer)
             .range(deps.storage, start, None, Order::Descending)
             .map(|item| item.map(|(_, order)| order))
+            .filter(|item| match item {
+                Ok(order) => !self.is_cancelled_offer(deps.storage, order),
+                Err(_) => true,
+            })
             .take(limit)
             .collect::<StdResult<Vec<_>>>()?;
 
         // return offers
         Ok(OffersResponse { offers })
     }
+
+    // query the latest sales of a nft, or of a collection if the token_id is None
+    pub fn query_sale_history(
+        self,
+        deps: Deps,
+        contract_address: Addr,
+        token_id: Option<String>,
+        start_after: Option<u64>,
+        limit: Option<u32>,
+    ) -> StdResult<SalesResponse> {
+        let limit = limit.unwrap_or(30).min(30) as usize;
+        let end = start_after.map(Bound::exclusive);
+
+        let sales = match token_id {
+            Some(token_id) => self
+                .nft_sales
+                .prefix((contract_address, token_id))
+                .range(deps.storage, None, end, Order::Descending)
+                .take(limit)
+                .map(|item| item.map(|(_, sale)| sale))
+                .collect::<StdResult<Vec<_>>>()?,
+            None => self
+                .collection_sales
+                .prefix(contract_address)
+                .range(deps.storage, None, end, Order::Descending)
+                .take(limit)
+                .map(|item| item.map(|(_, sale)| sale))
+                .collect::<StdResult<Vec<_>>>()?,
+        };
+        Ok(SalesResponse { sales })
+    }
+
+    // query the stats of a collection, the floor prices and the number of listings only
+    // include the active listings, which are not expired or cancelled
+    pub fn query_collection_stats(
+        self,
+        deps: Deps,
+        env: Env,
+        contract_address: Addr,
+        start_after: Option<ListingsCursor>,
+    ) -> StdResult<CollectionStatsResponse> {
+        let mut floor_prices: Vec<PaymentAsset> = vec![];
+        let mut listing_count = 0;
+        let mut scanned_listings = 0;
+        let mut last_listing: Option<Listing> = None;
+
+        let start = start_after.map(|cursor| {
+            Bound::exclusive((
+                (cursor.denom, cursor.price),
+                listing_key(&contract_address, &cursor.token_id),
+            ))
+        });
+
+        // the listings are sorted by denom and lowest price, the first active listing of a denom
+        // has the floor price, unless a dutch auction has not declined to its lowest price yet
+        for item in self
+            .listings
+            .idx
+            .prices
+            .sub_prefix(contract_address.clone())
+            .range(deps.storage, start, None, Order::Ascending)
+            .take(MAX_SCANNED_LISTINGS)
+        {
+            let (_, listing) = item?;
+            scanned_listings += 1;
+            if listing.is_expired(&env.block) || self.is_cancelled_listing(deps.storage, &listing) {
+                last_listing = Some(listing);
+                continue;
+            }
+            listing_count += 1;
+
+            let price = listing.current_price(&env.block);
+            match floor_prices
+                .iter_mut()
+                .find(|floor_price| floor_price.denom() == price.denom())
+            {
+                Some(floor_price) if price.amount() < floor_price.amount() => *floor_price = price,
+                Some(_) => {}
+                None => floor_prices.push(price),
+            }
+            last_listing = Some(listing);
+        }
+
+        // the next page continues after the last scanned listing
+        let next = match last_listing {
+            Some(listing) if scanned_listings == MAX_SCANNED_LISTINGS => {
+                let price = listing.lowest_price();
+                Some(ListingsCursor {
+                    denom: price.denom(),
+                    price: price.amount(),
+                    token_id: listing.token_id,
+                })
+            }
+            _ => None,
+        };
+
+        let stats = self
+            .collection_stats
+            .may_load(deps.storage, contract_address)?
+            .unwrap_or_default();
+        Ok(CollectionStatsResponse {
+            floor_prices,
+            listing_count,
+            volume: stats.volume,
+            sale_count: stats.sale_count,
+            next,
+        })
+    }
+
+    // query the last sale of a nft, or of a collection if the token_id is None
+    pub fn query_last_sale(
+        self,
+        deps: Deps,
+        contract_address: Addr,
+        token_id: Option<String>,
+    ) -> StdResult<Option<Sale>> {
+        let sales = self.query_sale_history(deps, contract_address, token_id, None, Some(1))?;
+        Ok(sales.sales.into_iter().next())
+    }
+
+    // query the best active offer of each nft of an owner in the collections,
+    // the nfts are enumerated by the Tokens query of each collection
+    pub fn query_offers_received(
+        self,
+        deps: Deps,
+        env: Env,
+        owner: Addr,
+        collections: Vec<Addr>,
+        start_after_nft: Option<NFT>,
+        limit: Option<u32>,
+    ) -> StdResult<ReceivedOffersResponse> {
+        let limit = limit.unwrap_or(30).min(30) as usize;
+        let mut offers: Vec<ReceivedOffer> = vec![];
+        let mut scanned_nfts = 0;
+        // the offers of the nfts which can still be read in this page
+        let mut offers_budget = MAX_SCANNED_OFFERS;
+        // the last nft whose offers are all compared
+        let mut last_nft: Option<NFT> = None;
+
+        // the collections before the one of the last nft are already returned
+        let position = start_after_nft
+            .as_ref()
+            .and_then(|nft| {
+                collections
+                    .iter()
+                    .position(|collection| *collection == nft.contract_address)
+            })
+            .unwrap_or(0);
+
+        for contract_address in collections.iter().skip(position) {
+            let mut start_after = match &start_after_nft {
+                Some(nft) if nft.contract_address == *contract_address => nft.token_id.clone(),
+                _ => None,
+            };
+            // the collection offers beyond their own budget are not compared
+            let mut collection_offers_budget = MAX_SCANNED_OFFERS;
+            let (collection_offer, _) = self.best_offer(
+                deps,
+                &env,
+                contract_address,
+                COLLECTION_OFFER_TOKEN_ID,
+                &owner,
+                &mut collection_offers_budget,
+            )?;
+
+            loop {
+                let tokens: cw721::TokensResponse = deps.querier.query_wasm_smart(
+                    contract_address,
+                    &Cw721QueryMsg::Tokens {
+                        owner: owner.to_string(),
+                        start_after: start_after.clone(),
+                        limit: Some(30),
+                    },
+                )?;
+
+                // the collection may return less tokens than requested before its last page
+                if tokens.tokens.is_empty() {
+                    break;
+                }
+
+                for token_id in tokens.tokens.iter() {
+                    let (nft_offer, compared_all) = self.best_offer(
+                        deps,
+                        &env,
+                        contract_address,
+                        token_id,
+                        &owner,
+                        &mut offers_budget,
+                    )?;
+
+                    // the page ends before a nft whose offers exceed the remaining budget,
+                    // only the first nft of a page is returned with the best of its first offers
+                    if !compared_all && last_nft.is_some() {
+                        return Ok(ReceivedOffersResponse {
+                            offers,
+                            next: last_nft,
+                        });
+                    }
+
+                    scanned_nfts += 1;
+                    let nft = NFT {
+                        contract_address: contract_address.clone(),
+                        token_id: Some(token_id.clone()),
+                    };
+
+                    // the collection offer is returned if it offers more than the nft offer
+                    let offer = match (nft_offer, collection_offer.clone()) {
+                        (Some(nft_offer), Some(collection_offer))
+                            if collection_offer.current_offer_amount(&env.block)
+                                > nft_offer.current_offer_amount(&env.block) =>
+                        {
+                            Some(collection_offer)
+                        }
+                        (nft_offer, collection_offer) => nft_offer.or(collection_offer),
+                    };
+
+                    if let Some(offer) = offer {
+                        offers.push(ReceivedOffer {
+                            nft: nft.clone(),
+                            offer,
+                        });
+                    }
+
+                    // the next page continues after the last scanned nft
+                    last_nft = Some(nft);
+                    if offers.len() == limit
+                        || scanned_nfts == MAX_SCANNED_NFTS
+                        || offers_budget == 0
+                    {
+                        return Ok(ReceivedOffersResponse {
+                            offers,
+                            next: last_nft,
+                        });
+                    }
+                }
+
+                start_after = tokens.tokens.last().cloned();
+            }
+        }
+
+        Ok(ReceivedOffersResponse { offers, next: None })
+    }
+
+    // the active offer of a nft (or of a collection, with the empty token id) offering the most
+    // at the current block, the offers of the owner of the nft and the offers not started yet
+    // are skipped, all offers are compared because the index sorts them by their start amount
+    // at most budget offers are read, from the highest start amount, the budget is decreased
+    // by the number of read offers, the returned flag tells if all offers are compared
+    fn best_offer(
+        &self,
+        deps: Deps,
+        env: &Env,
+        contract_address: &Addr,
+        token_id: &str,
+        owner: &Addr,
+        budget: &mut usize,
+    ) -> StdResult<(Option<OrderComponents>, bool)> {
+        let mut best_offer: Option<OrderComponents> = None;
+        for item in self
+            .offers
+            .idx
+            .prices
+            .sub_prefix((contract_address.clone(), token_id.to_string()))
+            .range(deps.storage, None, None, Order::Descending)
+        {
+            if *budget == 0 {
+                return Ok((best_offer, false));
+            }
+            *budget -= 1;
+
+            let (_, offer) = item?;
+            if offer.offerer == *owner
+                || !offer.is_started(&env.block)
+                || offer
+                    .end_time
+                    .map_or(false, |end_time| end_time.is_expired(&env.block))
+                || self.is_cancelled_offer(deps.storage, &offer)
+            {
+                continue;
+            }
+            if best_offer.as_ref().map_or(true, |best_offer| {
+                offer.current_offer_amount(&env.block) > best_offer.current_offer_amount(&env.block)
+            }) {
+                best_offer = Some(offer);
+            }
+        }
+        Ok((best_offer, true))
+    }
+
+    pub fn query_counter(self, deps: Deps, user: Addr) -> StdResult<CounterResponse> {
+        let counter = self.counter(deps.storage, &user)?;
+        Ok(CounterResponse { counter })
+    }
+
+    // the counter of a user, incremented to cancel all listings and offers of the user at once
+    pub fn counter(&self, storage: &dyn Storage, user: &Addr) -> StdResult<u64> {
+        Ok(self
+            .counters
+            .may_load(storage, user.clone())?
+            .unwrap_or_default())
+    }
+
+    // a listing is cancelled if the seller has incremented its counter after listing it
+    pub fn is_cancelled_listing(&self, storage: &dyn Storage, listing: &Listing) -> bool {
+        self.counter(storage, &listing.seller) != Ok(listing.counter)
+    }
+
+    // an offer is cancelled if the offerer has incremented its counter after offering it
+    pub fn is_cancelled_offer(&self, storage: &dyn Storage, offer: &OrderComponents) -> bool {
+        self.counter(storage, &offer.offerer) != Ok(offer.counter)
+    }
+
+    // the sha256 hash of the payload of an order signed by its maker
+    pub fn signed_order_hash(&self, env: &Env, order: &OrderComponents) -> StdResult<Binary> {
+        let payload = SignedOrderPayload {
+            chain_id: env.block.chain_id.clone(),
+            marketplace: env.contract.address.clone(),
+            order: order.clone(),
+        };
+        let hash = Sha256::digest(to_binary(&payload)?.as_slice());
+        Ok(Binary::from(hash.as_slice()))
+    }
+}
+
+// the nft contract answers that the nft or its approval does not exist,
+// the other errors (e.g. a missing or failing contract) tell nothing about the nft
+fn is_not_found(err: &StdError) -> bool {
+    match err {
+        StdError::NotFound { .. } => true,
+        StdError::GenericErr { msg, .. } => msg.to_lowercase().contains("not found"),
+        _ => false,
+    }
 }
@@ -1,9 +1,14 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Addr, BlockInfo, Coin};
+use cosmwasm_std::{Addr, BlockInfo, Coin, Timestamp, Uint128};
 use cw721::Expiration;
-use cw_storage_plus::{Index, IndexList, IndexedMap, Item, MultiIndex, UniqueIndex};
+use cw_storage_plus::{Index, IndexList, IndexedMap, Item, Map, MultiIndex, UniqueIndex};
 
-use crate::order_state::{orders, OfferIndexes, OrderComponents, OrderKey};
+use crate::order_state::{
+    orders, swap_orders, OfferIndexes, OrderComponents, OrderKey, PaymentAsset, CW20,
+};
+// the state of an auction is returned by the auction contracts
+pub use marketplace_types::auction::AuctionState;
+use marketplace_types::order::linear_amount;
 
 #[cw_serde]
 pub enum AuctionConfig {
@@ -12,6 +17,39 @@ pub enum AuctionConfig {
         start_time: Option<Expiration>, // we use expiration for convinience
         end_time: Option<Expiration>,   // it's required that start_time < end_time
     },
+    DutchAuction {
+        start_price: Coin,      // the price declines linearly from start_price to end_price
+        end_price: Coin,        // it's required that end_price < start_price, with the same denom
+        start_time: Expiration, // the price is calculated by block time, so we require AtTime
+        end_time: Expiration,   // it's required that start_time < end_time
+    },
+    Cw20FixedPrice {
+        price: CW20, // the cw20 token must be whitelisted by the owner of the marketplace
+        start_time: Option<Expiration>,
+        end_time: Option<Expiration>, // it's required that start_time < end_time
+    },
+    // the nft is auctioned by the auction contract registered for the english_auction type,
+    // the config is forwarded to the auction contract, the bids are made in the auction contract
+    EnglishAuction {
+        start_price: Coin,           // the first bid must be at least the start price
+        step_percentage: Option<u8>, // a bid must exceed the previous one by this percentage
+        buyout_price: Option<u128>,
+        start_time: Option<Expiration>,
+        end_time: Expiration, // the end time is extended by late bids in the auction contract
+    },
+}
+
+// the auction type of the english auctions in the auction contracts registry
+pub const ENGLISH_AUCTION: &str = "english_auction";
+
+impl AuctionConfig {
+    // the auction type of a config handled by an auction contract
+    pub fn auction_type(&self) -> Option<&'static str> {
+        match self {
+            AuctionConfig::EnglishAuction { .. } => Some(ENGLISH_AUCTION),
+            _ => None,
+        }
+    }
 }
 
 pub type TokenId = String;
@@ -23,16 +61,104 @@ pub struct Listing {
     pub auction_config: AuctionConfig, // config of the auction, should be validated by the auction contract when created
     pub seller: Addr,
     pub buyer: Option<Addr>, // buyer, will be initialized to None
+    #[serde(default)]
+    pub escrow: bool, // the nft is held by this contract until the listing is bought or cancelled
+    #[serde(default)]
+    pub reserved_buyers: Vec<Addr>, // only these addresses can buy a private listing, anyone if empty
+    #[serde(default)]
+    pub counter: u64, // the counter of the seller, the listing is cancelled if the counter is incremented
 }
 
 impl Listing {
     // expired is when a listing has passed the end_time
     pub fn is_expired(&self, block_info: &BlockInfo) -> bool {
         match self.auction_config {
-            AuctionConfig::FixedPrice { end_time, .. } => match end_time {
+            AuctionConfig::FixedPrice { end_time, .. }
+            | AuctionConfig::Cw20FixedPrice { end_time, .. } => match end_time {
                 Some(time) => time.is_expired(block_info),
                 None => false,
             },
+            AuctionConfig::DutchAuction { end_time, .. }
+            | AuctionConfig::EnglishAuction { end_time, .. } => end_time.is_expired(block_info),
+        }
+    }
+
+    // started is when a listing has passed the start_time
+    pub fn is_started(&self, block_info: &BlockInfo) -> bool {
+        match self.auction_config {
+            AuctionConfig::FixedPrice { start_time, .. }
+            | AuctionConfig::Cw20FixedPrice { start_time, .. }
+            | AuctionConfig::EnglishAuction { start_time, .. } => match start_time {
+                Some(time) => time.is_expired(block_info),
+                None => true,
+            },
+            AuctionConfig::DutchAuction { start_time, .. } => start_time.is_expired(block_info),
+        }
+    }
+
+    // a private listing can only be bought by its reserved buyers
+    pub fn can_be_bought_by(&self, buyer: &Addr) -> bool {
+        self.reserved_buyers.is_empty() || self.reserved_buyers.contains(buyer)
+    }
+
+    // the lowest price of the listing, a dutch auction declines to its end price
+    // and an english auction starts at its start price
+    pub fn lowest_price(&self) -> PaymentAsset {
+        match &self.auction_config {
+            AuctionConfig::FixedPrice { price, .. }
+            | AuctionConfig::EnglishAuction {
+                start_price: price, ..
+            } => PaymentAsset::Native {
+                denom: price.denom.clone(),
+                amount: price.amount.u128(),
+            },
+            AuctionConfig::DutchAuction { end_price, .. } => PaymentAsset::Native {
+                denom: end_price.denom.clone(),
+                amount: end_price.amount.u128(),
+            },
+            AuctionConfig::Cw20FixedPrice { price, .. } => PaymentAsset::Cw20 {
+                contract_address: price.contract_address.clone(),
+                amount: price.amount,
+            },
+        }
+    }
+
+    // the price a buyer has to pay for the listing at the given block
+    pub fn current_price(&self, block_info: &BlockInfo) -> PaymentAsset {
+        match &self.auction_config {
+            AuctionConfig::FixedPrice { price, .. }
+            | AuctionConfig::EnglishAuction {
+                start_price: price, ..
+            } => PaymentAsset::Native {
+                denom: price.denom.clone(),
+                amount: price.amount.u128(),
+            },
+            AuctionConfig::DutchAuction {
+                start_price,
+                end_price,
+                start_time,
+                end_time,
+            } => {
+                // the auction config is validated on listing, so both times are AtTime
+                let amount = match (start_time, end_time) {
+                    (Expiration::AtTime(start), Expiration::AtTime(end)) => linear_amount(
+                        start_price.amount.u128(),
+                        end_price.amount.u128(),
+                        *start,
+                        *end,
+                        block_info.time,
+                    ),
+                    _ => start_price.amount.u128(),
+                };
+                PaymentAsset::Native {
+                    denom: start_price.denom.clone(),
+                    amount,
+                }
+            }
+            AuctionConfig::Cw20FixedPrice { price, .. } => PaymentAsset::Cw20 {
+                contract_address: price.contract_address.clone(),
+                amount: price.amount,
+            },
         }
     }
 }
@@ -48,12 +174,15 @@ pub fn listing_key(contract_address: &Addr, token_id: &TokenId) -> ListingKey {
 // contract_address can point to multiple listings
 pub struct ListingIndexes<'a> {
     pub contract_address: MultiIndex<'a, Addr, Listing, ListingKey>,
+    pub sellers: MultiIndex<'a, Addr, Listing, ListingKey>,
+    // listings of a collection sorted by denom and lowest price
+    pub prices: MultiIndex<'a, (Addr, (String, u128)), Listing, ListingKey>,
 }
 
 impl<'a> IndexList<Listing> for ListingIndexes<'a> {
     // this method returns a list of all indexes
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<Listing>> + '_> {
-        let v: Vec<&dyn Index<Listing>> = vec![&self.contract_address];
+        let v: Vec<&dyn Index<Listing>> = vec![&self.contract_address, &self.sellers, &self.prices];
         Box::new(v.into_iter())
     }
 }
@@ -62,10 +191,23 @@ impl<'a> IndexList<Listing> for ListingIndexes<'a> {
 pub fn listings<'a>() -> IndexedMap<'a, ListingKey, Listing, ListingIndexes<'a>> {
     let indexes = ListingIndexes {
         contract_address: MultiIndex::new(
-            |_pk: &[u8], l: &Listing| (l.contract_address.clone()),
+            |_pk: &[u8], l: &Listing| l.contract_address.clone(),
             "listings",
             "listings__contract_address",
         ),
+        sellers: MultiIndex::new(
+            |_pk: &[u8], l: &Listing| l.seller.clone(),
+            "listings",
+            "listings__seller",
+        ),
+        prices: MultiIndex::new(
+            |_pk: &[u8], l: &Listing| {
+                let price = l.lowest_price();
+                (l.contract_address.clone(), (price.denom(), price.amount()))
+            },
+            "listings",
+            "listings__price",
+        ),
     };
     IndexedMap::new("listings", indexes)
 }
@@ -74,13 +216,172 @@ pub fn listings<'a>() -> IndexedMap<'a, ListingKey, Listing, ListingIndexes<'a>>
 pub struct Config {
     pub owner: Addr,
     pub vaura_address: Addr,
+    // the fee charged by the marketplace on every sale, in basis points of the price
+    #[serde(default)]
+    pub protocol_fee_bps: u16,
+    // the address receiving the protocol fee, required if the fee is not zero
+    #[serde(default)]
+    pub fee_collector: Option<Addr>,
+    // which collections can be listed and offered
+    #[serde(default)]
+    pub registry_mode: RegistryMode,
+}
+
+#[cw_serde]
+#[derive(Default)]
+pub enum RegistryMode {
+    Allow, // only the allowed collections
+    #[default]
+    Block, // all collections except the blocked ones
+}
+
+#[cw_serde]
+pub enum CollectionStatus {
+    Allowed,
+    Blocked,
+}
+
+// a collection registered by the owner of the marketplace
+#[cw_serde]
+pub struct RegisteredCollection {
+    pub contract_address: Addr,
+    pub name: String,
+    pub description: Option<String>,
+    pub status: CollectionStatus,
+}
+
+// the protocol fee is expressed in basis points of the price
+pub const BPS_DENOMINATOR: u16 = 10_000;
+
+// the protocol fee cannot be greater than 10% of the price
+pub const MAX_PROTOCOL_FEE_BPS: u16 = 1_000;
+
+impl Config {
+    // calculate the protocol fee of a sale and the address receiving it
+    pub fn protocol_fee(&self, amount: Uint128) -> Option<(Addr, Uint128)> {
+        match &self.fee_collector {
+            Some(fee_collector) if self.protocol_fee_bps > 0 => Some((
+                fee_collector.clone(),
+                amount.multiply_ratio(self.protocol_fee_bps, BPS_DENOMINATOR),
+            )),
+            _ => None,
+        }
+    }
 }
 
+// a sweep buys many listings at once, a listing which cannot be bought is skipped
+// the sweep is only stored during the transaction and removed when it is finalized
+#[cw_serde]
+pub struct SweepItem {
+    pub contract_address: Addr,
+    pub token_id: String,
+    pub price: Option<Coin>, // the price paid, None if the listing is skipped
+}
+
+#[cw_serde]
+pub struct Sweep {
+    pub buyer: Addr,
+    pub funds: Vec<Coin>,
+    pub items: Vec<SweepItem>,
+}
+
+// a bundle sells several nfts, possibly of different collections, for one native price
+#[cw_serde]
+pub struct BundleItem {
+    pub contract_address: Addr,
+    pub token_id: String,
+}
+
+#[cw_serde]
+pub struct Bundle {
+    pub id: u64,
+    pub seller: Addr,
+    pub nfts: Vec<BundleItem>,
+    pub price: Coin,
+    pub end_time: Option<Expiration>,
+    #[serde(default)]
+    pub counter: u64, // the counter of the seller, the bundle is cancelled if the counter is incremented
+}
+
+impl Bundle {
+    // the price is apportioned equally to the nfts of the bundle to calculate their royalties,
+    // the remainder of the division is added to the first nft
+    pub fn item_prices(&self) -> Vec<Uint128> {
+        let count = Uint128::from(self.nfts.len() as u128);
+        let share = self.price.amount / count;
+        let remainder = self.price.amount - share * count;
+        (0..self.nfts.len())
+            .map(|i| if i == 0 { share + remainder } else { share })
+            .collect()
+    }
+}
+
+#[cw_serde]
+pub enum SaleKind {
+    Listing, // a listing is bought
+    Offer,   // an offer is accepted
+    Bundle,  // a bundle is bought, each nft of the bundle is a sale
+}
+
+#[cw_serde]
+pub struct Sale {
+    pub id: u64, // the sales are numbered in the order they happened
+    pub contract_address: Addr,
+    pub token_id: String,
+    pub price: PaymentAsset, // the amount paid by the buyer, including the royalty and the protocol fee
+    pub buyer: Addr,
+    pub seller: Addr,
+    pub time: Timestamp,
+    pub kind: SaleKind,
+}
+
+// the traded volume and the number of sales of a collection
+#[cw_serde]
+#[derive(Default)]
+pub struct CollectionStats {
+    pub volume: Vec<PaymentAsset>, // the volume of each native denom and cw20 token
+    pub sale_count: u64,
+}
+
+impl CollectionStats {
+    pub fn add_sale(&mut self, price: &PaymentAsset) {
+        self.sale_count += 1;
+        match self
+            .volume
+            .iter_mut()
+            .find(|volume| volume.denom() == price.denom())
+        {
+            Some(PaymentAsset::Native { amount, .. }) | Some(PaymentAsset::Cw20 { amount, .. }) => {
+                *amount += price.amount()
+            }
+            None => self.volume.push(price.clone()),
+        }
+    }
+}
+
+// only the latest sales of a nft and a collection are kept in their history
+pub const MAX_NFT_SALES: u64 = 20;
+pub const MAX_COLLECTION_SALES: u64 = 100;
+
+// the offers received by an owner are returned by pages of at most this number of scanned nfts
+pub const MAX_SCANNED_NFTS: usize = 100;
+// the offers of the nfts read in a page of offers received, the collection offers have their own budget
+pub const MAX_SCANNED_OFFERS: usize = 100;
+// the stats of a collection are returned by pages of at most this number of scanned listings
+pub const MAX_SCANNED_LISTINGS: usize = 300;
+
+// a sweep buys at most this number of listings
+pub const MAX_SWEEP_ITEMS: usize = 50;
+// the buy of the item at index i of a sweep replies with the id SWEEP_ITEM_REPLY_ID + i,
+// the ids from SWEEP_ITEM_REPLY_ID to SWEEP_ITEM_REPLY_ID + MAX_SWEEP_ITEMS are reserved for sweeps
+pub const SWEEP_ITEM_REPLY_ID: u64 = 1_000;
+
 // Auction Contract
 // We index the list of auction contracts by their address
 // When they are upgraded, the new contract will decide to process a config or reject it based on code_id
 // For example, if the new contract is a performance upgrade, it can accept the config
 // If the new contract is a breaking change or a bug fix, it can reject the config
+// The name of an auction contract is the auction type of the listings routed to it
 
 #[cw_serde]
 pub struct AuctionContract {
@@ -93,11 +394,12 @@ pub type AuctionContractKey = Addr;
 
 pub struct AuctionContractIndexes<'a> {
     pub code_id: UniqueIndex<'a, u32, AuctionContract, AuctionContractKey>,
+    pub name: UniqueIndex<'a, String, AuctionContract, AuctionContractKey>,
 }
 
 impl<'a> IndexList<AuctionContract> for AuctionContractIndexes<'a> {
     fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<AuctionContract>> + '_> {
-        let v: Vec<&dyn Index<AuctionContract>> = vec![&self.code_id];
+        let v: Vec<&dyn Index<AuctionContract>> = vec![&self.code_id, &self.name];
         Box::new(v.into_iter())
     }
 }
@@ -109,6 +411,10 @@ fn auction_contracts<'a>(
             |c: &AuctionContract| c.code_id,
             "auction_contracts__code_id",
         ),
+        name: UniqueIndex::new(
+            |c: &AuctionContract| c.name.clone(),
+            "auction_contracts__name",
+        ),
     };
     IndexedMap::new("auction_contracts", indexes)
 }
@@ -121,6 +427,37 @@ pub struct MarketplaceContract<'a> {
         IndexedMap<'a, AuctionContractKey, AuctionContract, AuctionContractIndexes<'a>>,
 
     pub offers: IndexedMap<'a, OrderKey, OrderComponents, OfferIndexes<'a>>,
+    pub swap_offers: IndexedMap<'a, OrderKey, OrderComponents, OfferIndexes<'a>>,
+
+    // the cw20 tokens which can be used to price a listing
+    pub payment_tokens: Map<'a, Addr, bool>,
+
+    // the sweep in progress
+    pub sweep: Item<'a, Sweep>,
+
+    pub bundles: Map<'a, u64, Bundle>,
+    // the id of the last created bundle
+    pub bundle_count: Item<'a, u64>,
+
+    // the counter of a user is stored with its listings, bundles and offers, and included in its
+    // signed orders, incrementing the counter cancels all of them at once
+    pub counters: Map<'a, Addr, u64>,
+    // the hashes of the fulfilled signed orders, a signed order cannot be fulfilled twice
+    pub fulfilled_orders: Map<'a, &'a [u8], bool>,
+
+    // the id of the last sale
+    pub sale_count: Item<'a, u64>,
+    // the sale history of each nft and each collection, indexed by the id of the sale
+    pub nft_sales: Map<'a, (Addr, TokenId, u64), Sale>,
+    pub collection_sales: Map<'a, (Addr, u64), Sale>,
+    // the number of sales kept in the history of each nft and each collection
+    pub nft_sale_counts: Map<'a, (Addr, TokenId), u64>,
+    pub collection_sale_counts: Map<'a, Addr, u64>,
+    // the stats of each collection, updated on every sale
+    pub collection_stats: Map<'a, Addr, CollectionStats>,
+
+    // the collections allowed or blocked by the owner
+    pub collections: Map<'a, Addr, RegisteredCollection>,
 }
 
 // impl default for MarketplaceContract
@@ -132,6 +469,26 @@ impl Default for MarketplaceContract<'static> {
             auction_contracts: auction_contracts(),
 
             offers: orders(),
+            swap_offers: swap_orders(),
+
+            payment_tokens: Map::new("payment_tokens"),
+
+            sweep: Item::new("sweep"),
+
+            bundles: Map::new("bundles"),
+            bundle_count: Item::new("bundle_count"),
+
+            counters: Map::new("counters"),
+            fulfilled_orders: Map::new("fulfilled_orders"),
+
+            sale_count: Item::new("sale_count"),
+            nft_sales: Map::new("nft_sales"),
+            collection_sales: Map::new("collection_sales"),
+            nft_sale_counts: Map::new("nft_sale_counts"),
+            collection_sale_counts: Map::new("collection_sale_counts"),
+            collection_stats: Map::new("collection_stats"),
+
+            collections: Map::new("collections"),
         }
     }
 }
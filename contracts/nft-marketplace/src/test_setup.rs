@@ -5,7 +5,9 @@ pub mod env {
     use cw2981_royalties::msg::InstantiateMsg as Cw2981InstantiateMsg;
     use cw2981_royalties::{
         execute as cw2981_execute, instantiate as cw2981_instantiate, query as cw2981_query,
+        Metadata, MintMsg,
     };
+    use cw721_base::msg::ExecuteMsg as Cw721ExecuteMsg;
 
     use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
 
@@ -15,11 +17,28 @@ pub mod env {
     };
     use bidding_token::state::InstantiateMsg as Cw20InstantiateMsg;
 
+    // The standard cw20 contract, used to price listings
+    use cw20_base::contract::{
+        execute as cw20_base_execute, instantiate as cw20_base_instantiate,
+        query as cw20_base_query,
+    };
+    use cw20_base::msg::InstantiateMsg as Cw20BaseInstantiateMsg;
+
+    // The nft auction contract, an auction contract of the marketplace
+    use nft_auction::contract::{
+        execute as auction_execute, instantiate as auction_instantiate, migrate as auction_migrate,
+        query as auction_query, reply as auction_reply,
+    };
+    use nft_auction::msg::{
+        ExecuteMsg as AuctionExecuteMsg, InstantiateMsg as AuctionInstantiateMsg,
+    };
+
     use crate::contract::{
         execute as MarketPlaceExecute, instantiate as MarketPlaceInstantiate,
-        query as MarketPlaceQuery,
+        query as MarketPlaceQuery, reply as MarketPlaceReply,
     };
     use crate::msg::{ExecuteMsg as MarketPlaceExecuteMsg, InstantiateMsg};
+    use crate::state::AuctionConfig;
 
     // ****************************************
     // You MUST define the constants value here
@@ -80,6 +99,12 @@ pub mod env {
         Box::new(contract)
     }
 
+    fn cw20_base_contract_template() -> Box<dyn Contract<Empty>> {
+        let contract =
+            ContractWrapper::new(cw20_base_execute, cw20_base_instantiate, cw20_base_query);
+        Box::new(contract)
+    }
+
     fn cw2981_contract_template() -> Box<dyn Contract<Empty>> {
         let contract = ContractWrapper::new(cw2981_execute, cw2981_instantiate, cw2981_query);
         Box::new(contract)
@@ -87,7 +112,15 @@ pub mod env {
 
     fn nft_marketplace_contract_template() -> Box<dyn Contract<Empty>> {
         let contract =
-            ContractWrapper::new(MarketPlaceExecute, MarketPlaceInstantiate, MarketPlaceQuery);
+            ContractWrapper::new(MarketPlaceExecute, MarketPlaceInstantiate, MarketPlaceQuery)
+                .with_reply(MarketPlaceReply);
+        Box::new(contract)
+    }
+
+    pub fn nft_auction_contract_template() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(auction_execute, auction_instantiate, auction_query)
+            .with_migrate(auction_migrate)
+            .with_reply(auction_reply);
         Box::new(contract)
     }
 
@@ -257,16 +290,210 @@ pub mod env {
 
         let res = app.execute_contract(
             Addr::unchecked(OWNER),
-            Addr::unchecked(marketplace_contract_addr),
+            Addr::unchecked(marketplace_contract_addr.clone()),
             &set_vaura_token_msg,
             &[],
         );
         assert!(res.is_ok());
 
+        // Cw20 base contract
+        // store the code of all contracts to the app and get the code ids
+        let cw20_base_contract_code_id = app.store_code(cw20_base_contract_template());
+
+        // create instantiate message for contract, USER_1 and USER_2 hold the initial balances
+        let cw20_base_instantiate_msg = Cw20BaseInstantiateMsg {
+            name: "Contract_B".to_string(),
+            symbol: "cwAura".to_string(),
+            decimals: 6,
+            initial_balances: vec![
+                Cw20Coin {
+                    address: USER_1.to_string(),
+                    amount: Uint128::new(TOKEN_INITIAL_BALANCE),
+                },
+                Cw20Coin {
+                    address: USER_2.to_string(),
+                    amount: Uint128::new(TOKEN_INITIAL_BALANCE),
+                },
+            ],
+            mint: None,
+            marketing: None,
+        };
+
+        // instantiate contract
+        let cw20_base_contract_addr = app
+            .instantiate_contract(
+                cw20_base_contract_code_id,
+                Addr::unchecked(OWNER),
+                &cw20_base_instantiate_msg,
+                &[],
+                "test instantiate cw20 base contract",
+                None,
+            )
+            .unwrap();
+
+        // add contract info to the vector
+        contract_info_vec.push(ContractInfo {
+            contract_addr: cw20_base_contract_addr.to_string(),
+            contract_code_id: cw20_base_contract_code_id,
+        });
+
+        // whitelist the cw20 base token in the marketplace contract
+        let add_payment_token_msg = MarketPlaceExecuteMsg::AddPaymentToken {
+            token_address: cw20_base_contract_addr.to_string(),
+        };
+
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(marketplace_contract_addr.clone()),
+            &add_payment_token_msg,
+            &[],
+        );
+        assert!(res.is_ok());
+
+        // Nft auction contract
+        // the owner is the admin, so that the contract can be migrated
+        let auction_contract_code_id = app.store_code(nft_auction_contract_template());
+
+        let auction_contract_addr = app
+            .instantiate_contract(
+                auction_contract_code_id,
+                Addr::unchecked(OWNER),
+                &AuctionInstantiateMsg {
+                    owner: Addr::unchecked(OWNER),
+                },
+                &[],
+                "test instantiate nft auction contract",
+                Some(OWNER.to_string()),
+            )
+            .unwrap();
+
+        contract_info_vec.push(ContractInfo {
+            contract_addr: auction_contract_addr.to_string(),
+            contract_code_id: auction_contract_code_id,
+        });
+
+        // the auction contract creates the auctions of the marketplace
+        let update_config_msg = AuctionExecuteMsg::UpdateConfig {
+            marketplace: Some(marketplace_contract_addr.to_string()),
+            accepted_code_ids: None,
+        };
+
+        let res = app.execute_contract(
+            Addr::unchecked(OWNER),
+            auction_contract_addr,
+            &update_config_msg,
+            &[],
+        );
+        assert!(res.is_ok());
+
         // return the app instance, the addresses and code IDs of all contracts
         (app, contract_info_vec)
     }
 
+    // mint a nft of the cw2981 contract to the owner
+    pub fn mint_nft(app: &mut App, cw2981_address: &str, owner: &str, token_id: &str) {
+        let mint_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::Mint(MintMsg {
+            token_id: token_id.to_string(),
+            owner: owner.to_string(),
+            token_uri: None,
+            extension: Metadata::default(),
+        });
+        app.execute_contract(
+            Addr::unchecked(OWNER),
+            Addr::unchecked(cw2981_address),
+            &mint_msg,
+            &[],
+        )
+        .unwrap();
+    }
+
+    // the owner approves the marketplace to transfer the nft,
+    // the listing requires a never expired approval
+    pub fn approve_nft(
+        app: &mut App,
+        cw2981_address: &str,
+        marketplace_address: &str,
+        owner: &str,
+        token_id: &str,
+    ) {
+        let approve_msg: Cw721ExecuteMsg<Metadata, Metadata> = Cw721ExecuteMsg::Approve {
+            spender: marketplace_address.to_string(),
+            token_id: token_id.to_string(),
+            expires: None,
+        };
+        app.execute_contract(
+            Addr::unchecked(owner),
+            Addr::unchecked(cw2981_address),
+            &approve_msg,
+            &[],
+        )
+        .unwrap();
+    }
+
+    pub fn mint_and_approve_nft(
+        app: &mut App,
+        cw2981_address: &str,
+        marketplace_address: &str,
+        owner: &str,
+        token_id: &str,
+    ) {
+        mint_nft(app, cw2981_address, owner, token_id);
+        approve_nft(app, cw2981_address, marketplace_address, owner, token_id);
+    }
+
+    // the seller approves and lists the nft at a fixed price of the native token
+    pub fn list_nft(
+        app: &mut App,
+        cw2981_address: &str,
+        marketplace_address: &str,
+        seller: &str,
+        token_id: &str,
+        price: u128,
+    ) {
+        approve_nft(app, cw2981_address, marketplace_address, seller, token_id);
+
+        let list_msg = MarketPlaceExecuteMsg::ListNft {
+            contract_address: cw2981_address.to_string(),
+            token_id: token_id.to_string(),
+            auction_config: AuctionConfig::FixedPrice {
+                price: Coin {
+                    denom: NATIVE_DENOM.to_string(),
+                    amount: Uint128::from(price),
+                },
+                start_time: None,
+                end_time: None,
+            },
+            escrow: false,
+            reserved_buyers: vec![],
+        };
+        app.execute_contract(
+            Addr::unchecked(seller),
+            Addr::unchecked(marketplace_address),
+            &list_msg,
+            &[],
+        )
+        .unwrap();
+    }
+
+    pub fn mint_and_list_nft(
+        app: &mut App,
+        cw2981_address: &str,
+        marketplace_address: &str,
+        seller: &str,
+        token_id: &str,
+        price: u128,
+    ) {
+        mint_nft(app, cw2981_address, seller, token_id);
+        list_nft(
+            app,
+            cw2981_address,
+            marketplace_address,
+            seller,
+            token_id,
+            price,
+        );
+    }
+
     // cannot instantiate bidding-token contract with initial_balance
     #[test]
     fn cannot_instantiate_cw20_with_initial_balance() {
@@ -0,0 +1,151 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, StdError, StdResult};
+
+#[cw_serde]
+pub struct NFT {
+    pub contract_address: Addr,
+    pub token_id: Option<String>,
+}
+
+#[cw_serde]
+#[derive(Eq, PartialOrd, Ord)]
+pub struct NftTrait {
+    pub trait_type: String,
+    pub value: String,
+}
+
+// any nft of a collection having all the traits in its metadata
+#[cw_serde]
+pub struct TraitNFT {
+    pub contract_address: Addr,
+    pub traits: Vec<NftTrait>,
+}
+
+#[cw_serde]
+pub struct CW20 {
+    pub contract_address: Addr,
+    pub amount: u128,
+}
+
+#[cw_serde]
+pub struct NATIVE {
+    pub denom: String,
+    pub amount: u128,
+}
+
+#[cw_serde]
+pub enum Asset {
+    Nft(NFT),
+    TraitNft(TraitNFT),
+    Native(NATIVE),
+    Cw20(CW20),
+}
+
+#[cw_serde]
+pub enum PaymentAsset {
+    Native {
+        denom: String,
+        amount: u128,
+    },
+    Cw20 {
+        contract_address: Addr,
+        amount: u128,
+    },
+}
+
+impl PaymentAsset {
+    // the denom of a native token, or the address of a cw20 token
+    pub fn denom(&self) -> String {
+        match self {
+            PaymentAsset::Native { denom, .. } => denom.clone(),
+            PaymentAsset::Cw20 {
+                contract_address, ..
+            } => contract_address.to_string(),
+        }
+    }
+
+    pub fn amount(&self) -> u128 {
+        match self {
+            PaymentAsset::Native { amount, .. } | PaymentAsset::Cw20 { amount, .. } => *amount,
+        }
+    }
+}
+
+impl TryFrom<Asset> for PaymentAsset {
+    type Error = StdError;
+
+    fn try_from(asset: Asset) -> StdResult<Self> {
+        match asset {
+            Asset::Native(NATIVE { denom, amount }) => Ok(PaymentAsset::Native { denom, amount }),
+            Asset::Cw20(CW20 {
+                contract_address,
+                amount,
+            }) => Ok(PaymentAsset::Cw20 {
+                contract_address,
+                amount,
+            }),
+            _ => Err(StdError::generic_err("Asset is not a payment asset")),
+        }
+    }
+}
+
+// the OrderKey includes the address and id of NFT
+// !DO NOT change the order of the fields
+pub type OrderKey = (Addr, Addr, String);
+
+pub fn order_key(user_address: &Addr, contract_address: &Addr, token_id: &str) -> OrderKey {
+    (
+        user_address.clone(),
+        contract_address.clone(),
+        token_id.to_owned(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn payment_asset_from_asset() {
+        let native = PaymentAsset::try_from(Asset::Native(NATIVE {
+            denom: "uaura".to_string(),
+            amount: 100,
+        }))
+        .unwrap();
+        assert_eq!(native.denom(), "uaura");
+        assert_eq!(native.amount(), 100);
+
+        let cw20 = PaymentAsset::try_from(Asset::Cw20(CW20 {
+            contract_address: Addr::unchecked("cw20"),
+            amount: 200,
+        }))
+        .unwrap();
+        assert_eq!(cw20.denom(), "cw20");
+        assert_eq!(cw20.amount(), 200);
+    }
+
+    #[test]
+    fn nft_is_not_a_payment_asset() {
+        let res = PaymentAsset::try_from(Asset::Nft(NFT {
+            contract_address: Addr::unchecked("nft"),
+            token_id: Some("1".to_string()),
+        }));
+        assert_eq!(
+            res.unwrap_err(),
+            StdError::generic_err("Asset is not a payment asset")
+        );
+    }
+
+    #[test]
+    fn order_key_keeps_the_order_of_the_fields() {
+        let key = order_key(&Addr::unchecked("user"), &Addr::unchecked("nft"), "1");
+        assert_eq!(
+            key,
+            (
+                Addr::unchecked("user"),
+                Addr::unchecked("nft"),
+                "1".to_string()
+            )
+        );
+    }
+}
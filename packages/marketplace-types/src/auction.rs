@@ -0,0 +1,83 @@
+//! The auctions of the nft auction contract.
+//!
+//! An `AuctionOrder` is not the `order::OrderComponents` of the marketplace. Its offer is the
+//! auctioned nft with the seller as offerer, and its consideration is the current price with the
+//! highest bidder as recipient, which is the seller until the first bid. It has no order type or
+//! counter, its start and end times are always set, and `config` holds the step percentage of
+//! the bids. Both models are stored by their own contracts, so they keep their own layouts.
+
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, BlockInfo, Coin};
+use cw721::Expiration;
+
+use crate::asset::{Asset, OrderKey};
+
+// the state of the auction of a nft, the current price is the highest bid if there is a bidder
+#[cw_serde]
+pub struct AuctionState {
+    pub seller: Addr,
+    pub current_price: Coin,
+    pub highest_bidder: Option<Addr>,
+    pub end_time: Expiration,
+}
+
+#[cw_serde]
+pub struct AuctionOffer {
+    pub item: Asset,
+    pub start_amount: u128,
+    pub end_amount: u128,
+    pub offerer: Addr,
+}
+
+pub fn auction_offer(
+    item: &Asset,
+    start_amount: &u128,
+    end_amount: &u128,
+    offerer: &Addr,
+) -> AuctionOffer {
+    AuctionOffer {
+        item: item.clone(),
+        start_amount: *start_amount,
+        end_amount: *end_amount,
+        offerer: offerer.clone(),
+    }
+}
+
+#[cw_serde]
+pub struct AuctionConsideration {
+    pub item: Asset,
+    pub start_amount: u128,
+    pub end_amount: u128,
+    pub recipient: Addr,
+}
+
+pub fn auction_consideration(
+    item: &Asset,
+    start_amount: &u128,
+    end_amount: &u128,
+    recipient: &Addr,
+) -> AuctionConsideration {
+    AuctionConsideration {
+        item: item.clone(),
+        start_amount: *start_amount,
+        end_amount: *end_amount,
+        recipient: recipient.clone(),
+    }
+}
+
+#[cw_serde]
+pub struct AuctionOrder {
+    pub order_id: OrderKey,
+    pub offer: Vec<AuctionOffer>,
+    pub consideration: Vec<AuctionConsideration>,
+    pub start_time: Expiration,
+    pub end_time: Expiration,
+    pub config: String,
+}
+
+impl AuctionOrder {
+    // expired is when a listing has passed the end_time
+    pub fn is_expired(&self, block_info: &BlockInfo) -> bool {
+        self.end_time.is_expired(block_info)
+    }
+}
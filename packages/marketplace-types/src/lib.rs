@@ -0,0 +1,4 @@
+pub mod asset;
+pub mod auction;
+pub mod order;
+pub mod payment;
@@ -0,0 +1,187 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::{Addr, BlockInfo, Timestamp, Uint128};
+use cw721::Expiration;
+
+use crate::asset::{Asset, OrderKey, CW20, NATIVE};
+
+pub type User = Addr;
+
+#[cw_serde]
+pub enum OrderType {
+    OFFER,
+    LISTING,
+}
+
+#[cw_serde]
+pub enum Side {
+    OFFER,
+    CONSIDERATION,
+}
+
+#[cw_serde]
+pub enum ItemType {
+    NATIVE,
+    CW20,
+    CW721,
+}
+
+#[cw_serde]
+pub struct OfferItem {
+    pub item_type: ItemType,
+    pub item: Asset,
+    pub start_amount: u128,
+    pub end_amount: u128,
+}
+
+pub fn offer_item(
+    item_type: &ItemType,
+    item: &Asset,
+    start_amount: &u128,
+    end_amount: &u128,
+) -> OfferItem {
+    OfferItem {
+        item_type: item_type.clone(),
+        item: item.clone(),
+        start_amount: *start_amount,
+        end_amount: *end_amount,
+    }
+}
+
+#[cw_serde]
+pub struct ConsiderationItem {
+    pub item_type: ItemType,
+    pub item: Asset,
+    pub start_amount: u128,
+    pub end_amount: u128,
+    pub recipient: Addr,
+}
+
+pub fn consideration_item(
+    item_type: &ItemType,
+    item: &Asset,
+    start_amount: &u128,
+    end_amount: &u128,
+    recipient: &Addr,
+) -> ConsiderationItem {
+    ConsiderationItem {
+        item_type: item_type.clone(),
+        item: item.clone(),
+        start_amount: *start_amount,
+        end_amount: *end_amount,
+        recipient: recipient.clone(),
+    }
+}
+
+#[cw_serde]
+pub struct OrderComponents {
+    pub order_type: OrderType,
+    pub order_id: OrderKey,
+    pub offerer: User,
+    pub offer: Vec<OfferItem>,
+    pub consideration: Vec<ConsiderationItem>,
+    pub start_time: Option<Expiration>,
+    pub end_time: Option<Expiration>,
+    // the counter of the offerer, the order is cancelled if the counter is incremented
+    #[serde(default)]
+    pub counter: u64,
+}
+
+impl OrderComponents {
+    // the amount of funds offered by the first offer item
+    pub fn offer_amount(&self) -> u128 {
+        match self.offer.first().map(|offer_item| &offer_item.item) {
+            Some(Asset::Cw20(CW20 { amount, .. })) | Some(Asset::Native(NATIVE { amount, .. })) => {
+                *amount
+            }
+            _ => 0,
+        }
+    }
+
+    // an order without a start time is started when it is created
+    pub fn is_started(&self, block_info: &BlockInfo) -> bool {
+        self.start_time
+            .map_or(true, |start_time| start_time.is_expired(block_info))
+    }
+
+    // a time-varying offer has different amounts at the start time and at the end time
+    pub fn is_time_varying(&self) -> bool {
+        match self.offer.first() {
+            Some(offer_item) => offer_item.start_amount != offer_item.end_amount,
+            None => false,
+        }
+    }
+
+    // the amount of funds offered at the given block, the amount of a time-varying offer
+    // changes linearly from start_amount at start_time to end_amount at end_time
+    pub fn current_offer_amount(&self, block_info: &BlockInfo) -> u128 {
+        match (self.offer.first(), self.start_time, self.end_time) {
+            (
+                Some(offer_item),
+                Some(Expiration::AtTime(start_time)),
+                Some(Expiration::AtTime(end_time)),
+            ) if self.is_time_varying() => linear_amount(
+                offer_item.start_amount,
+                offer_item.end_amount,
+                start_time,
+                end_time,
+                block_info.time,
+            ),
+            _ => self.offer_amount(),
+        }
+    }
+}
+
+// calculate the amount of a value changing linearly from start_amount at start_time
+// to end_amount at end_time, the amount is clamped outside of the time range
+pub fn linear_amount(
+    start_amount: u128,
+    end_amount: u128,
+    start_time: Timestamp,
+    end_time: Timestamp,
+    current_time: Timestamp,
+) -> u128 {
+    if current_time <= start_time {
+        return start_amount;
+    }
+    if current_time >= end_time {
+        return end_amount;
+    }
+
+    let elapsed = current_time.seconds() - start_time.seconds();
+    let duration = end_time.seconds() - start_time.seconds();
+    if start_amount >= end_amount {
+        let decrease = Uint128::from(start_amount - end_amount).multiply_ratio(elapsed, duration);
+        start_amount - decrease.u128()
+    } else {
+        let increase = Uint128::from(end_amount - start_amount).multiply_ratio(elapsed, duration);
+        start_amount + increase.u128()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn linear_amount_is_clamped_to_the_time_range() {
+        let start = Timestamp::from_seconds(100);
+        let end = Timestamp::from_seconds(200);
+
+        assert_eq!(
+            linear_amount(1000, 0, start, end, Timestamp::from_seconds(50)),
+            1000
+        );
+        assert_eq!(
+            linear_amount(1000, 0, start, end, Timestamp::from_seconds(150)),
+            500
+        );
+        assert_eq!(
+            linear_amount(0, 1000, start, end, Timestamp::from_seconds(175)),
+            750
+        );
+        assert_eq!(
+            linear_amount(1000, 0, start, end, Timestamp::from_seconds(250)),
+            0
+        );
+    }
+}
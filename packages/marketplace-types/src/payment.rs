@@ -0,0 +1,353 @@
+use cosmwasm_std::{to_binary, Addr, BankMsg, Coin, CosmosMsg, Deps, StdResult, Uint128, WasmMsg};
+use cw20::Cw20ExecuteMsg;
+use cw2981_royalties::msg::{Cw2981QueryMsg, RoyaltiesInfoResponse};
+use cw2981_royalties::QueryMsg as Cw2981ContractQueryMsg;
+
+use crate::asset::PaymentAsset;
+
+// the payment of the sale of a nft, the royalty of the nft is paid to its creator
+pub struct NftPayment<'a> {
+    pub nft_contract_address: &'a Addr,
+    pub token_id: &'a str,
+    pub token: &'a PaymentAsset,
+    // the payment is transferred from the sender by allowance,
+    // or from the funds held by the contract if the sender is None
+    pub sender: Option<&'a Addr>,
+    pub recipient: &'a Addr,
+    // the protocol fee is paid to the fee collector before the royalty
+    pub protocol_fee: Option<(&'a Addr, Uint128)>,
+}
+
+// the creator and the royalty amount of the sale of a cw2981 nft,
+// none if the nft contract does not support royalties or there is no royalty
+pub fn royalty_info(
+    deps: Deps,
+    nft_contract_address: &Addr,
+    token_id: &str,
+    sale_price: Uint128,
+) -> StdResult<Option<(Addr, Uint128)>> {
+    let royalty_query_msg = Cw2981ContractQueryMsg::Extension {
+        msg: Cw2981QueryMsg::RoyaltyInfo {
+            token_id: token_id.to_string(),
+            sale_price,
+        },
+    };
+    let royalty_info_rsp: StdResult<RoyaltiesInfoResponse> = deps
+        .querier
+        .query_wasm_smart(nft_contract_address, &royalty_query_msg);
+
+    match royalty_info_rsp {
+        Ok(RoyaltiesInfoResponse {
+            address,
+            royalty_amount,
+        }) if !address.is_empty() && !royalty_amount.is_zero() => {
+            Ok(Some((deps.api.addr_validate(&address)?, royalty_amount)))
+        }
+        _ => Ok(None),
+    }
+}
+
+// the messages paying the protocol fee, the royalty and the remaining amount to the recipient
+// the royalty is calculated on the full price, but it cannot exceed the amount left after the fee
+// a zero amount is not transferred, since a bank send of zero coins fails
+pub fn payment_with_royalty(deps: Deps, payment: &NftPayment) -> StdResult<Vec<CosmosMsg>> {
+    let mut res_messages: Vec<CosmosMsg> = vec![];
+    let mut pay = |amount: Uint128, recipient: &Addr| -> StdResult<()> {
+        if !amount.is_zero() {
+            res_messages.push(payment_message(
+                payment.token,
+                amount,
+                payment.sender,
+                recipient,
+            )?);
+        }
+        Ok(())
+    };
+    let amount = Uint128::from(payment.token.amount());
+
+    let mut remaining_amount = amount;
+    if let Some((fee_collector, fee_amount)) = payment.protocol_fee {
+        pay(fee_amount, fee_collector)?;
+        remaining_amount = amount.checked_sub(fee_amount)?;
+    }
+
+    let royalty = royalty_info(deps, payment.nft_contract_address, payment.token_id, amount)?
+        .map(|(creator, royalty_amount)| (creator, royalty_amount.min(remaining_amount)));
+
+    match royalty {
+        // there is a royalty, and the creator is not the recipient
+        Some((creator, royalty_amount)) if creator != *payment.recipient => {
+            pay(royalty_amount, &creator)?;
+            pay(remaining_amount - royalty_amount, payment.recipient)?;
+        }
+        // transfer all funds to recipient
+        _ => pay(remaining_amount, payment.recipient)?,
+    }
+
+    Ok(res_messages)
+}
+
+// the transfer of an amount of the payment token to the recipient,
+// from the sender by allowance, or from the funds held by the contract if the sender is None
+pub fn payment_message(
+    token: &PaymentAsset,
+    amount: Uint128,
+    sender: Option<&Addr>,
+    recipient: &Addr,
+) -> StdResult<CosmosMsg> {
+    let msg = match token {
+        PaymentAsset::Native { denom, .. } => BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: vec![Coin {
+                denom: denom.clone(),
+                amount,
+            }],
+        }
+        .into(),
+        PaymentAsset::Cw20 {
+            contract_address, ..
+        } => {
+            let msg = match sender {
+                // execute cw20 transfer msg from sender to recipient
+                Some(sender) => Cw20ExecuteMsg::TransferFrom {
+                    owner: sender.to_string(),
+                    recipient: recipient.to_string(),
+                    amount,
+                },
+                // execute cw20 transfer msg from this contract to recipient
+                None => Cw20ExecuteMsg::Transfer {
+                    recipient: recipient.to_string(),
+                    amount,
+                },
+            };
+            WasmMsg::Execute {
+                contract_addr: contract_address.to_string(),
+                msg: to_binary(&msg)?,
+                funds: vec![],
+            }
+            .into()
+        }
+    };
+    Ok(msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, MockApi, MockQuerier, MockStorage};
+    use cosmwasm_std::{
+        coins, from_binary, ContractResult, OwnedDeps, SystemError, SystemResult, WasmQuery,
+    };
+
+    const NFT_CONTRACT: &str = "nft_contract";
+    const CREATOR: &str = "creator";
+    const SELLER: &str = "seller";
+    const BUYER: &str = "buyer";
+    const FEE_COLLECTOR: &str = "fee_collector";
+
+    // the nft contract pays a royalty of royalty_percentage to the creator
+    fn deps_with_royalty(
+        royalty_percentage: Option<u64>,
+    ) -> OwnedDeps<MockStorage, MockApi, MockQuerier> {
+        let mut deps = mock_dependencies();
+        deps.querier.update_wasm(move |query| match query {
+            WasmQuery::Smart { contract_addr, msg } if contract_addr == NFT_CONTRACT => {
+                match (royalty_percentage, from_binary(msg)) {
+                    (
+                        Some(percentage),
+                        Ok(Cw2981ContractQueryMsg::Extension {
+                            msg: Cw2981QueryMsg::RoyaltyInfo { sale_price, .. },
+                        }),
+                    ) => SystemResult::Ok(ContractResult::Ok(
+                        to_binary(&RoyaltiesInfoResponse {
+                            address: CREATOR.to_string(),
+                            royalty_amount: sale_price.multiply_ratio(percentage, 100u64),
+                        })
+                        .unwrap(),
+                    )),
+                    _ => SystemResult::Ok(ContractResult::Err("not supported".to_string())),
+                }
+            }
+            _ => SystemResult::Err(SystemError::NoSuchContract {
+                addr: NFT_CONTRACT.to_string(),
+            }),
+        });
+        deps
+    }
+
+    fn native_payment(amount: u128) -> PaymentAsset {
+        PaymentAsset::Native {
+            denom: "uaura".to_string(),
+            amount,
+        }
+    }
+
+    fn bank_send(recipient: &str, amount: u128) -> CosmosMsg {
+        BankMsg::Send {
+            to_address: recipient.to_string(),
+            amount: coins(amount, "uaura"),
+        }
+        .into()
+    }
+
+    fn payment<'a>(
+        token: &'a PaymentAsset,
+        nft_contract_address: &'a Addr,
+        recipient: &'a Addr,
+    ) -> NftPayment<'a> {
+        NftPayment {
+            nft_contract_address,
+            token_id: "1",
+            token,
+            sender: None,
+            recipient,
+            protocol_fee: None,
+        }
+    }
+
+    #[test]
+    fn recipient_is_paid_without_royalty() {
+        let deps = deps_with_royalty(None);
+        let token = native_payment(100);
+        let nft_contract_address = Addr::unchecked(NFT_CONTRACT);
+        let recipient = Addr::unchecked(SELLER);
+
+        let messages = payment_with_royalty(
+            deps.as_ref(),
+            &payment(&token, &nft_contract_address, &recipient),
+        )
+        .unwrap();
+        assert_eq!(messages, vec![bank_send(SELLER, 100)]);
+    }
+
+    #[test]
+    fn creator_is_paid_the_royalty() {
+        let deps = deps_with_royalty(Some(20));
+        let token = native_payment(100);
+        let nft_contract_address = Addr::unchecked(NFT_CONTRACT);
+        let recipient = Addr::unchecked(SELLER);
+
+        let messages = payment_with_royalty(
+            deps.as_ref(),
+            &payment(&token, &nft_contract_address, &recipient),
+        )
+        .unwrap();
+        assert_eq!(
+            messages,
+            vec![bank_send(CREATOR, 20), bank_send(SELLER, 80)]
+        );
+
+        // the creator selling its nft is paid the full amount at once
+        let recipient = Addr::unchecked(CREATOR);
+        let messages = payment_with_royalty(
+            deps.as_ref(),
+            &payment(&token, &nft_contract_address, &recipient),
+        )
+        .unwrap();
+        assert_eq!(messages, vec![bank_send(CREATOR, 100)]);
+    }
+
+    #[test]
+    fn protocol_fee_is_paid_before_the_royalty() {
+        let deps = deps_with_royalty(Some(20));
+        let token = native_payment(100);
+        let nft_contract_address = Addr::unchecked(NFT_CONTRACT);
+        let recipient = Addr::unchecked(SELLER);
+        let fee_collector = Addr::unchecked(FEE_COLLECTOR);
+
+        let messages = payment_with_royalty(
+            deps.as_ref(),
+            &NftPayment {
+                protocol_fee: Some((&fee_collector, Uint128::new(10))),
+                ..payment(&token, &nft_contract_address, &recipient)
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            messages,
+            vec![
+                bank_send(FEE_COLLECTOR, 10),
+                bank_send(CREATOR, 20),
+                bank_send(SELLER, 70)
+            ]
+        );
+
+        // the royalty cannot exceed the amount left after the fee, nothing is left to the seller
+        let messages = payment_with_royalty(
+            deps.as_ref(),
+            &NftPayment {
+                protocol_fee: Some((&fee_collector, Uint128::new(90))),
+                ..payment(&token, &nft_contract_address, &recipient)
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            messages,
+            vec![bank_send(FEE_COLLECTOR, 90), bank_send(CREATOR, 10)]
+        );
+    }
+
+    #[test]
+    fn zero_amounts_are_not_transferred() {
+        let deps = deps_with_royalty(Some(100));
+        let token = native_payment(100);
+        let nft_contract_address = Addr::unchecked(NFT_CONTRACT);
+        let recipient = Addr::unchecked(SELLER);
+        let fee_collector = Addr::unchecked(FEE_COLLECTOR);
+
+        // the royalty takes the full amount, and there is no fee
+        let messages = payment_with_royalty(
+            deps.as_ref(),
+            &NftPayment {
+                protocol_fee: Some((&fee_collector, Uint128::zero())),
+                ..payment(&token, &nft_contract_address, &recipient)
+            },
+        )
+        .unwrap();
+        assert_eq!(messages, vec![bank_send(CREATOR, 100)]);
+
+        // nothing is transferred for a zero price
+        let token = native_payment(0);
+        let messages = payment_with_royalty(
+            deps.as_ref(),
+            &payment(&token, &nft_contract_address, &recipient),
+        )
+        .unwrap();
+        assert!(messages.is_empty());
+    }
+
+    #[test]
+    fn cw20_is_paid_by_allowance_or_from_the_contract() {
+        let token = PaymentAsset::Cw20 {
+            contract_address: Addr::unchecked("cw20"),
+            amount: 100,
+        };
+        let recipient = Addr::unchecked(SELLER);
+        let sender = Addr::unchecked(BUYER);
+
+        let cw20_transfer = |msg: &Cw20ExecuteMsg| -> CosmosMsg {
+            WasmMsg::Execute {
+                contract_addr: "cw20".to_string(),
+                msg: to_binary(msg).unwrap(),
+                funds: vec![],
+            }
+            .into()
+        };
+
+        assert_eq!(
+            payment_message(&token, Uint128::new(100), Some(&sender), &recipient).unwrap(),
+            cw20_transfer(&Cw20ExecuteMsg::TransferFrom {
+                owner: BUYER.to_string(),
+                recipient: SELLER.to_string(),
+                amount: Uint128::new(100),
+            })
+        );
+        assert_eq!(
+            payment_message(&token, Uint128::new(100), None, &recipient).unwrap(),
+            cw20_transfer(&Cw20ExecuteMsg::Transfer {
+                recipient: SELLER.to_string(),
+                amount: Uint128::new(100),
+            })
+        );
+    }
+}